@@ -8,7 +8,7 @@ use serde_json::json;
 use sigma_rs::{
     rule::{rule_from_yaml, RuleHandle},
     tree::builder::build_tree,
-    DynamicEvent,
+    Branch, DynamicEvent, RuleSet,
 };
 use std::hint::black_box;
 use std::path::PathBuf;
@@ -219,5 +219,112 @@ fn benchmark_tree_negative(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, benchmark_tree_positive, benchmark_tree_negative);
+fn benchmark_tree_sync(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let trees = rt.block_on(build_benchmark_trees());
+    let events = create_test_events();
+
+    // Same trees as above, matched without futures or a runtime
+    c.bench_function("tree_positive_sync", |b| {
+        b.iter(|| {
+            for tree in &trees {
+                black_box(tree.root.matches_sync(black_box(&events[0])));
+            }
+        });
+    });
+
+    c.bench_function("tree_negative_sync", |b| {
+        b.iter(|| {
+            for tree in &trees {
+                black_box(tree.root.matches_sync(black_box(&events[3])));
+            }
+        });
+    });
+}
+
+/// Build a ruleset of `copies` copies of the benchmark rules
+async fn build_benchmark_ruleset(copies: usize) -> RuleSet {
+    let mut ruleset = RuleSet::new();
+
+    for copy in 0..copies {
+        for (i, rule_yaml) in create_test_rules().iter().enumerate() {
+            let mut rule = rule_from_yaml(rule_yaml.as_bytes()).unwrap();
+            rule.id = format!("12345678-1234-1234-{:04}-{:012}", copy, i);
+            ruleset.add_rule(rule).await.unwrap();
+        }
+    }
+
+    ruleset
+}
+
+fn benchmark_ruleset_evaluation(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let ruleset = rt.block_on(build_benchmark_ruleset(100));
+    let events = create_test_events();
+
+    let mut group = c.benchmark_group("ruleset_700_rules");
+
+    // One tokio task per rule, as RuleSet::evaluate did before the sync path
+    group.bench_function("spawn_per_rule", |b| {
+        let trees: Vec<_> = rt.block_on(async {
+            let mut trees = Vec::new();
+            for _ in 0..100 {
+                trees.extend(
+                    build_benchmark_trees()
+                        .await
+                        .into_iter()
+                        .map(std::sync::Arc::new),
+                );
+            }
+            trees
+        });
+        b.iter(|| {
+            rt.block_on(async {
+                let event = std::sync::Arc::new(events[0].clone());
+                let tasks: Vec<_> = trees
+                    .iter()
+                    .map(|tree| {
+                        let tree = std::sync::Arc::clone(tree);
+                        let event = std::sync::Arc::clone(&event);
+                        tokio::spawn(async move { tree.match_event(&*event).await })
+                    })
+                    .collect();
+                for task in tasks {
+                    black_box(task.await.unwrap());
+                }
+            });
+        });
+    });
+
+    group.bench_function("evaluate_async", |b| {
+        b.iter(|| rt.block_on(async { black_box(ruleset.evaluate(&events[0]).await.unwrap()) }));
+    });
+
+    group.bench_function("evaluate_sync", |b| {
+        b.iter(|| black_box(ruleset.evaluate_sync(black_box(&events[0]))));
+    });
+
+    // Sharding amortizes thread startup over a batch of events
+    group.bench_function("evaluate_sharded_batch", |b| {
+        b.iter(|| black_box(ruleset.evaluate_sharded(black_box(&events), 4)));
+    });
+
+    group.bench_function("evaluate_sync_batch", |b| {
+        b.iter(|| {
+            for event in &events {
+                black_box(ruleset.evaluate_sync(black_box(event)));
+            }
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    benchmark_tree_positive,
+    benchmark_tree_negative,
+    benchmark_tree_sync,
+    benchmark_ruleset_evaluation
+);
 criterion_main!(benches);
@@ -0,0 +1,388 @@
+#!/usr/bin/env python3
+"""Generate the EVTX sample files in tests/fixtures/evtx.
+
+The samples are written in the layout Windows uses: a file header, 64 KiB
+chunks and event records whose Binary XML instantiates templates with typed
+substitution values. Element names and template definitions are written
+inline on first use and referenced by chunk offset afterwards.
+
+Usage: scripts/generate-evtx-fixtures.py [OUTPUT_DIR]
+"""
+
+import struct
+import sys
+import uuid
+import zlib
+from datetime import datetime, timezone
+from pathlib import Path
+
+CHUNK_SIZE = 0x10000
+FILE_HEADER_SIZE = 0x1000
+CHUNK_HEADER_SIZE = 0x200
+
+# Substitution value types
+STRING, UINT8, UINT16, UINT32, UINT64 = 0x01, 0x04, 0x06, 0x08, 0x0A
+GUID, FILETIME, SID, HEXINT64, BINXML = 0x0F, 0x11, 0x13, 0x15, 0x21
+NULL = 0x00
+
+EVENT_NS = "http://schemas.microsoft.com/win/2004/08/events/event"
+
+
+def filetime(text):
+    """FILETIME ticks for a UTC time with 100ns precision, e.g. 2024-01-01T00:00:00.1234567"""
+    seconds, fraction = text.split(".")
+    moment = datetime.strptime(seconds, "%Y-%m-%dT%H:%M:%S").replace(tzinfo=timezone.utc)
+    delta = moment - datetime(1601, 1, 1, tzinfo=timezone.utc)
+    return (delta.days * 86400 + delta.seconds) * 10_000_000 + int(fraction.ljust(7, "0"))
+
+
+def sid(text):
+    parts = [int(p) for p in text.split("-")[1:]]
+    revision, authority, subs = parts[0], parts[1], parts[2:]
+    return (
+        bytes([revision, len(subs)])
+        + authority.to_bytes(6, "big")
+        + b"".join(struct.pack("<I", s) for s in subs)
+    )
+
+
+def encode_value(value_type, value):
+    if value is None:
+        return b""
+    if value_type == STRING:
+        return value.encode("utf-16-le")
+    if value_type == UINT8:
+        return struct.pack("<B", value)
+    if value_type == UINT16:
+        return struct.pack("<H", value)
+    if value_type == UINT32:
+        return struct.pack("<I", value)
+    if value_type in (UINT64, HEXINT64):
+        return struct.pack("<Q", value)
+    if value_type == GUID:
+        return uuid.UUID(value).bytes_le
+    if value_type == FILETIME:
+        return struct.pack("<Q", filetime(value))
+    if value_type == SID:
+        return sid(value)
+    raise ValueError(f"unsupported value type {value_type:#x}")
+
+
+# Template trees: ("element", name, [(attribute, value)], [children]) where
+# values and children are ("text", str) or ("sub", index, value_type)
+def element(name, attributes=(), children=()):
+    return ("element", name, list(attributes), list(children))
+
+
+def text(value):
+    return ("text", value)
+
+
+def sub(index, value_type, optional=True):
+    return ("sub", index, value_type, optional)
+
+
+def system_template():
+    return element(
+        "System",
+        children=[
+            element("Provider", [("Name", sub(0, STRING)), ("Guid", sub(1, GUID))]),
+            element("EventID", [("Qualifiers", sub(2, UINT16))], [sub(3, UINT16, False)]),
+            element("Version", children=[sub(4, UINT8)]),
+            element("Level", children=[sub(5, UINT8)]),
+            element("Task", children=[sub(6, UINT16)]),
+            element("Opcode", children=[sub(7, UINT8)]),
+            element("Keywords", children=[sub(8, HEXINT64)]),
+            element("TimeCreated", [("SystemTime", sub(9, FILETIME))]),
+            element("EventRecordID", children=[sub(10, UINT64)]),
+            element("Correlation", [("ActivityID", sub(11, GUID))]),
+            element("Execution", [("ProcessID", sub(12, UINT32)), ("ThreadID", sub(13, UINT32))]),
+            element("Channel", children=[sub(14, STRING)]),
+            element("Computer", children=[sub(15, STRING)]),
+            element("Security", [("UserID", sub(16, SID))]),
+        ],
+    )
+
+
+SYSTEM_TYPES = [
+    STRING, GUID, UINT16, UINT16, UINT8, UINT8, UINT16, UINT8, HEXINT64,
+    FILETIME, UINT64, GUID, UINT32, UINT32, STRING, STRING, SID,
+]
+
+
+def event_data_template(names, first_index):
+    return element(
+        "EventData",
+        children=[
+            element("Data", [("Name", text(name))], [sub(first_index + i, value_type)])
+            for i, (name, value_type) in enumerate(names)
+        ],
+    )
+
+
+def event_template(names):
+    return element(
+        "Event",
+        [("xmlns", text(EVENT_NS))],
+        [system_template(), event_data_template(names, len(SYSTEM_TYPES))],
+    )
+
+
+def user_data_template():
+    return element(
+        "Event",
+        [("xmlns", text(EVENT_NS))],
+        [system_template(), element("UserData", children=[sub(len(SYSTEM_TYPES), BINXML)])],
+    )
+
+
+def log_cleared_template():
+    return element(
+        "LogFileCleared",
+        [("xmlns", text("http://manifests.microsoft.com/win/2004/08/windows/eventlog"))],
+        [
+            element("SubjectUserSid", children=[sub(0, SID)]),
+            element("SubjectUserName", children=[sub(1, STRING)]),
+            element("SubjectDomainName", children=[sub(2, STRING)]),
+            element("SubjectLogonId", children=[sub(3, HEXINT64)]),
+        ],
+    )
+
+
+class Chunk:
+    def __init__(self):
+        self.data = bytearray(CHUNK_HEADER_SIZE)
+        self.names = {}
+        self.templates = {}
+        self.records = []
+
+    def pos(self):
+        return len(self.data)
+
+    def put(self, fmt, *values):
+        self.data += struct.pack(fmt, *values)
+
+    def patch(self, offset, fmt, value):
+        struct.pack_into(fmt, self.data, offset, value)
+
+    def name(self, name):
+        offset = self.names.get(name)
+        if offset is not None:
+            self.put("<I", offset)
+            return
+        offset = self.pos() + 4
+        self.names[name] = offset
+        encoded = name.encode("utf-16-le")
+        name_hash = sum(name.encode("utf-16-le")) & 0xFFFF
+        self.put("<IIHH", offset, 0, name_hash, len(name))
+        self.data += encoded + b"\0\0"
+
+    def value_text(self, value):
+        self.put("<BBH", 0x05, STRING, len(value))
+        self.data += value.encode("utf-16-le")
+
+    def content(self, node):
+        if node[0] == "text":
+            self.value_text(node[1])
+        elif node[0] == "sub":
+            _, index, value_type, optional = node
+            self.put("<BHB", 0x0E if optional else 0x0D, index, value_type)
+        else:
+            self.element(node)
+
+    def element(self, node):
+        _, name, attributes, children = node
+        self.put("<BH", 0x41 if attributes else 0x01, 0xFFFF)
+        size_offset = self.pos()
+        self.put("<I", 0)
+        self.name(name)
+        if attributes:
+            attributes_offset = self.pos()
+            self.put("<I", 0)
+            for i, (attribute, value) in enumerate(attributes):
+                self.put("<B", 0x46 if i + 1 < len(attributes) else 0x06)
+                self.name(attribute)
+                self.content(value)
+            self.patch(attributes_offset, "<I", self.pos() - attributes_offset - 4)
+        if children:
+            self.put("<B", 0x02)
+            for child in children:
+                self.content(child)
+            self.put("<B", 0x04)
+        else:
+            self.put("<B", 0x03)
+        self.patch(size_offset, "<I", self.pos() - size_offset - 4)
+
+    def template_instance(self, key, tree, values):
+        """values: [(value_type, value)], BINXML values are (tree, values)"""
+        guid = uuid.uuid5(uuid.NAMESPACE_URL, key).bytes_le
+        self.put("<BB", 0x0C, 0x01)
+        self.data += guid[:4]
+        offset = self.templates.get(key)
+        if offset is None:
+            offset = self.pos() + 4
+            self.templates[key] = offset
+            self.put("<I", offset)
+            self.put("<I", 0)
+            self.data += guid
+            size_offset = self.pos()
+            self.put("<I", 0)
+            self.data += bytes([0x0F, 0x01, 0x01, 0x00])
+            self.element(tree)
+            self.put("<B", 0x00)
+            self.patch(size_offset, "<I", self.pos() - size_offset - 4)
+        else:
+            self.put("<I", offset)
+
+        self.put("<I", len(values))
+        descriptors = self.pos()
+        for value_type, _ in values:
+            self.put("<HBB", 0, value_type, 0)
+        for i, (value_type, value) in enumerate(values):
+            start = self.pos()
+            if value_type == BINXML:
+                nested_key, nested_tree, nested_values = value
+                self.data += bytes([0x0F, 0x01, 0x01, 0x00])
+                self.template_instance(nested_key, nested_tree, nested_values)
+                self.put("<B", 0x00)
+            else:
+                self.data += encode_value(value_type, value)
+            self.patch(descriptors + i * 4, "<H", self.pos() - start)
+
+    def record(self, record_id, written, key, tree, values):
+        start = self.pos()
+        self.data += b"**\0\0"
+        self.put("<IQQ", 0, record_id, filetime(written))
+        self.data += bytes([0x0F, 0x01, 0x01, 0x00])
+        self.template_instance(key, tree, values)
+        self.put("<B", 0x00)
+        size = self.pos() - start + 4
+        self.put("<I", size)
+        self.patch(start + 4, "<I", size)
+        self.records.append((record_id, start))
+
+    def finish(self):
+        assert self.pos() <= CHUNK_SIZE, "chunk overflow"
+        first_id, last_offset = self.records[0][0], self.records[-1][1]
+        last_id = self.records[-1][0]
+        free_space = self.pos()
+        self.data += bytes(CHUNK_SIZE - free_space)
+        header = struct.pack(
+            "<8sQQQQIIII",
+            b"ElfChnk\0", first_id, last_id, first_id, last_id, 128, last_offset, free_space,
+            zlib.crc32(self.data[CHUNK_HEADER_SIZE:free_space]),
+        )
+        self.data[: len(header)] = header
+        checksum = zlib.crc32(self.data[:120] + self.data[128:CHUNK_HEADER_SIZE])
+        self.patch(124, "<I", checksum)
+        return bytes(self.data)
+
+
+def write_file(path, chunks):
+    last_id = max(record_id for chunk in chunks for record_id, _ in chunk.records)
+    header = bytearray(FILE_HEADER_SIZE)
+    struct.pack_into(
+        "<8sQQQIHHHH", header, 0,
+        b"ElfFile\0", 0, len(chunks) - 1, last_id + 1, 128, 2, 3, FILE_HEADER_SIZE, len(chunks),
+    )
+    struct.pack_into("<I", header, 124, zlib.crc32(header[:120]))
+    path.write_bytes(bytes(header) + b"".join(chunk.finish() for chunk in chunks))
+
+
+def system_values(provider, provider_guid, event_id, time, record_id, channel, user, task=0):
+    return [
+        (STRING, provider), (GUID, provider_guid), (NULL, None), (UINT16, event_id),
+        (UINT8, 5), (UINT8, 4), (UINT16, task), (UINT8, 0), (HEXINT64, 0x8000000000000000),
+        (FILETIME, time), (UINT64, record_id), (NULL, None), (UINT32, 3012), (UINT32, 4220),
+        (STRING, channel), (STRING, "WKS01.contoso.local"), (SID, user),
+    ]
+
+
+SYSMON = ("Microsoft-Windows-Sysmon", "5770385f-c22a-43e0-bf4c-06f5698ffbd9")
+SYSMON_CHANNEL = "Microsoft-Windows-Sysmon/Operational"
+PROCESS_CREATE = [
+    ("UtcTime", STRING), ("ProcessGuid", GUID), ("ProcessId", UINT32), ("Image", STRING),
+    ("CommandLine", STRING), ("CurrentDirectory", STRING), ("User", STRING),
+    ("ParentImage", STRING), ("ParentCommandLine", STRING),
+]
+
+
+def process_create(chunk, record_id, time, image, command_line, parent_image, parent_command_line):
+    values = system_values(*SYSMON, 1, time, record_id, SYSMON_CHANNEL, "S-1-5-18", task=1)
+    values += [
+        (STRING, time.replace("T", " ")[:23]),
+        (GUID, "ad8d4a8c-1b2e-65a8-1e01-000000001000"),
+        (UINT32, 6000 + record_id),
+        (STRING, image),
+        (STRING, command_line),
+        (STRING, "C:\\Users\\alice\\"),
+        (STRING, "CONTOSO\\alice"),
+        (STRING, parent_image),
+        (STRING, parent_command_line),
+    ]
+    chunk.record(record_id, time, "sysmon-1", event_template(PROCESS_CREATE), values)
+
+
+def sysmon(output):
+    first = Chunk()
+    process_create(
+        first, 1, "2024-03-05T10:15:30.1234567",
+        "C:\\Windows\\System32\\WindowsPowerShell\\v1.0\\powershell.exe",
+        "powershell.exe -nop -w hidden -enc SQBFAFgAIAAoAE4AZQB3AC0ATwBiAGoA",
+        "C:\\Windows\\System32\\cmd.exe", "cmd.exe /c start.bat",
+    )
+    process_create(
+        first, 2, "2024-03-05T10:15:31.5000000",
+        "C:\\Windows\\System32\\whoami.exe", "whoami /all",
+        "C:\\Windows\\System32\\WindowsPowerShell\\v1.0\\powershell.exe",
+        "powershell.exe -nop -w hidden -enc SQBFAFgAIAAoAE4AZQB3AC0ATwBiAGoA",
+    )
+
+    # Templates and names are chunk-local, so the second chunk redefines them
+    second = Chunk()
+    process_create(
+        second, 3, "2024-03-05T10:16:02.0000000",
+        "C:\\Windows\\System32\\notepad.exe", "notepad.exe C:\\Users\\alice\\notes.txt",
+        "C:\\Windows\\explorer.exe", "C:\\Windows\\Explorer.EXE",
+    )
+    write_file(output / "sysmon.evtx", [first, second])
+
+
+def security(output):
+    chunk = Chunk()
+    logon = [
+        ("SubjectUserSid", SID), ("SubjectUserName", STRING), ("TargetUserSid", SID),
+        ("TargetUserName", STRING), ("TargetDomainName", STRING), ("LogonType", UINT32),
+        ("IpAddress", STRING), ("IpPort", STRING),
+    ]
+    auditing = ("Microsoft-Windows-Security-Auditing", "54849625-5478-4994-a5ba-3e3b0328c30d")
+    values = system_values(*auditing, 4624, "2024-03-05T11:00:00.0000000", 10, "Security", "S-1-5-18")
+    values += [
+        (SID, "S-1-5-18"), (STRING, "WKS01$"),
+        (SID, "S-1-5-21-3623811015-3361044348-30300820-1013"),
+        (STRING, "alice"), (STRING, "CONTOSO"), (UINT32, 10),
+        (STRING, "10.0.0.5"), (STRING, "50122"),
+    ]
+    chunk.record(10, "2024-03-05T11:00:00.0000000", "security-4624", event_template(logon), values)
+
+    eventlog = ("Microsoft-Windows-Eventlog", "fc65ddd8-d6ef-4962-83d5-6e5cfe9ce148")
+    values = system_values(*eventlog, 1102, "2024-03-05T11:05:00.0000000", 11, "Security", "S-1-5-18")
+    values.append(
+        (BINXML, ("log-cleared", log_cleared_template(), [
+            (SID, "S-1-5-21-3623811015-3361044348-30300820-1013"),
+            (STRING, "alice"), (STRING, "CONTOSO"), (HEXINT64, 0x3E7A1),
+        ]))
+    )
+    chunk.record(11, "2024-03-05T11:05:00.0000000", "eventlog-1102", user_data_template(), values)
+    write_file(output / "security.evtx", [chunk])
+
+
+def main():
+    output = Path(sys.argv[1]) if len(sys.argv) > 1 else Path(__file__).parent.parent / "tests/fixtures/evtx"
+    output.mkdir(parents=True, exist_ok=True)
+    sysmon(output)
+    security(output)
+
+
+if __name__ == "__main__":
+    main()
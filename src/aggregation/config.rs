@@ -11,6 +11,14 @@ pub struct AggregationConfig {
     pub max_cache_size: u64,
     /// Maximum memory usage in bytes for the cache
     pub max_cache_memory: Option<u64>,
+    /// Maximum number of events kept in one group's `timeframe` window
+    ///
+    /// The oldest events are dropped beyond it, so `count()` never exceeds this value.
+    pub max_window_entries_per_group: usize,
+    /// Maximum number of distinct values tracked by one group's `count(field)`
+    ///
+    /// The least recently seen values are dropped beyond it.
+    pub max_distinct_values_per_group: usize,
 }
 
 impl Default for AggregationConfig {
@@ -20,6 +28,8 @@ impl Default for AggregationConfig {
             cleanup_interval: Duration::from_secs(60), // 1 minute
             max_cache_size: 10_000,                    // Limit to 10K groups
             max_cache_memory: Some(100 * 1024 * 1024), // 100MB limit
+            max_window_entries_per_group: 10_000,
+            max_distinct_values_per_group: 10_000,
         }
     }
 }
@@ -1,12 +1,16 @@
-use super::{AggregationConfig, AggregationResult, AggregationStatistics};
+use super::{AggregationConfig, AggregationFunction, AggregationResult, AggregationStatistics};
 use crate::ast::nodes::NodeAggregation;
+use crate::ast::value_to_json;
 use crate::event::Event;
+use crate::pattern::coercion::{coerce_for_number, NumericValue};
 use chrono::{DateTime, Utc};
-use moka::future::Cache;
+use moka::sync::Cache;
 use parking_lot::RwLock;
 use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
+use tracing::warn;
 
 /// An evaluator that performs aggregation operations based on configured rules.
 ///
@@ -16,9 +20,9 @@ use std::sync::Arc;
 /// * `cache` - A cache storing group states indexed by string keys
 /// * `config` - Configuration parameters for the aggregation
 /// * `stats` - Thread-safe statistics tracking during evaluation
+#[derive(Debug)]
 pub struct AggregationEvaluator {
     cache: Cache<String, Arc<RwLock<GroupState>>>,
-    #[allow(dead_code)] // TODO: Use config for customizing aggregation behavior
     config: AggregationConfig,
     stats: Arc<Stats>,
 }
@@ -30,6 +34,153 @@ struct GroupState {
     min: f64,
     max: f64,
     last_update: DateTime<Utc>,
+    /// Distinct values seen for `count(field)`, with the time each was last seen
+    distinct: HashMap<String, DateTime<Utc>>,
+    /// Distinct values in the order they were last seen, oldest first
+    ///
+    /// A value seen again is re-queued; its earlier entries are stale and
+    /// skipped when they reach the front.
+    distinct_order: VecDeque<(DateTime<Utc>, String)>,
+    /// Per-event values kept while a `timeframe` bounds the aggregation, oldest first
+    window: VecDeque<WindowEntry>,
+    /// Whether hitting a capacity limit has already been logged for this group
+    cap_logged: bool,
+}
+
+#[derive(Debug)]
+struct WindowEntry {
+    timestamp: DateTime<Utc>,
+    value: f64,
+}
+
+impl GroupState {
+    fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            count: 0,
+            sum: 0.0,
+            min: f64::MAX,
+            max: f64::MIN,
+            last_update: now,
+            distinct: HashMap::new(),
+            distinct_order: VecDeque::new(),
+            window: VecDeque::new(),
+            cap_logged: false,
+        }
+    }
+
+    /// Drop entries and distinct values older than `window_start`
+    fn evict_before(&mut self, window_start: DateTime<Utc>) {
+        while self
+            .window
+            .front()
+            .is_some_and(|entry| entry.timestamp < window_start)
+        {
+            self.window.pop_front();
+        }
+        while self
+            .distinct_order
+            .front()
+            .is_some_and(|(seen, _)| *seen < window_start)
+        {
+            self.pop_distinct();
+        }
+    }
+
+    /// Remove the front of the distinct queue, dropping the value if the entry is current
+    ///
+    /// Returns whether a value was dropped.
+    fn pop_distinct(&mut self) -> bool {
+        let Some((seen, value)) = self.distinct_order.pop_front() else {
+            return false;
+        };
+        if self.distinct.get(&value) == Some(&seen) {
+            self.distinct.remove(&value);
+            return true;
+        }
+        false
+    }
+
+    /// Record a distinct value seen at `now`, evicting the least recently seen value at capacity
+    ///
+    /// Returns whether a value was evicted.
+    fn insert_distinct(&mut self, value: String, now: DateTime<Utc>, capacity: usize) -> bool {
+        let mut evicted = false;
+        match self.distinct.get_mut(&value) {
+            Some(seen) if *seen >= now => return false,
+            Some(seen) => *seen = now,
+            None => {
+                while self.distinct.len() >= capacity && !self.distinct_order.is_empty() {
+                    evicted |= self.pop_distinct();
+                }
+                self.distinct.insert(value.clone(), now);
+            }
+        }
+
+        // Event time may arrive out of order, so keep the queue sorted by it
+        let position = self
+            .distinct_order
+            .partition_point(|(seen, _)| *seen <= now);
+        self.distinct_order.insert(position, (now, value));
+
+        // Re-seen values leave stale entries behind; compact before they dominate
+        if self.distinct_order.len() > capacity.saturating_mul(2).max(16) {
+            let distinct = &self.distinct;
+            self.distinct_order
+                .retain(|(seen, value)| distinct.get(value) == Some(seen));
+        }
+        evicted
+    }
+
+    /// Insert a window entry in time order, dropping the oldest at capacity
+    ///
+    /// Returns whether an entry was dropped.
+    fn push_window(&mut self, entry: WindowEntry, capacity: usize) -> bool {
+        let mut evicted = false;
+        while self.window.len() >= capacity && self.window.pop_front().is_some() {
+            evicted = true;
+        }
+        let position = self
+            .window
+            .partition_point(|existing| existing.timestamp <= entry.timestamp);
+        self.window.insert(position, entry);
+        evicted
+    }
+
+    fn windowed_value(&self, function: &AggregationFunction) -> f64 {
+        let values = self.window.iter().map(|e| e.value);
+        match function {
+            AggregationFunction::Count => self.window.len() as f64,
+            AggregationFunction::CountDistinct(_) => self.distinct.len() as f64,
+            AggregationFunction::Sum(_) => values.sum(),
+            AggregationFunction::Average(_) => {
+                if self.window.is_empty() {
+                    0.0
+                } else {
+                    values.sum::<f64>() / self.window.len() as f64
+                }
+            }
+            AggregationFunction::Min(_) => values.fold(f64::MAX, f64::min),
+            AggregationFunction::Max(_) => values.fold(f64::MIN, f64::max),
+        }
+    }
+
+    /// Current value of an unwindowed aggregation, without recording an event
+    fn running_value(&self, function: &AggregationFunction) -> f64 {
+        match function {
+            AggregationFunction::Count => self.count as f64,
+            AggregationFunction::CountDistinct(_) => self.distinct.len() as f64,
+            AggregationFunction::Sum(_) => self.sum,
+            AggregationFunction::Average(_) => {
+                if self.count > 0 {
+                    self.sum / self.count as f64
+                } else {
+                    0.0
+                }
+            }
+            AggregationFunction::Min(_) => self.min,
+            AggregationFunction::Max(_) => self.max,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -76,93 +227,146 @@ impl AggregationEvaluator {
         }
     }
 
+    /// Get the configuration this evaluator was created with
+    pub fn config(&self) -> &AggregationConfig {
+        &self.config
+    }
+
     /// Evaluate an aggregation node against an event
+    ///
+    /// Kept for compatibility; delegates to [`evaluate_sync`](Self::evaluate_sync).
     pub async fn evaluate(&self, node: &NodeAggregation, event: &dyn Event) -> AggregationResult {
+        self.evaluate_sync(node, event)
+    }
+
+    /// Evaluate an aggregation node against an event, without awaiting
+    ///
+    /// Events lacking the `by` field, or a numeric value for a `sum`, `avg`,
+    /// `min` or `max` field, leave every group untouched and never trigger. A group that triggers
+    /// starts over, so each result covers a new set of events.
+    pub fn evaluate_sync(&self, node: &NodeAggregation, event: &dyn Event) -> AggregationResult {
         // Increment evaluation counter atomically - no lock needed
         self.stats.total_evaluations.fetch_add(1, Ordering::Relaxed);
 
-        // Extract group key
+        // Windows follow event time so replayed logs aggregate as they happened
+        let now = event.event_time().unwrap_or_else(Utc::now);
+
+        // Extract group key; events without the group field belong to no group
         let group_key = match &node.by_field {
             Some(field) => match event.select(field) {
+                (Some(crate::event::Value::Null), _) | (None, _) => {
+                    return AggregationResult {
+                        triggered: false,
+                        value: 0.0,
+                        group: None,
+                        timestamp: now,
+                    };
+                }
                 (Some(value), _) => format!("{}:{}", field, value_to_cow(&value)),
-                _ => format!("{}:unknown", field),
             },
             None => "default".to_string(),
         };
 
-        // Get or create group state
-        let state = match self
-            .cache
-            .try_get_with(group_key.clone(), async {
-                Ok(Arc::new(RwLock::new(GroupState {
-                    count: 0,
-                    sum: 0.0,
-                    min: f64::MAX,
-                    max: f64::MIN,
-                    last_update: Utc::now(),
-                })))
-                    as std::result::Result<Arc<RwLock<GroupState>>, std::convert::Infallible>
-            })
-            .await
-        {
-            Ok(state) => state,
-            Err(_) => {
-                // This should never happen with Infallible, but handle gracefully
-                tracing::error!("Cache operation failed unexpectedly");
-                return AggregationResult {
-                    triggered: false,
-                    value: 0.0,
-                    group: Some(group_key),
-                    timestamp: Utc::now(),
-                };
-            }
+        // The value this event contributes, if it contributes one
+        let sample = match &node.function {
+            AggregationFunction::Count | AggregationFunction::CountDistinct(_) => Some(1.0),
+            AggregationFunction::Sum(field)
+            | AggregationFunction::Average(field)
+            | AggregationFunction::Min(field)
+            | AggregationFunction::Max(field) => extract_numeric_value(event, field),
         };
 
+        // Get or create group state
+        let state = self.cache.get_with(group_key.clone(), || {
+            Arc::new(RwLock::new(GroupState::new(now)))
+        });
+
         let mut state_guard = state.write();
 
-        // Update aggregation based on function
-        let current_value = match &node.function {
-            crate::aggregation::AggregationFunction::Count => {
-                state_guard.count += 1;
-                state_guard.count as f64
-            }
-            crate::aggregation::AggregationFunction::Sum(field) => {
-                let value = extract_numeric_value(event, field);
-                state_guard.sum += value;
-                state_guard.sum
+        let Some(sample) = sample else {
+            let value = if node.time_window.is_some() {
+                state_guard.windowed_value(&node.function)
+            } else {
+                state_guard.running_value(&node.function)
+            };
+            return AggregationResult {
+                triggered: false,
+                value,
+                group: Some(group_key),
+                timestamp: now,
+            };
+        };
+
+        let mut capped = false;
+
+        // Distinct values are tracked for count(field) regardless of windowing
+        if let AggregationFunction::CountDistinct(field) = &node.function {
+            if let (Some(value), _) = event.select(field) {
+                capped |= state_guard.insert_distinct(
+                    value_to_cow(&value).into_owned(),
+                    now,
+                    self.config.max_distinct_values_per_group,
+                );
             }
-            crate::aggregation::AggregationFunction::Average(field) => {
-                let value = extract_numeric_value(event, field);
-                state_guard.sum += value;
-                state_guard.count += 1;
-                if state_guard.count > 0 {
-                    state_guard.sum / state_guard.count as f64
-                } else {
-                    0.0
+        }
+
+        // Update aggregation based on function
+        let current_value = if let Some(window) = node.time_window {
+            capped |= state_guard.push_window(
+                WindowEntry {
+                    timestamp: now,
+                    value: sample,
+                },
+                self.config.max_window_entries_per_group,
+            );
+            // The window ends at the newest event time seen for the group
+            let watermark = state_guard.last_update.max(now);
+            let window_start = watermark - chrono::Duration::from_std(window).unwrap_or_default();
+            state_guard.evict_before(window_start);
+            state_guard.windowed_value(&node.function)
+        } else {
+            match &node.function {
+                AggregationFunction::Count => state_guard.count += 1,
+                AggregationFunction::CountDistinct(_) => {}
+                AggregationFunction::Sum(_) => state_guard.sum += sample,
+                AggregationFunction::Average(_) => {
+                    state_guard.sum += sample;
+                    state_guard.count += 1;
                 }
+                AggregationFunction::Min(_) => state_guard.min = state_guard.min.min(sample),
+                AggregationFunction::Max(_) => state_guard.max = state_guard.max.max(sample),
             }
-            crate::aggregation::AggregationFunction::Min(field) => {
-                let value = extract_numeric_value(event, field);
-                state_guard.min = state_guard.min.min(value);
-                state_guard.min
-            }
-            crate::aggregation::AggregationFunction::Max(field) => {
-                let value = extract_numeric_value(event, field);
-                state_guard.max = state_guard.max.max(value);
-                state_guard.max
-            }
+            state_guard.running_value(&node.function)
         };
 
-        state_guard.last_update = Utc::now();
+        if capped && !state_guard.cap_logged {
+            state_guard.cap_logged = true;
+            warn!(
+                "Aggregation group '{}' reached its capacity ({} window entries, {} distinct values); \
+                 oldest entries are evicted and counts above the limit cannot be reached",
+                group_key,
+                self.config.max_window_entries_per_group,
+                self.config.max_distinct_values_per_group
+            );
+        }
+
+        state_guard.last_update = state_guard.last_update.max(now);
 
         // Check if threshold is met using proper comparison
         let triggered = node.comparison.evaluate(current_value, node.threshold);
 
+        if triggered {
+            // Start the group over under the lock, so concurrent events land in the new set
+            let cap_logged = state_guard.cap_logged;
+            *state_guard = GroupState::new(state_guard.last_update);
+            state_guard.cap_logged = cap_logged;
+        }
+
         AggregationResult {
             triggered,
             value: current_value,
             group: Some(group_key),
-            timestamp: Utc::now(),
+            timestamp: now,
         }
     }
 
@@ -189,18 +393,18 @@ impl AggregationEvaluator {
     }
 }
 
-fn extract_numeric_value(event: &dyn Event, field: &str) -> f64 {
-    match event.select(field) {
-        (Some(value), _) => match value {
-            crate::event::Value::Float(f) => f,
-            crate::event::Value::Integer(i) => i as f64,
-            _ => 0.0,
-        },
-        _ => 0.0,
+/// Read a field as a number, coercing numeric strings like the comparison modifiers do
+fn extract_numeric_value(event: &dyn Event, field: &str) -> Option<f64> {
+    let (Some(value), _) = event.select(field) else {
+        return None;
+    };
+    match coerce_for_number(&value_to_json(value))? {
+        NumericValue::Integer(i) => Some(i as f64),
+        NumericValue::Float(f) => Some(f),
     }
 }
 
-fn value_to_cow(value: &crate::event::Value) -> Cow<'_, str> {
+pub(crate) fn value_to_cow(value: &crate::event::Value) -> Cow<'_, str> {
     match value {
         crate::event::Value::String(s) => Cow::Borrowed(s),
         crate::event::Value::Integer(i) => Cow::Owned(i.to_string()),
@@ -419,6 +623,229 @@ mod tests {
         assert_eq!(result4.value, 35.0);
     }
 
+    #[tokio::test]
+    async fn test_aggregation_count_distinct() {
+        let evaluator = AggregationEvaluator::new();
+        let node = NodeAggregation {
+            function: AggregationFunction::CountDistinct("user".to_string()),
+            by_field: None,
+            time_window: None,
+            comparison: ComparisonOp::GreaterOrEqual,
+            threshold: 2.0,
+        };
+
+        let alice = TestEvent::new().with_field("user", Value::String(Arc::from("alice")));
+        let bob = TestEvent::new().with_field("user", Value::String(Arc::from("bob")));
+
+        assert_eq!(evaluator.evaluate(&node, &alice).await.value, 1.0);
+        let repeated = evaluator.evaluate(&node, &alice).await;
+        assert!(!repeated.triggered);
+        assert_eq!(repeated.value, 1.0);
+
+        let result = evaluator.evaluate(&node, &bob).await;
+        assert!(result.triggered);
+        assert_eq!(result.value, 2.0);
+    }
+
+    #[tokio::test]
+    async fn test_aggregation_state_is_bounded() {
+        let distinct = NodeAggregation {
+            function: AggregationFunction::CountDistinct("user".to_string()),
+            by_field: None,
+            time_window: None,
+            comparison: ComparisonOp::GreaterThan,
+            threshold: f64::MAX,
+        };
+        let count = NodeAggregation {
+            function: AggregationFunction::Count,
+            by_field: None,
+            time_window: Some(std::time::Duration::from_secs(3600)),
+            comparison: ComparisonOp::GreaterThan,
+            threshold: f64::MAX,
+        };
+        let config = AggregationConfig {
+            max_window_entries_per_group: 100,
+            max_distinct_values_per_group: 50,
+            ..AggregationConfig::default()
+        };
+        let distinct_evaluator = AggregationEvaluator::with_config(config.clone());
+        let count_evaluator = AggregationEvaluator::with_config(config);
+
+        let total = 110;
+        for i in 0..total {
+            let event =
+                crate::event::DynamicEvent::new(serde_json::json!({"user": format!("user{}", i)}))
+                    .with_timestamp(1_700_000_000 + i as i64);
+            distinct_evaluator.evaluate_sync(&distinct, &event);
+            // Re-seeing a value keeps it from being the least recently seen
+            if i % 10 == 0 {
+                let again = crate::event::DynamicEvent::new(serde_json::json!({"user": "user0"}))
+                    .with_timestamp(1_700_000_000 + i as i64);
+                distinct_evaluator.evaluate_sync(&distinct, &again);
+            }
+            // A burst inside one second never leaves the window by time alone
+            count_evaluator.evaluate_sync(&count, &event.with_timestamp(1_700_000_000));
+        }
+
+        let state = distinct_evaluator.cache.get("default").unwrap();
+        let state = state.read();
+        assert_eq!(state.distinct.len(), 50);
+        assert!(state.distinct.contains_key("user0"));
+        assert!(!state.distinct.contains_key("user1"));
+        assert!(state.distinct.contains_key(&format!("user{}", total - 1)));
+        assert!(state.distinct_order.len() <= 100);
+        assert!(state.cap_logged);
+
+        let state = count_evaluator.cache.get("default").unwrap();
+        assert_eq!(state.read().window.len(), 100);
+    }
+
+    #[tokio::test]
+    async fn test_aggregation_window_eviction_out_of_order() {
+        let evaluator = AggregationEvaluator::new();
+        let node = NodeAggregation {
+            function: AggregationFunction::Count,
+            by_field: None,
+            time_window: Some(std::time::Duration::from_secs(60)),
+            comparison: ComparisonOp::GreaterThan,
+            threshold: 100.0,
+        };
+        let at = |offset: i64| {
+            crate::event::DynamicEvent::new(serde_json::json!({}))
+                .with_timestamp(1_700_000_000 + offset)
+        };
+
+        evaluator.evaluate_sync(&node, &at(30));
+        evaluator.evaluate_sync(&node, &at(0));
+        evaluator.evaluate_sync(&node, &at(50));
+
+        // Entries stay in event-time order, so eviction stops at the first one in the window
+        let result = evaluator.evaluate_sync(&node, &at(85));
+        assert_eq!(result.value, 3.0);
+        let state = evaluator.cache.get("default").unwrap();
+        let timestamps: Vec<i64> = state
+            .read()
+            .window
+            .iter()
+            .map(|entry| entry.timestamp.timestamp() - 1_700_000_000)
+            .collect();
+        assert_eq!(timestamps, vec![30, 50, 85]);
+    }
+
+    #[tokio::test]
+    async fn test_aggregation_resets_after_trigger() {
+        let evaluator = AggregationEvaluator::new();
+        let node = NodeAggregation {
+            function: AggregationFunction::Count,
+            by_field: Some("user".to_string()),
+            time_window: Some(std::time::Duration::from_secs(60)),
+            comparison: ComparisonOp::GreaterOrEqual,
+            threshold: 2.0,
+        };
+        let alice = TestEvent::new().with_field("user", Value::String(Arc::from("alice")));
+
+        assert!(!evaluator.evaluate(&node, &alice).await.triggered);
+        assert!(evaluator.evaluate(&node, &alice).await.triggered);
+
+        // The next event starts a new window instead of alerting again
+        let result = evaluator.evaluate(&node, &alice).await;
+        assert!(!result.triggered);
+        assert_eq!(result.value, 1.0);
+        assert!(evaluator.evaluate(&node, &alice).await.triggered);
+
+        // The group keeps its state object, so holders of the old one are not orphaned
+        let before = evaluator.cache.get("user:alice").unwrap();
+        evaluator.evaluate(&node, &alice).await;
+        let after = evaluator.cache.get("user:alice").unwrap();
+        assert!(Arc::ptr_eq(&before, &after));
+        assert_eq!(after.read().window.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_aggregation_skips_events_without_group_field() {
+        let evaluator = AggregationEvaluator::new();
+        let node = NodeAggregation {
+            function: AggregationFunction::Count,
+            by_field: Some("SourceIp".to_string()),
+            time_window: None,
+            comparison: ComparisonOp::GreaterOrEqual,
+            threshold: 2.0,
+        };
+        let missing = TestEvent::new();
+        let null = TestEvent::new().with_field("SourceIp", Value::Null);
+        let unknown = TestEvent::new().with_field("SourceIp", Value::String(Arc::from("unknown")));
+
+        for event in [&missing, &null, &missing] {
+            let result = evaluator.evaluate(&node, event).await;
+            assert!(!result.triggered);
+            assert_eq!(result.group, None);
+        }
+
+        // A literal "unknown" is an ordinary group that missing fields never joined
+        let result = evaluator.evaluate(&node, &unknown).await;
+        assert!(!result.triggered);
+        assert_eq!(result.value, 1.0);
+        evaluator.cache.run_pending_tasks();
+        assert_eq!(evaluator.cache.entry_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_aggregation_time_window_expiry() {
+        let evaluator = AggregationEvaluator::new();
+        let node = NodeAggregation {
+            function: AggregationFunction::Count,
+            by_field: None,
+            time_window: Some(std::time::Duration::from_millis(50)),
+            comparison: ComparisonOp::GreaterOrEqual,
+            threshold: 2.0,
+        };
+
+        let event = TestEvent::new();
+        assert_eq!(evaluator.evaluate(&node, &event).await.value, 1.0);
+
+        // The first event falls out of the window before the second arrives
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let result = evaluator.evaluate(&node, &event).await;
+        assert!(!result.triggered);
+        assert_eq!(result.value, 1.0);
+
+        let result = evaluator.evaluate(&node, &event).await;
+        assert!(result.triggered);
+        assert_eq!(result.value, 2.0);
+    }
+
+    #[tokio::test]
+    async fn test_aggregation_uses_event_time() {
+        let evaluator = AggregationEvaluator::new();
+        let node = NodeAggregation {
+            function: AggregationFunction::Count,
+            by_field: None,
+            time_window: Some(std::time::Duration::from_secs(300)),
+            comparison: ComparisonOp::GreaterOrEqual,
+            threshold: 3.0,
+        };
+
+        // Historical events replayed back to back aggregate by their own time
+        let at = |offset: i64| {
+            crate::event::DynamicEvent::new(serde_json::json!({}))
+                .with_timestamp(1_700_000_000 + offset)
+        };
+
+        assert_eq!(evaluator.evaluate(&node, &at(0)).await.value, 1.0);
+        assert_eq!(evaluator.evaluate(&node, &at(60)).await.value, 2.0);
+        // Ten minutes later both earlier events have left the window
+        let result = evaluator.evaluate(&node, &at(600)).await;
+        assert!(!result.triggered);
+        assert_eq!(result.value, 1.0);
+        assert_eq!(result.timestamp.timestamp(), 1_700_000_600);
+
+        // A late event within the window still counts
+        let result = evaluator.evaluate(&node, &at(590)).await;
+        assert_eq!(result.value, 2.0);
+        let result = evaluator.evaluate(&node, &at(610)).await;
+        assert!(result.triggered);
+    }
+
     #[tokio::test]
     async fn test_aggregation_cache_eviction() {
         // Test with small cache to force eviction
@@ -427,6 +854,7 @@ mod tests {
             cleanup_interval: std::time::Duration::from_secs(1),
             max_cache_size: 2, // Very small cache
             max_cache_memory: None,
+            ..AggregationConfig::default()
         };
 
         let evaluator = AggregationEvaluator::with_config(config);
@@ -479,7 +907,7 @@ mod tests {
         }
 
         // Run cache maintenance to ensure entries are properly counted
-        evaluator.cache.run_pending_tasks().await;
+        evaluator.cache.run_pending_tasks();
 
         let stats = evaluator.get_statistics().await;
         assert_eq!(stats.total_evaluations, 10);
@@ -504,7 +932,56 @@ mod tests {
         // Should handle missing field gracefully
         let result = evaluator.evaluate(&node, &event).await;
         assert!(!result.triggered);
-        assert_eq!(result.value, 0.0); // Default value for missing field
+        assert_eq!(result.value, 0.0); // Nothing has been summed
+    }
+
+    #[tokio::test]
+    async fn test_aggregation_skips_non_numeric_values() {
+        let evaluator = AggregationEvaluator::new();
+        let node = NodeAggregation {
+            function: AggregationFunction::Min("temp".to_string()),
+            by_field: None,
+            time_window: Some(std::time::Duration::from_secs(60)),
+            comparison: ComparisonOp::LessThan,
+            threshold: 10.0,
+        };
+
+        // Events without a usable value neither trigger nor drag the minimum to zero
+        let missing = TestEvent::new();
+        let text = TestEvent::new().with_field("temp", Value::String(Arc::from("cold")));
+        assert!(!evaluator.evaluate(&node, &missing).await.triggered);
+        assert!(!evaluator.evaluate(&node, &text).await.triggered);
+
+        let warm = TestEvent::new().with_field("temp", Value::String(Arc::from("15")));
+        let result = evaluator.evaluate(&node, &warm).await;
+        assert!(!result.triggered);
+        assert_eq!(result.value, 15.0);
+        assert!(!evaluator.evaluate(&node, &missing).await.triggered);
+
+        // Numeric strings are coerced like the comparison modifiers do
+        let cold = TestEvent::new().with_field("temp", Value::String(Arc::from(" 4.5 ")));
+        let result = evaluator.evaluate(&node, &cold).await;
+        assert!(result.triggered);
+        assert_eq!(result.value, 4.5);
+    }
+
+    #[tokio::test]
+    async fn test_aggregation_average_ignores_missing_values() {
+        let evaluator = AggregationEvaluator::new();
+        let node = NodeAggregation {
+            function: AggregationFunction::Average("score".to_string()),
+            by_field: None,
+            time_window: None,
+            comparison: ComparisonOp::LessThan,
+            threshold: 50.0,
+        };
+
+        assert!(!evaluator.evaluate(&node, &TestEvent::new()).await.triggered);
+        let event = TestEvent::new().with_field("score", Value::Integer(80));
+        assert_eq!(evaluator.evaluate(&node, &event).await.value, 80.0);
+        let result = evaluator.evaluate(&node, &TestEvent::new()).await;
+        assert!(!result.triggered);
+        assert_eq!(result.value, 80.0);
     }
 
     #[tokio::test]
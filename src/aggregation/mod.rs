@@ -16,6 +16,8 @@ pub use sliding_window::SlidingWindow;
 pub enum AggregationFunction {
     /// Count aggregation
     Count,
+    /// Count of distinct values of a field
+    CountDistinct(String),
     /// Sum aggregation over a field
     Sum(String),
     /// Average aggregation over a field
@@ -1,3 +1,4 @@
+use crate::event::Event;
 use chrono::{DateTime, Utc};
 use parking_lot::RwLock;
 use std::collections::VecDeque;
@@ -18,7 +19,7 @@ struct WindowInner {
     entries: VecDeque<WindowEntry>,
     /// Cached aggregated value
     cached_value: f64,
-    /// Last update timestamp
+    /// Newest event time seen
     last_update: DateTime<Utc>,
     /// Whether cache is valid
     cache_valid: bool,
@@ -45,15 +46,28 @@ impl SlidingWindow {
     }
 
     /// Add a value to the sliding window at the specified timestamp
+    ///
+    /// Timestamps are event times and may arrive out of order; the window
+    /// ends at the newest timestamp seen so far.
     pub fn add_value(&self, value: f64, timestamp: DateTime<Utc>) {
         let mut inner = self.inner.write();
 
-        // Add new entry
-        inner.entries.push_back(WindowEntry { value, timestamp });
+        // Keep entries ordered by event time
+        let position = inner
+            .entries
+            .partition_point(|entry| entry.timestamp <= timestamp);
+        inner
+            .entries
+            .insert(position, WindowEntry { value, timestamp });
+
+        let watermark = match inner.entries.back() {
+            Some(newest) => newest.timestamp,
+            None => timestamp,
+        };
 
         // Remove entries outside the window
         let window_start =
-            timestamp - chrono::Duration::from_std(self.duration).unwrap_or_default();
+            watermark - chrono::Duration::from_std(self.duration).unwrap_or_default();
         while let Some(front) = inner.entries.front() {
             if front.timestamp >= window_start {
                 break;
@@ -63,7 +77,12 @@ impl SlidingWindow {
 
         // Invalidate cache since we added new data
         inner.cache_valid = false;
-        inner.last_update = timestamp;
+        inner.last_update = watermark;
+    }
+
+    /// Add a value at the event's own time, falling back to processing time
+    pub fn add_event_value(&self, value: f64, event: &dyn Event) {
+        self.add_value(value, event.event_time().unwrap_or_else(Utc::now));
     }
 
     /// Get current aggregated value in the window
@@ -141,9 +160,10 @@ impl SlidingWindow {
         self.duration
     }
 
-    /// Compact the window by removing expired entries based on current time
+    /// Compact the window by removing entries expired as of the newest event time seen
     pub fn compact(&self) {
-        self.compact_at_time(Utc::now())
+        let watermark = self.inner.read().last_update;
+        self.compact_at_time(watermark)
     }
 
     /// Compact the window by removing entries expired as of the given time
@@ -312,4 +332,31 @@ mod tests {
         assert_eq!(window.entry_count(), 0);
         assert_eq!(window.get_current_value(), 0.0);
     }
+
+    #[test]
+    fn test_sliding_window_out_of_order_event_time() {
+        let window = SlidingWindow::new(Duration::from_secs(10));
+        let base_time = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+
+        window.add_value(1.0, base_time + chrono::Duration::seconds(8));
+        // A late event still inside the window is kept in order
+        window.add_value(2.0, base_time + chrono::Duration::seconds(2));
+        assert_eq!(
+            window.time_range(),
+            Some((
+                base_time + chrono::Duration::seconds(2),
+                base_time + chrono::Duration::seconds(8)
+            ))
+        );
+
+        // A late event older than the window is dropped immediately
+        window.add_value(4.0, base_time + chrono::Duration::seconds(20));
+        window.add_value(8.0, base_time + chrono::Duration::seconds(5));
+        assert_eq!(window.get_current_value(), 4.0);
+
+        // Compaction follows event time, not the wall clock
+        window.add_value(16.0, base_time + chrono::Duration::seconds(15));
+        window.compact();
+        assert_eq!(window.entry_count(), 2);
+    }
 }
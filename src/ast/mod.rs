@@ -1,5 +1,8 @@
+use crate::error::SigmaError;
 use crate::event::{Event, Value};
-use crate::pattern::coercion::{coerce_for_numeric_match, coerce_for_string_match};
+use crate::pattern::coercion::{
+    coerce_for_number, coerce_for_string_match, coerce_for_timestamp, NumericValue, TimePart,
+};
 use async_trait::async_trait;
 use std::fmt::Debug;
 use tracing::warn;
@@ -9,7 +12,7 @@ pub mod nodes;
 pub use nodes::*;
 
 /// Convert our Value type to serde_json::Value for pattern matching
-fn value_to_json(value: Value) -> serde_json::Value {
+pub(crate) fn value_to_json(value: Value) -> serde_json::Value {
     match value {
         Value::String(s) => serde_json::Value::String(s.to_string()),
         Value::Integer(i) => serde_json::Value::Number(serde_json::Number::from(i)),
@@ -33,6 +36,11 @@ fn value_to_json(value: Value) -> serde_json::Value {
 }
 
 /// Result of a match operation
+///
+/// A value pattern on a field missing from the event is not applicable and
+/// does not match. Existence patterns (`|exists`, `null`) always apply, and a
+/// negation is applicable because a missing field definitely fails the
+/// negated selection.
 #[derive(Debug, Clone, PartialEq)]
 pub struct MatchResult {
     /// Whether the match was successful
@@ -75,6 +83,60 @@ impl MatchResult {
     }
 }
 
+/// Why a branch matched an event
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Explanation {
+    /// The satisfied part of the condition, e.g. `(selection AND NOT filter)`
+    pub condition: String,
+    /// Detection identifiers whose selections matched, in evaluation order
+    pub identifiers: Vec<String>,
+    /// Field patterns that matched, with the event values they matched
+    pub fields: Vec<FieldMatch>,
+}
+
+/// A field pattern that matched an event value
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FieldMatch {
+    /// Field name
+    pub field: String,
+    /// Description of the pattern, as in `pattern_desc`
+    pub pattern: String,
+    /// Value of the field in the event
+    pub value: serde_json::Value,
+}
+
+impl Explanation {
+    /// Create an explanation holding only a satisfied condition
+    pub fn new(condition: impl Into<String>) -> Self {
+        Self {
+            condition: condition.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Combine the explanations of branches that all matched
+    pub fn all(explanations: impl IntoIterator<Item = Explanation>) -> Self {
+        let mut combined = Self::default();
+        let mut conditions = Vec::new();
+
+        for explanation in explanations {
+            conditions.push(explanation.condition);
+            for identifier in explanation.identifiers {
+                if !combined.identifiers.contains(&identifier) {
+                    combined.identifiers.push(identifier);
+                }
+            }
+            combined.fields.extend(explanation.fields);
+        }
+
+        combined.condition = match conditions.len() {
+            1 => conditions.remove(0),
+            _ => format!("({})", conditions.join(" AND ")),
+        };
+        combined
+    }
+}
+
 /// Base trait for all AST nodes
 #[async_trait]
 pub trait Branch: Debug + Send + Sync {
@@ -83,9 +145,98 @@ pub trait Branch: Debug + Send + Sync {
 
     /// Get a human-readable description of the node
     fn describe(&self) -> String;
+
+    /// The node as a [`SyncBranch`], if it can match without awaiting
+    fn as_sync(&self) -> Option<&dyn SyncBranch> {
+        None
+    }
+}
+
+/// AST node that matches without awaiting
+///
+/// Every `SyncBranch` is a [`Branch`]. The engine builds its trees from
+/// `SyncBranch` nodes only, so rulesets evaluate them without allocating
+/// futures or blocking.
+pub trait SyncBranch: Debug + Send + Sync {
+    /// Match the node against an event
+    ///
+    /// Matching is pure CPU work, so this runs to completion on the calling
+    /// thread.
+    fn matches_sync(&self, event: &dyn Event) -> MatchResult;
+
+    /// Match the node against an event, explaining a match
+    ///
+    /// The default explanation is the node's description alone.
+    fn explain(&self, event: &dyn Event) -> (MatchResult, Option<Explanation>) {
+        let result = self.matches_sync(event);
+        let explanation = result.matched.then(|| Explanation::new(self.describe()));
+        (result, explanation)
+    }
+
+    /// Get a human-readable description of the node
+    fn describe(&self) -> String;
+}
+
+#[async_trait]
+impl<T: SyncBranch> Branch for T {
+    async fn matches(&self, event: &dyn Event) -> MatchResult {
+        self.matches_sync(event)
+    }
+
+    fn describe(&self) -> String {
+        SyncBranch::describe(self)
+    }
+
+    fn as_sync(&self) -> Option<&dyn SyncBranch> {
+        Some(self)
+    }
+}
+
+#[async_trait]
+impl Branch for dyn SyncBranch {
+    async fn matches(&self, event: &dyn Event) -> MatchResult {
+        self.matches_sync(event)
+    }
+
+    fn describe(&self) -> String {
+        SyncBranch::describe(self)
+    }
+
+    fn as_sync(&self) -> Option<&dyn SyncBranch> {
+        Some(self)
+    }
+}
+
+impl dyn Branch {
+    /// Match the node against an event without awaiting
+    ///
+    /// Fails for nodes that only implement [`Branch`] rather than blocking
+    /// the calling thread on them.
+    pub fn matches_sync(&self, event: &dyn Event) -> Result<MatchResult, SigmaError> {
+        Ok(self.require_sync()?.matches_sync(event))
+    }
+
+    /// Match the node against an event, explaining a match
+    pub fn explain(
+        &self,
+        event: &dyn Event,
+    ) -> Result<(MatchResult, Option<Explanation>), SigmaError> {
+        Ok(self.require_sync()?.explain(event))
+    }
+
+    fn require_sync(&self) -> Result<&dyn SyncBranch, SigmaError> {
+        self.as_sync().ok_or_else(|| {
+            SigmaError::Runtime(format!(
+                "Branch {} cannot match without awaiting",
+                self.describe()
+            ))
+        })
+    }
 }
 
-use crate::pattern::{new_string_matcher, NumMatcher, StringMatcher, TextPatternModifier};
+use crate::pattern::{
+    new_string_matcher, FieldRefPattern, NumMatcher, StringMatcher, TextPatternModifier,
+};
 use std::sync::Arc;
 
 /// Field rule for matching event fields
@@ -116,6 +267,21 @@ pub enum FieldPattern {
     },
     /// Keyword matching against event keywords
     Keywords(Vec<String>),
+    /// Field presence: `true` for `|exists: true`, `false` for `|exists: false` and `null`
+    ///
+    /// Fields holding a null value count as absent.
+    Exists(bool),
+    /// Comparison against another field of the same event (`|fieldref`)
+    FieldRef(FieldRefPattern),
+    /// Numeric matching on a component of a timestamp field (`|hour`, `|day`, ...)
+    TimePart {
+        /// Component extracted from the timestamp
+        part: TimePart,
+        /// Numeric matcher applied to the component
+        matcher: Arc<dyn NumMatcher>,
+        /// Human-readable description of the pattern
+        pattern_desc: Arc<str>,
+    },
 }
 
 // Implement Serialize for compatibility
@@ -132,6 +298,14 @@ impl serde::Serialize for FieldPattern {
                 serializer.serialize_str(pattern_desc.as_ref())
             }
             FieldPattern::Keywords(keywords) => keywords.serialize(serializer),
+            FieldPattern::Exists(true) => serializer.serialize_str("exists"),
+            FieldPattern::Exists(false) => serializer.serialize_str("null"),
+            FieldPattern::FieldRef(pattern) => {
+                serializer.serialize_str(&format!("fieldref {}", pattern.field))
+            }
+            FieldPattern::TimePart { pattern_desc, .. } => {
+                serializer.serialize_str(pattern_desc.as_ref())
+            }
         }
     }
 }
@@ -157,18 +331,47 @@ impl PartialEq for FieldPattern {
                 },
             ) => p1 == p2,
             (FieldPattern::Keywords(k1), FieldPattern::Keywords(k2)) => k1 == k2,
+            (FieldPattern::Exists(e1), FieldPattern::Exists(e2)) => e1 == e2,
+            (FieldPattern::FieldRef(r1), FieldPattern::FieldRef(r2)) => r1 == r2,
+            (
+                FieldPattern::TimePart {
+                    part: t1,
+                    pattern_desc: p1,
+                    ..
+                },
+                FieldPattern::TimePart {
+                    part: t2,
+                    pattern_desc: p2,
+                    ..
+                },
+            ) => t1 == t2 && p1 == p2,
             _ => false,
         }
     }
 }
 
+impl FieldPattern {
+    /// Human-readable description of the pattern
+    pub fn description(&self) -> String {
+        match self {
+            FieldPattern::String { pattern_desc, .. }
+            | FieldPattern::Numeric { pattern_desc, .. }
+            | FieldPattern::TimePart { pattern_desc, .. } => pattern_desc.to_string(),
+            FieldPattern::Keywords(keywords) => keywords.join(", "),
+            FieldPattern::Exists(true) => "exists".to_string(),
+            FieldPattern::Exists(false) => "null".to_string(),
+            FieldPattern::FieldRef(pattern) => format!("fieldref {}", pattern.field),
+        }
+    }
+}
+
 impl FieldRule {
     /// Create a new field rule
     pub fn new(field: Arc<str>, pattern: FieldPattern) -> Self {
         Self { field, pattern }
     }
 
-    /// Create a string pattern
+    /// Create a case-insensitive string pattern
     pub fn string_pattern(
         field: Arc<str>,
         pattern: String,
@@ -176,7 +379,7 @@ impl FieldRule {
     ) -> Result<Self, String> {
         let matcher = new_string_matcher(
             modifier,
-            false, // lowercase
+            true,  // lowercase
             false, // all
             false, // no_collapse_ws
             vec![pattern.clone()],
@@ -191,11 +394,11 @@ impl FieldRule {
         })
     }
 
-    /// Create a glob pattern
+    /// Create a case-insensitive glob pattern
     pub fn glob_pattern(field: Arc<str>, pattern: String) -> Result<Self, String> {
         let matcher = new_string_matcher(
             TextPatternModifier::None,
-            false, // lowercase
+            true,  // lowercase
             false, // all
             false, // no_collapse_ws
             vec![pattern.clone()],
@@ -212,6 +415,38 @@ impl FieldRule {
 
     /// Check if this field rule matches the given event
     pub async fn matches(&self, event: &dyn Event) -> MatchResult {
+        self.matches_sync(event)
+    }
+
+    /// Check if this field rule matches the given event, recording the matched value
+    pub fn explain(&self, event: &dyn Event) -> (MatchResult, Option<Explanation>) {
+        let result = self.matches_sync(event);
+        if !result.matched {
+            return (result, None);
+        }
+
+        let value = match &self.pattern {
+            FieldPattern::Keywords(keywords) => serde_json::json!(keywords),
+            _ => match event.select(self.field.as_ref()) {
+                (Some(value), true) => value_to_json(value),
+                _ => serde_json::Value::Null,
+            },
+        };
+
+        let explanation = Explanation {
+            condition: self.field.to_string(),
+            identifiers: Vec::new(),
+            fields: vec![FieldMatch {
+                field: self.field.to_string(),
+                pattern: self.pattern.description(),
+                value,
+            }],
+        };
+        (result, Some(explanation))
+    }
+
+    /// Check if this field rule matches the given event, without awaiting
+    pub fn matches_sync(&self, event: &dyn Event) -> MatchResult {
         match &self.pattern {
             FieldPattern::String {
                 matcher,
@@ -223,10 +458,15 @@ impl FieldRule {
                     _ => return MatchResult::not_applicable(),
                 };
 
-                // Convert our Value to serde_json::Value for coercion
-                let json_value = value_to_json(value);
-                let value_str = coerce_for_string_match(&json_value);
-                MatchResult::new(matcher.string_match(&value_str), true)
+                let matched = match value {
+                    // Strings need no coercion, so skip the JSON conversion
+                    Value::String(s) => matcher.string_match(&s),
+                    value => {
+                        let json_value = value_to_json(value);
+                        matcher.string_match(&coerce_for_string_match(&json_value))
+                    }
+                };
+                MatchResult::new(matched, true)
             }
             FieldPattern::Numeric { matcher, .. } => {
                 let (value_opt, found) = event.select(self.field.as_ref());
@@ -237,12 +477,13 @@ impl FieldRule {
 
                 // Convert our Value to serde_json::Value for coercion
                 let json_value = value_to_json(value);
-                let num_value = match coerce_for_numeric_match(&json_value) {
-                    Some(n) => n,
-                    None => return MatchResult::not_matched(),
+                let matched = match coerce_for_number(&json_value) {
+                    Some(NumericValue::Integer(n)) => matcher.num_match(n),
+                    Some(NumericValue::Float(f)) => matcher.float_match(f),
+                    None => false,
                 };
 
-                MatchResult::new(matcher.num_match(num_value), true)
+                MatchResult::new(matched, true)
             }
             FieldPattern::Keywords(keywords) => {
                 let (event_keywords, applicable) = event.keywords();
@@ -252,14 +493,50 @@ impl FieldRule {
                 let matched = keywords.iter().all(|k| event_keywords.contains(k));
                 MatchResult::new(matched, true)
             }
+            FieldPattern::Exists(expected) => {
+                let (value, found) = event.select(self.field.as_ref());
+                let present = found && !matches!(value, None | Some(Value::Null));
+                MatchResult::new(present == *expected, true)
+            }
+            FieldPattern::FieldRef(pattern) => {
+                let value = match event.select(self.field.as_ref()) {
+                    (Some(v), true) => value_to_json(v),
+                    _ => return MatchResult::not_applicable(),
+                };
+                // A missing referenced field cannot be equal to anything
+                let reference = match event.select(pattern.field.as_ref()) {
+                    (Some(v), true) => value_to_json(v),
+                    _ => return MatchResult::not_matched(),
+                };
+
+                let matched = pattern.matches(
+                    &coerce_for_string_match(&value),
+                    &coerce_for_string_match(&reference),
+                );
+                MatchResult::new(matched, true)
+            }
+            FieldPattern::TimePart { part, matcher, .. } => {
+                let value = match event.select(self.field.as_ref()) {
+                    (Some(v), true) => value_to_json(v),
+                    _ => return MatchResult::not_applicable(),
+                };
+
+                // Values that are not timestamps have no component to compare
+                let matched = coerce_for_timestamp(&value)
+                    .is_some_and(|timestamp| matcher.num_match(part.extract(&timestamp)));
+                MatchResult::new(matched, true)
+            }
         }
     }
 }
 
-#[async_trait]
-impl Branch for FieldRule {
-    async fn matches(&self, event: &dyn Event) -> MatchResult {
-        self.matches(event).await
+impl SyncBranch for FieldRule {
+    fn matches_sync(&self, event: &dyn Event) -> MatchResult {
+        FieldRule::matches_sync(self, event)
+    }
+
+    fn explain(&self, event: &dyn Event) -> (MatchResult, Option<Explanation>) {
+        FieldRule::explain(self, event)
     }
 
     fn describe(&self) -> String {
@@ -1,40 +1,55 @@
-use super::{Branch, FieldRule, MatchResult};
+use super::{Explanation, FieldRule, MatchResult, SyncBranch};
 use crate::error::SigmaError;
 use crate::event::Event;
-use async_trait::async_trait;
 use std::sync::Arc;
 
 /// Node for logical AND operation
 #[derive(Debug, Clone)]
 pub struct NodeAnd {
     /// Left branch of the AND operation
-    pub left: Arc<dyn Branch>,
+    pub left: Arc<dyn SyncBranch>,
     /// Right branch of the AND operation
-    pub right: Arc<dyn Branch>,
+    pub right: Arc<dyn SyncBranch>,
 }
 
 impl NodeAnd {
     /// Create a new AND node
-    pub fn new(left: Arc<dyn Branch>, right: Arc<dyn Branch>) -> Self {
+    pub fn new(left: Arc<dyn SyncBranch>, right: Arc<dyn SyncBranch>) -> Self {
         Self { left, right }
     }
 }
 
-#[async_trait]
-impl Branch for NodeAnd {
-    async fn matches(&self, event: &dyn Event) -> MatchResult {
-        let left_result = self.left.matches(event).await;
+impl SyncBranch for NodeAnd {
+    fn matches_sync(&self, event: &dyn Event) -> MatchResult {
+        let left_result = self.left.matches_sync(event);
         if !left_result.matched {
             return MatchResult::new(false, left_result.applicable);
         }
 
-        let right_result = self.right.matches(event).await;
+        let right_result = self.right.matches_sync(event);
         MatchResult::new(
             left_result.matched && right_result.matched,
             left_result.applicable && right_result.applicable,
         )
     }
 
+    fn explain(&self, event: &dyn Event) -> (MatchResult, Option<Explanation>) {
+        let (left_result, left) = self.left.explain(event);
+        if !left_result.matched {
+            return (MatchResult::new(false, left_result.applicable), None);
+        }
+
+        let (right_result, right) = self.right.explain(event);
+        let result = MatchResult::new(
+            right_result.matched,
+            left_result.applicable && right_result.applicable,
+        );
+        let explanation = result
+            .matched
+            .then(|| Explanation::all(left.into_iter().chain(right)));
+        (result, explanation)
+    }
+
     fn describe(&self) -> String {
         format!("({} AND {})", self.left.describe(), self.right.describe())
     }
@@ -44,33 +59,46 @@ impl Branch for NodeAnd {
 #[derive(Debug, Clone)]
 pub struct NodeOr {
     /// Left branch of the OR operation
-    pub left: Arc<dyn Branch>,
+    pub left: Arc<dyn SyncBranch>,
     /// Right branch of the OR operation
-    pub right: Arc<dyn Branch>,
+    pub right: Arc<dyn SyncBranch>,
 }
 
 impl NodeOr {
     /// Create a new OR node
-    pub fn new(left: Arc<dyn Branch>, right: Arc<dyn Branch>) -> Self {
+    pub fn new(left: Arc<dyn SyncBranch>, right: Arc<dyn SyncBranch>) -> Self {
         Self { left, right }
     }
 }
 
-#[async_trait]
-impl Branch for NodeOr {
-    async fn matches(&self, event: &dyn Event) -> MatchResult {
-        let left_result = self.left.matches(event).await;
+impl SyncBranch for NodeOr {
+    fn matches_sync(&self, event: &dyn Event) -> MatchResult {
+        let left_result = self.left.matches_sync(event);
         if left_result.matched {
             return MatchResult::new(true, left_result.applicable);
         }
 
-        let right_result = self.right.matches(event).await;
+        let right_result = self.right.matches_sync(event);
         MatchResult::new(
             left_result.matched || right_result.matched,
             left_result.applicable || right_result.applicable,
         )
     }
 
+    fn explain(&self, event: &dyn Event) -> (MatchResult, Option<Explanation>) {
+        let (left_result, left) = self.left.explain(event);
+        if left_result.matched {
+            return (MatchResult::new(true, left_result.applicable), left);
+        }
+
+        let (right_result, right) = self.right.explain(event);
+        let result = MatchResult::new(
+            right_result.matched,
+            left_result.applicable || right_result.applicable,
+        );
+        (result, right)
+    }
+
     fn describe(&self) -> String {
         format!("({} OR {})", self.left.describe(), self.right.describe())
     }
@@ -80,24 +108,30 @@ impl Branch for NodeOr {
 #[derive(Debug, Clone)]
 pub struct NodeNot {
     /// Branch to negate
-    pub branch: Arc<dyn Branch>,
+    pub branch: Arc<dyn SyncBranch>,
 }
 
 impl NodeNot {
     /// Create a new NOT node
-    pub fn new(branch: Arc<dyn Branch>) -> Self {
+    pub fn new(branch: Arc<dyn SyncBranch>) -> Self {
         Self { branch }
     }
 }
 
-#[async_trait]
-impl Branch for NodeNot {
-    async fn matches(&self, event: &dyn Event) -> MatchResult {
-        let result = self.branch.matches(event).await;
-        if !result.applicable {
-            return result;
-        }
-        MatchResult::new(!result.matched, result.applicable)
+impl SyncBranch for NodeNot {
+    fn matches_sync(&self, event: &dyn Event) -> MatchResult {
+        // A missing field fails the negated selection, so its negation holds
+        let result = self.branch.matches_sync(event);
+        MatchResult::new(!result.matched, true)
+    }
+
+    fn explain(&self, event: &dyn Event) -> (MatchResult, Option<Explanation>) {
+        // The negated branch did not match, so there are no values to report
+        let result = self.matches_sync(event);
+        let explanation = result
+            .matched
+            .then(|| Explanation::new(format!("NOT {}", self.branch.describe())));
+        (result, explanation)
     }
 
     fn describe(&self) -> String {
@@ -109,17 +143,17 @@ impl Branch for NodeNot {
 #[derive(Debug, Clone)]
 pub struct NodeSimpleAnd {
     /// Collection of branches that must all match
-    pub branches: Vec<Arc<dyn Branch>>,
+    pub branches: Vec<Arc<dyn SyncBranch>>,
 }
 
 impl NodeSimpleAnd {
     /// Create a new AND node with multiple branches
-    pub fn new(branches: Vec<Arc<dyn Branch>>) -> Self {
+    pub fn new(branches: Vec<Arc<dyn SyncBranch>>) -> Self {
         Self { branches }
     }
 
     /// Reduce to more efficient representation if possible
-    pub fn reduce(self) -> Result<Arc<dyn Branch>, SigmaError> {
+    pub fn reduce(self) -> Result<Arc<dyn SyncBranch>, SigmaError> {
         let branches_len = self.branches.len();
         let mut iter = self.branches.into_iter();
         match (iter.next(), iter.next(), branches_len.saturating_sub(2)) {
@@ -141,11 +175,10 @@ impl NodeSimpleAnd {
     }
 }
 
-#[async_trait]
-impl Branch for NodeSimpleAnd {
-    async fn matches(&self, event: &dyn Event) -> MatchResult {
+impl SyncBranch for NodeSimpleAnd {
+    fn matches_sync(&self, event: &dyn Event) -> MatchResult {
         for branch in &self.branches {
-            let result = branch.matches(event).await;
+            let result = branch.matches_sync(event);
             if !result.matched || !result.applicable {
                 return result;
             }
@@ -153,6 +186,18 @@ impl Branch for NodeSimpleAnd {
         MatchResult::matched()
     }
 
+    fn explain(&self, event: &dyn Event) -> (MatchResult, Option<Explanation>) {
+        let mut explanations = Vec::with_capacity(self.branches.len());
+        for branch in &self.branches {
+            let (result, explanation) = branch.explain(event);
+            if !result.matched || !result.applicable {
+                return (result, None);
+            }
+            explanations.extend(explanation);
+        }
+        (MatchResult::matched(), Some(Explanation::all(explanations)))
+    }
+
     fn describe(&self) -> String {
         let descriptions: Vec<String> = self.branches.iter().map(|b| b.describe()).collect();
         format!("({})", descriptions.join(" AND "))
@@ -163,17 +208,17 @@ impl Branch for NodeSimpleAnd {
 #[derive(Debug, Clone)]
 pub struct NodeSimpleOr {
     /// Collection of branches where at least one must match
-    pub branches: Vec<Arc<dyn Branch>>,
+    pub branches: Vec<Arc<dyn SyncBranch>>,
 }
 
 impl NodeSimpleOr {
     /// Create a new OR node with multiple branches
-    pub fn new(branches: Vec<Arc<dyn Branch>>) -> Self {
+    pub fn new(branches: Vec<Arc<dyn SyncBranch>>) -> Self {
         Self { branches }
     }
 
     /// Reduce to more efficient representation if possible
-    pub fn reduce(self) -> Result<Arc<dyn Branch>, SigmaError> {
+    pub fn reduce(self) -> Result<Arc<dyn SyncBranch>, SigmaError> {
         let branches_len = self.branches.len();
         let mut iter = self.branches.into_iter();
         match (iter.next(), iter.next(), branches_len.saturating_sub(2)) {
@@ -195,13 +240,12 @@ impl NodeSimpleOr {
     }
 }
 
-#[async_trait]
-impl Branch for NodeSimpleOr {
-    async fn matches(&self, event: &dyn Event) -> MatchResult {
+impl SyncBranch for NodeSimpleOr {
+    fn matches_sync(&self, event: &dyn Event) -> MatchResult {
         let mut one_applicable = false;
 
         for branch in &self.branches {
-            let result = branch.matches(event).await;
+            let result = branch.matches_sync(event);
             if result.matched {
                 return MatchResult::matched();
             }
@@ -213,6 +257,22 @@ impl Branch for NodeSimpleOr {
         MatchResult::new(false, one_applicable)
     }
 
+    fn explain(&self, event: &dyn Event) -> (MatchResult, Option<Explanation>) {
+        let mut one_applicable = false;
+
+        for branch in &self.branches {
+            let (result, explanation) = branch.explain(event);
+            if result.matched {
+                return (MatchResult::matched(), explanation);
+            }
+            if result.applicable {
+                one_applicable = true;
+            }
+        }
+
+        (MatchResult::new(false, one_applicable), None)
+    }
+
     fn describe(&self) -> String {
         let descriptions: Vec<String> = self.branches.iter().map(|b| b.describe()).collect();
         format!("({})", descriptions.join(" OR "))
@@ -220,7 +280,7 @@ impl Branch for NodeSimpleOr {
 }
 
 /// Helper function to create a NOT node if negated
-pub fn new_node_not_if_negated(branch: Arc<dyn Branch>, negated: bool) -> Arc<dyn Branch> {
+pub fn new_node_not_if_negated(branch: Arc<dyn SyncBranch>, negated: bool) -> Arc<dyn SyncBranch> {
     if negated {
         Arc::new(NodeNot::new(branch))
     } else {
@@ -248,14 +308,65 @@ impl Identifier {
     }
 }
 
-#[async_trait]
-impl Branch for Identifier {
-    async fn matches(&self, event: &dyn Event) -> MatchResult {
-        self.field_rule.matches(event).await
+impl SyncBranch for Identifier {
+    fn matches_sync(&self, event: &dyn Event) -> MatchResult {
+        self.field_rule.matches_sync(event)
+    }
+
+    fn explain(&self, event: &dyn Event) -> (MatchResult, Option<Explanation>) {
+        self.field_rule.explain(event)
     }
 
     fn describe(&self) -> String {
-        self.field_rule.describe()
+        SyncBranch::describe(&self.field_rule)
+    }
+}
+
+/// Named detection identifier (selection) referenced by the condition
+#[derive(Debug, Clone)]
+pub struct NodeSelection {
+    /// Identifier name, e.g. `selection`
+    pub name: Arc<str>,
+    /// Branch built from the identifier's value
+    pub branch: Arc<dyn SyncBranch>,
+}
+
+impl NodeSelection {
+    /// Create a new named selection node
+    pub fn new(name: impl Into<Arc<str>>, branch: Arc<dyn SyncBranch>) -> Self {
+        Self {
+            name: name.into(),
+            branch,
+        }
+    }
+}
+
+impl SyncBranch for NodeSelection {
+    fn matches_sync(&self, event: &dyn Event) -> MatchResult {
+        self.branch.matches_sync(event)
+    }
+
+    fn explain(&self, event: &dyn Event) -> (MatchResult, Option<Explanation>) {
+        let (result, explanation) = self.branch.explain(event);
+        let explanation = explanation.map(|explanation| {
+            let mut identifiers = vec![self.name.to_string()];
+            identifiers.extend(
+                explanation
+                    .identifiers
+                    .into_iter()
+                    .filter(|i| *i != *self.name),
+            );
+            Explanation {
+                condition: self.name.to_string(),
+                identifiers,
+                fields: explanation.fields,
+            }
+        });
+        (result, explanation)
+    }
+
+    fn describe(&self) -> String {
+        self.name.to_string()
     }
 }
 
@@ -324,11 +435,10 @@ impl NodeAggregation {
     }
 }
 
-#[async_trait]
-impl Branch for NodeAggregation {
-    async fn matches(&self, _event: &dyn Event) -> MatchResult {
-        // Aggregation logic will be implemented by the AggregationEvaluator
-        // This is just a placeholder for the AST node
+impl SyncBranch for NodeAggregation {
+    fn matches_sync(&self, _event: &dyn Event) -> MatchResult {
+        // Aggregations are stateful; Tree feeds matching events to its
+        // AggregationEvaluator instead of evaluating this node directly
         MatchResult::not_matched()
     }
 
@@ -354,20 +464,36 @@ impl Branch for NodeAggregation {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ast::Branch;
+    use async_trait::async_trait;
     use std::sync::Arc;
 
     // Mock branch for testing
     #[derive(Debug, Clone)]
     struct MockBranch(String);
 
+    impl SyncBranch for MockBranch {
+        fn matches_sync(&self, _event: &dyn Event) -> MatchResult {
+            MatchResult::matched()
+        }
+
+        fn describe(&self) -> String {
+            self.0.clone()
+        }
+    }
+
+    /// Branch that can only match by awaiting
+    #[derive(Debug)]
+    struct AsyncBranch;
+
     #[async_trait]
-    impl Branch for MockBranch {
+    impl Branch for AsyncBranch {
         async fn matches(&self, _event: &dyn Event) -> MatchResult {
             MatchResult::matched()
         }
 
         fn describe(&self) -> String {
-            self.0.clone()
+            "async".to_string()
         }
     }
 
@@ -380,7 +506,7 @@ mod tests {
 
     #[test]
     fn test_simple_and_reduce_single() {
-        let branch = Arc::new(MockBranch("test".to_string())) as Arc<dyn Branch>;
+        let branch = Arc::new(MockBranch("test".to_string())) as Arc<dyn SyncBranch>;
         let and_node = NodeSimpleAnd::new(vec![branch.clone()]);
         let result = and_node.reduce().unwrap();
         assert_eq!(result.describe(), "test");
@@ -388,8 +514,8 @@ mod tests {
 
     #[test]
     fn test_simple_and_reduce_two() {
-        let branch1 = Arc::new(MockBranch("test1".to_string())) as Arc<dyn Branch>;
-        let branch2 = Arc::new(MockBranch("test2".to_string())) as Arc<dyn Branch>;
+        let branch1 = Arc::new(MockBranch("test1".to_string())) as Arc<dyn SyncBranch>;
+        let branch2 = Arc::new(MockBranch("test2".to_string())) as Arc<dyn SyncBranch>;
         let and_node = NodeSimpleAnd::new(vec![branch1, branch2]);
         let result = and_node.reduce().unwrap();
         assert_eq!(result.describe(), "(test1 AND test2)");
@@ -397,8 +523,8 @@ mod tests {
 
     #[test]
     fn test_simple_and_reduce_multiple() {
-        let branches: Vec<Arc<dyn Branch>> = (0..5)
-            .map(|i| Arc::new(MockBranch(format!("test{}", i))) as Arc<dyn Branch>)
+        let branches: Vec<Arc<dyn SyncBranch>> = (0..5)
+            .map(|i| Arc::new(MockBranch(format!("test{}", i))) as Arc<dyn SyncBranch>)
             .collect();
         let and_node = NodeSimpleAnd::new(branches);
         let result = and_node.reduce().unwrap();
@@ -417,7 +543,7 @@ mod tests {
 
     #[test]
     fn test_simple_or_reduce_single() {
-        let branch = Arc::new(MockBranch("test".to_string())) as Arc<dyn Branch>;
+        let branch = Arc::new(MockBranch("test".to_string())) as Arc<dyn SyncBranch>;
         let or_node = NodeSimpleOr::new(vec![branch.clone()]);
         let result = or_node.reduce().unwrap();
         assert_eq!(result.describe(), "test");
@@ -425,8 +551,8 @@ mod tests {
 
     #[test]
     fn test_simple_or_reduce_two() {
-        let branch1 = Arc::new(MockBranch("test1".to_string())) as Arc<dyn Branch>;
-        let branch2 = Arc::new(MockBranch("test2".to_string())) as Arc<dyn Branch>;
+        let branch1 = Arc::new(MockBranch("test1".to_string())) as Arc<dyn SyncBranch>;
+        let branch2 = Arc::new(MockBranch("test2".to_string())) as Arc<dyn SyncBranch>;
         let or_node = NodeSimpleOr::new(vec![branch1, branch2]);
         let result = or_node.reduce().unwrap();
         assert_eq!(result.describe(), "(test1 OR test2)");
@@ -434,8 +560,8 @@ mod tests {
 
     #[test]
     fn test_simple_or_reduce_multiple() {
-        let branches: Vec<Arc<dyn Branch>> = (0..5)
-            .map(|i| Arc::new(MockBranch(format!("test{}", i))) as Arc<dyn Branch>)
+        let branches: Vec<Arc<dyn SyncBranch>> = (0..5)
+            .map(|i| Arc::new(MockBranch(format!("test{}", i))) as Arc<dyn SyncBranch>)
             .collect();
         let or_node = NodeSimpleOr::new(branches);
         let result = or_node.reduce().unwrap();
@@ -444,4 +570,22 @@ mod tests {
             "(test0 OR test1 OR test2 OR test3 OR test4)"
         );
     }
+
+    #[tokio::test]
+    async fn test_async_only_branch_matches_sync() {
+        let event = crate::DynamicEvent::new(serde_json::json!({}));
+        let branch = Arc::new(AsyncBranch) as Arc<dyn Branch>;
+        assert!(branch.as_sync().is_none());
+        assert!(branch.matches(&event).await.matched);
+        assert!(branch.matches_sync(&event).is_err());
+        assert!(branch.explain(&event).is_err());
+
+        let mock = Arc::new(MockBranch("mock".to_string())) as Arc<dyn SyncBranch>;
+        let and_node: Arc<dyn Branch> = Arc::new(NodeAnd::new(mock.clone(), mock));
+        assert!(and_node.matches_sync(&event).unwrap().matched);
+
+        let (result, explanation) = and_node.explain(&event).unwrap();
+        assert!(result.matched);
+        assert_eq!(explanation.unwrap().condition, "(mock AND mock)");
+    }
 }
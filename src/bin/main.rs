@@ -1,16 +1,23 @@
 use clap::{Parser, ValueEnum};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use sigma_rs::correlation::CorrelationEngine;
+use sigma_rs::event::evtx::EvtxReader;
+use sigma_rs::event::{Event, TimestampExtractor, TimestampFormat};
+use sigma_rs::parser::{PlaceholderRegistry, UnresolvedPlaceholder};
+use sigma_rs::pipeline::ProcessingPipeline;
+use sigma_rs::ruleset::LogsourceClassifier;
 use sigma_rs::{DynamicEvent, RuleSet};
 use std::fs;
 use std::io::{self, BufRead, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tracing_subscriber;
 
 #[derive(Debug, Clone, ValueEnum)]
 enum InputSource {
     Stdin,
     Kafka,
+    Evtx,
 }
 
 #[derive(Debug, Clone, ValueEnum)]
@@ -35,10 +42,44 @@ struct Cli {
     #[arg(short, long, default_value = "stdout")]
     output: OutputTarget,
 
+    /// EVTX files or directories to read with `--input evtx` (repeatable)
+    #[arg(long, value_name = "PATH", required_if_eq("input", "evtx"))]
+    evtx: Vec<PathBuf>,
+
     /// Configuration file (required for Kafka)
     #[arg(short, long)]
     config: Option<PathBuf>,
 
+    /// Placeholder definitions for `|expand` fields (YAML or TOML)
+    #[arg(long)]
+    placeholders: Option<PathBuf>,
+
+    /// Treat unresolved placeholders as non-matching instead of failing the rule
+    #[arg(long, requires = "placeholders")]
+    unresolved_no_match: bool,
+
+    /// Processing pipeline applied to rules before compilation (repeatable)
+    #[arg(long = "pipeline", value_name = "PATH")]
+    pipelines: Vec<PathBuf>,
+
+    /// Logsource mappings for routing events to relevant rules (YAML)
+    #[arg(long, value_name = "PATH")]
+    logsource_map: Option<PathBuf>,
+
+    /// Reject rules with unknown modifiers or malformed detections
+    #[arg(long)]
+    strict: bool,
+
+    /// Take event time from this field instead of processing time (repeatable,
+    /// dot notation for nested fields)
+    #[arg(long = "timestamp-field", value_name = "FIELD")]
+    timestamp_fields: Vec<String>,
+
+    /// Format of the event time fields (repeatable): auto (default), rfc3339, epoch_seconds,
+    /// epoch_millis, epoch_nanos, windows_file_time or a strftime pattern
+    #[arg(long = "timestamp-format", value_name = "FORMAT")]
+    timestamp_formats: Vec<TimestampFormat>,
+
     /// Enable debug logging
     #[arg(short, long)]
     debug: bool,
@@ -120,6 +161,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Load rules
     let mut ruleset = RuleSet::new();
+    ruleset.set_strict(cli.strict);
+    if let Some(path) = &cli.placeholders {
+        let mut placeholders = PlaceholderRegistry::from_file(path)?;
+        if cli.unresolved_no_match {
+            placeholders = placeholders.with_unresolved(UnresolvedPlaceholder::NoMatch);
+        }
+        ruleset.set_placeholders(std::sync::Arc::new(placeholders));
+    }
+    if !cli.pipelines.is_empty() {
+        let pipelines = cli
+            .pipelines
+            .iter()
+            .map(ProcessingPipeline::from_file)
+            .collect::<Result<Vec<_>, _>>()?;
+        ruleset.set_pipeline(std::sync::Arc::new(ProcessingPipeline::chain(pipelines)));
+    }
+    if let Some(path) = &cli.logsource_map {
+        let classifier = LogsourceClassifier::from_file(path)?;
+        ruleset.set_classifier(std::sync::Arc::new(classifier));
+    }
     ruleset.load_directory(&cli.rules.to_string_lossy()).await?;
 
     if ruleset.len() == 0 {
@@ -137,19 +198,25 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         cli.rules.display()
     );
 
+    let detector = Detector::new(ruleset)?;
+    let events = EventFactory::new(&cli);
+
     // Process events based on input/output configuration
     match (cli.input, cli.output) {
         (InputSource::Stdin, OutputTarget::Stdout) => {
-            process_stdin_to_stdout(ruleset).await?;
+            process_stdin_to_stdout(&detector, &events).await?;
         }
         (InputSource::Kafka, OutputTarget::Stdout) => {
-            process_kafka_to_stdout(ruleset, config.kafka).await?;
+            process_kafka_to_stdout(&detector, &events, config.kafka).await?;
         }
         (InputSource::Stdin, OutputTarget::Kafka) => {
-            process_stdin_to_kafka(ruleset, config.kafka).await?;
+            process_stdin_to_kafka(&detector, &events, config.kafka).await?;
         }
         (InputSource::Kafka, OutputTarget::Kafka) => {
-            process_kafka_to_kafka(ruleset, config.kafka).await?;
+            process_kafka_to_kafka(&detector, &events, config.kafka).await?;
+        }
+        (InputSource::Evtx, OutputTarget::Stdout) => {
+            process_evtx_to_stdout(&detector, &events, &cli.evtx).await?;
         }
         _ => {
             eprintln!("Invalid input/output combination");
@@ -164,7 +231,106 @@ fn needs_config(cli: &Cli) -> bool {
     matches!(cli.input, InputSource::Kafka) || matches!(cli.output, OutputTarget::Kafka)
 }
 
-async fn process_stdin_to_stdout(ruleset: RuleSet) -> Result<(), Box<dyn std::error::Error>> {
+/// Creates events from JSON, taking event time from the `--timestamp-*` fields
+struct EventFactory {
+    extractor: Option<TimestampExtractor>,
+}
+
+impl EventFactory {
+    fn new(cli: &Cli) -> Self {
+        if cli.timestamp_fields.is_empty() && cli.timestamp_formats.is_empty() {
+            return Self { extractor: None };
+        }
+
+        let mut extractor = TimestampExtractor::default();
+        if !cli.timestamp_fields.is_empty() {
+            extractor.fields = cli.timestamp_fields.clone();
+        }
+        if !cli.timestamp_formats.is_empty() {
+            extractor.formats = cli.timestamp_formats.clone();
+        }
+        Self {
+            extractor: Some(extractor),
+        }
+    }
+
+    fn create(&self, event: Value) -> DynamicEvent {
+        self.extract(DynamicEvent::new(event))
+    }
+
+    /// Create an event occurring at `time` unless its timestamp fields say otherwise
+    fn create_at(&self, event: Value, time: chrono::DateTime<chrono::Utc>) -> DynamicEvent {
+        self.extract(DynamicEvent::new(event).with_time(time))
+    }
+
+    fn extract(&self, dynamic_event: DynamicEvent) -> DynamicEvent {
+        match &self.extractor {
+            Some(extractor) => dynamic_event.with_event_time(extractor),
+            None => dynamic_event,
+        }
+    }
+}
+
+/// Evaluates events against the rules and the correlation rules built on them
+struct Detector {
+    ruleset: RuleSet,
+    correlations: CorrelationEngine,
+}
+
+/// A rule or correlation rule fired by an event
+struct Detection {
+    rule_id: String,
+    rule_title: String,
+}
+
+impl Detector {
+    fn new(ruleset: RuleSet) -> Result<Self, Box<dyn std::error::Error>> {
+        let correlations = CorrelationEngine::new(&ruleset)?;
+        if !correlations.is_empty() {
+            eprintln!("Loaded {} correlation rules", correlations.len());
+        }
+        Ok(Self {
+            ruleset,
+            correlations,
+        })
+    }
+
+    /// Evaluate an event, returning its rule matches and the correlations it completes
+    async fn detect(
+        &self,
+        event: &DynamicEvent,
+    ) -> Result<Vec<Detection>, Box<dyn std::error::Error>> {
+        let result = self.ruleset.evaluate(event).await?;
+        let correlated = self.correlations.process(event, &result);
+
+        let matches = result
+            .matches
+            .into_iter()
+            .filter(|m| m.matched)
+            .map(|m| Detection {
+                rule_id: m.rule_id,
+                rule_title: m.rule_title,
+            });
+        let correlations = correlated.into_iter().map(|alert| Detection {
+            rule_id: alert.rule_id,
+            rule_title: alert.rule_title,
+        });
+        Ok(matches.chain(correlations).collect())
+    }
+}
+
+/// Time reported for a match: the event time when known, otherwise now
+fn match_time(event: &DynamicEvent) -> String {
+    event
+        .event_time()
+        .unwrap_or_else(chrono::Utc::now)
+        .to_rfc3339()
+}
+
+async fn process_stdin_to_stdout(
+    detector: &Detector,
+    events: &EventFactory,
+) -> Result<(), Box<dyn std::error::Error>> {
     let stdin = io::stdin();
     let stdout = io::stdout();
     let mut stdout_lock = stdout.lock();
@@ -176,28 +342,110 @@ async fn process_stdin_to_stdout(ruleset: RuleSet) -> Result<(), Box<dyn std::er
         }
 
         let event: Value = serde_json::from_str(&line)?;
-        let dynamic_event = DynamicEvent::new(event.clone());
-
-        let result = ruleset.evaluate(&dynamic_event).await?;
-
-        for rule_match in &result.matches {
-            if rule_match.matched {
-                let output = serde_json::json!({
-                    "timestamp": chrono::Utc::now().to_rfc3339(),
-                    "event": event,
-                    "rule_id": rule_match.rule_id,
-                    "rule_title": rule_match.rule_title,
-                });
-                writeln!(stdout_lock, "{}", serde_json::to_string(&output)?)?;
-            }
+        let dynamic_event = events.create(event.clone());
+
+        for detection in detector.detect(&dynamic_event).await? {
+            let output = serde_json::json!({
+                "timestamp": match_time(&dynamic_event),
+                "event": event,
+                "rule_id": detection.rule_id,
+                "rule_title": detection.rule_title,
+            });
+            writeln!(stdout_lock, "{}", serde_json::to_string(&output)?)?;
         }
     }
 
     Ok(())
 }
 
+/// Collect `.evtx` files, searching directories recursively
+fn evtx_files(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut found: Vec<PathBuf> = walkdir::WalkDir::new(path)
+                .into_iter()
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.into_path())
+                .filter(|path| {
+                    path.is_file()
+                        && path
+                            .extension()
+                            .is_some_and(|ext| ext.eq_ignore_ascii_case("evtx"))
+                })
+                .collect();
+            found.sort();
+            files.extend(found);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    files
+}
+
+async fn process_evtx_to_stdout(
+    detector: &Detector,
+    events: &EventFactory,
+    paths: &[PathBuf],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let stdout = io::stdout();
+    let mut stdout_lock = stdout.lock();
+
+    for path in evtx_files(paths) {
+        let (records, matches) =
+            evaluate_evtx_file(detector, events, &path, &mut stdout_lock).await?;
+        eprintln!(
+            "{}: {} records, {} matches",
+            path.display(),
+            records,
+            matches
+        );
+    }
+
+    Ok(())
+}
+
+async fn evaluate_evtx_file(
+    detector: &Detector,
+    events: &EventFactory,
+    path: &Path,
+    out: &mut impl Write,
+) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+    let mut records = 0;
+    let mut matches = 0;
+
+    for record in EvtxReader::open(path)? {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                eprintln!("Skipping record in {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        records += 1;
+
+        let event = record.event;
+        let dynamic_event = events.create_at(event.clone(), record.timestamp);
+        for detection in detector.detect(&dynamic_event).await? {
+            matches += 1;
+            let output = serde_json::json!({
+                "timestamp": match_time(&dynamic_event),
+                "record_id": record.record_id,
+                "record_timestamp": record.timestamp.to_rfc3339(),
+                "event": event,
+                "rule_id": detection.rule_id,
+                "rule_title": detection.rule_title,
+            });
+            writeln!(out, "{}", serde_json::to_string(&output)?)?;
+        }
+    }
+
+    Ok((records, matches))
+}
+
 async fn process_kafka_to_stdout(
-    ruleset: RuleSet,
+    detector: &Detector,
+    events: &EventFactory,
     config: KafkaConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
     use futures::stream::StreamExt;
@@ -225,19 +473,15 @@ async fn process_kafka_to_stdout(
                 if let Some(payload) = msg.payload() {
                     if let Ok(data) = std::str::from_utf8(payload) {
                         if let Ok(event) = serde_json::from_str::<Value>(data) {
-                            let dynamic_event = DynamicEvent::new(event.clone());
-                            let result = ruleset.evaluate(&dynamic_event).await?;
-
-                            for rule_match in &result.matches {
-                                if rule_match.matched {
-                                    let output = serde_json::json!({
-                                        "timestamp": chrono::Utc::now().to_rfc3339(),
-                                        "event": event,
-                                        "rule_id": rule_match.rule_id,
-                                        "rule_title": rule_match.rule_title,
-                                    });
-                                    writeln!(stdout_lock, "{}", serde_json::to_string(&output)?)?;
-                                }
+                            let dynamic_event = events.create(event.clone());
+                            for detection in detector.detect(&dynamic_event).await? {
+                                let output = serde_json::json!({
+                                    "timestamp": match_time(&dynamic_event),
+                                    "event": event,
+                                    "rule_id": detection.rule_id,
+                                    "rule_title": detection.rule_title,
+                                });
+                                writeln!(stdout_lock, "{}", serde_json::to_string(&output)?)?;
                             }
                         }
                     }
@@ -254,7 +498,8 @@ async fn process_kafka_to_stdout(
 }
 
 async fn process_stdin_to_kafka(
-    ruleset: RuleSet,
+    detector: &Detector,
+    events: &EventFactory,
     config: KafkaConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
     use rdkafka::config::ClientConfig;
@@ -274,29 +519,25 @@ async fn process_stdin_to_kafka(
         }
 
         let event: Value = serde_json::from_str(&line)?;
-        let dynamic_event = DynamicEvent::new(event.clone());
-
-        let result = ruleset.evaluate(&dynamic_event).await?;
-
-        for rule_match in &result.matches {
-            if rule_match.matched {
-                let output = serde_json::json!({
-                    "timestamp": chrono::Utc::now().to_rfc3339(),
-                    "event": event,
-                    "rule_id": rule_match.rule_id,
-                    "rule_title": rule_match.rule_title,
-                });
-
-                let payload = serde_json::to_string(&output)?;
-                let record = FutureRecord::to(&config.output_topic)
-                    .payload(&payload)
-                    .key(&rule_match.rule_id);
-
-                producer
-                    .send(record, std::time::Duration::from_secs(0))
-                    .await
-                    .map_err(|(e, _)| e)?;
-            }
+        let dynamic_event = events.create(event.clone());
+
+        for detection in detector.detect(&dynamic_event).await? {
+            let output = serde_json::json!({
+                "timestamp": match_time(&dynamic_event),
+                "event": event,
+                "rule_id": detection.rule_id,
+                "rule_title": detection.rule_title,
+            });
+
+            let payload = serde_json::to_string(&output)?;
+            let record = FutureRecord::to(&config.output_topic)
+                .payload(&payload)
+                .key(&detection.rule_id);
+
+            producer
+                .send(record, std::time::Duration::from_secs(0))
+                .await
+                .map_err(|(e, _)| e)?;
         }
     }
 
@@ -304,7 +545,8 @@ async fn process_stdin_to_kafka(
 }
 
 async fn process_kafka_to_kafka(
-    ruleset: RuleSet,
+    detector: &Detector,
+    events: &EventFactory,
     config: KafkaConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
     use futures::stream::StreamExt;
@@ -335,28 +577,24 @@ async fn process_kafka_to_kafka(
                 if let Some(payload) = msg.payload() {
                     if let Ok(data) = std::str::from_utf8(payload) {
                         if let Ok(event) = serde_json::from_str::<Value>(data) {
-                            let dynamic_event = DynamicEvent::new(event.clone());
-                            let result = ruleset.evaluate(&dynamic_event).await?;
-
-                            for rule_match in &result.matches {
-                                if rule_match.matched {
-                                    let output = serde_json::json!({
-                                        "timestamp": chrono::Utc::now().to_rfc3339(),
-                                        "event": event,
-                                        "rule_id": rule_match.rule_id,
-                                        "rule_title": rule_match.rule_title,
-                                    });
-
-                                    let payload = serde_json::to_string(&output)?;
-                                    let record = FutureRecord::to(&config.output_topic)
-                                        .payload(&payload)
-                                        .key(&rule_match.rule_id);
-
-                                    producer
-                                        .send(record, std::time::Duration::from_secs(0))
-                                        .await
-                                        .map_err(|(e, _)| e)?;
-                                }
+                            let dynamic_event = events.create(event.clone());
+                            for detection in detector.detect(&dynamic_event).await? {
+                                let output = serde_json::json!({
+                                    "timestamp": match_time(&dynamic_event),
+                                    "event": event,
+                                    "rule_id": detection.rule_id,
+                                    "rule_title": detection.rule_title,
+                                });
+
+                                let payload = serde_json::to_string(&output)?;
+                                let record = FutureRecord::to(&config.output_topic)
+                                    .payload(&payload)
+                                    .key(&detection.rule_id);
+
+                                producer
+                                    .send(record, std::time::Duration::from_secs(0))
+                                    .await
+                                    .map_err(|(e, _)| e)?;
                             }
                         }
                     }
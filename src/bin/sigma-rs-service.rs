@@ -4,7 +4,11 @@
 //! REST and gRPC APIs for event evaluation.
 
 use clap::Parser;
-use sigma_rs::{SigmaEngine, SigmaEngineBuilder};
+use sigma_rs::event::{TimestampExtractor, TimestampFormat};
+use sigma_rs::parser::{PlaceholderRegistry, UnresolvedPlaceholder};
+use sigma_rs::pipeline::ProcessingPipeline;
+use sigma_rs::ruleset::LogsourceClassifier;
+use sigma_rs::SigmaEngineBuilder;
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -12,7 +16,11 @@ use tracing::{error, info};
 use tracing_subscriber;
 
 #[cfg(feature = "service")]
-use sigma_rs::service::{ServiceRunner, SigmaService};
+use sigma_rs::consumer::{
+    source::SyslogSource, ConsumerConfig, LogAlertSink, SigmaMessageProcessor, SourcePipeline,
+};
+#[cfg(feature = "service")]
+use sigma_rs::service::ServiceRunner;
 
 #[derive(Parser)]
 #[command(name = "sigma-rs-service")]
@@ -38,6 +46,36 @@ struct Args {
     #[arg(short, long)]
     config: Option<PathBuf>,
 
+    /// Placeholder definitions for `|expand` fields (YAML or TOML)
+    #[arg(long)]
+    placeholders: Option<PathBuf>,
+
+    /// Treat unresolved placeholders as non-matching instead of failing the rule
+    #[arg(long, requires = "placeholders")]
+    unresolved_no_match: bool,
+
+    /// Processing pipeline applied to rules before compilation (repeatable)
+    #[arg(long = "pipeline", value_name = "PATH")]
+    pipelines: Vec<PathBuf>,
+
+    /// Logsource mappings for routing events to relevant rules (YAML)
+    #[arg(long, value_name = "PATH")]
+    logsource_map: Option<PathBuf>,
+
+    /// Reject rules with unknown modifiers or malformed detections
+    #[arg(long)]
+    strict: bool,
+
+    /// Take event time from this field instead of processing time (repeatable,
+    /// dot notation for nested fields)
+    #[arg(long = "timestamp-field", value_name = "FIELD")]
+    timestamp_fields: Vec<String>,
+
+    /// Format of the event time fields (repeatable): auto (default), rfc3339, epoch_seconds,
+    /// epoch_millis, epoch_nanos, windows_file_time or a strftime pattern
+    #[arg(long = "timestamp-format", value_name = "FORMAT")]
+    timestamp_formats: Vec<TimestampFormat>,
+
     /// Enable debug logging
     #[arg(short, long)]
     debug: bool,
@@ -53,6 +91,18 @@ struct Args {
     /// Enable metrics endpoint
     #[arg(long)]
     metrics: bool,
+
+    /// Receive syslog messages over UDP on this address (e.g. 0.0.0.0:514)
+    #[arg(long, value_name = "ADDR")]
+    syslog_udp: Option<SocketAddr>,
+
+    /// Receive syslog messages over TCP on this address (e.g. 0.0.0.0:601)
+    #[arg(long, value_name = "ADDR")]
+    syslog_tcp: Option<SocketAddr>,
+
+    /// Maximum concurrent syslog TCP connections
+    #[arg(long, default_value = "256")]
+    syslog_max_connections: usize,
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -172,11 +222,50 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("Loading rules from: {}", rules_dir.display());
 
     // Build the Sigma engine
-    let engine = SigmaEngineBuilder::new()
+    let mut builder = SigmaEngineBuilder::new()
         .add_rule_dir(rules_dir.to_string_lossy())
         .fail_on_parse_error(false)
-        .build()
-        .await?;
+        .strict(args.strict);
+
+    if let Some(path) = &args.placeholders {
+        info!("Loading placeholders from: {}", path.display());
+        let mut placeholders = PlaceholderRegistry::from_file(path)?;
+        if args.unresolved_no_match {
+            placeholders = placeholders.with_unresolved(UnresolvedPlaceholder::NoMatch);
+        }
+        builder = builder.with_placeholders(placeholders);
+    }
+
+    if !args.pipelines.is_empty() {
+        let mut pipelines = Vec::with_capacity(args.pipelines.len());
+        for path in &args.pipelines {
+            info!("Loading processing pipeline from: {}", path.display());
+            pipelines.push(ProcessingPipeline::from_file(path)?);
+        }
+        builder = builder.with_pipeline(ProcessingPipeline::chain(pipelines));
+    }
+
+    if let Some(path) = &args.logsource_map {
+        info!("Loading logsource mappings from: {}", path.display());
+        builder = builder.with_logsource_classifier(LogsourceClassifier::from_file(path)?);
+    }
+
+    if !args.timestamp_fields.is_empty() || !args.timestamp_formats.is_empty() {
+        let mut extractor = TimestampExtractor::default();
+        if !args.timestamp_fields.is_empty() {
+            extractor.fields = args.timestamp_fields.clone();
+        }
+        if !args.timestamp_formats.is_empty() {
+            extractor.formats = args.timestamp_formats.clone();
+        }
+        info!(
+            "Taking event time from fields: {}",
+            extractor.fields.join(", ")
+        );
+        builder = builder.with_timestamp_extractor(extractor);
+    }
+
+    let engine = builder.build().await?;
 
     let rule_count = engine.ruleset().len();
 
@@ -216,6 +305,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             runner = runner.with_grpc(Arc::clone(&engine), grpc_addr);
         }
 
+        // Evaluate syslog messages, logging matches
+        let syslog = if args.syslog_udp.is_some() || args.syslog_tcp.is_some() {
+            let mut source = SyslogSource::new().with_max_connections(args.syslog_max_connections);
+            if let Some(addr) = args.syslog_udp {
+                source.listen_udp(addr).await?;
+            }
+            if let Some(addr) = args.syslog_tcp {
+                source.listen_tcp(addr).await?;
+            }
+
+            let processor =
+                SigmaMessageProcessor::new(Arc::clone(&engine)).with_sink(Arc::new(LogAlertSink));
+            let pipeline = SourcePipeline::new(processor, &ConsumerConfig::default());
+            Some(tokio::spawn(async move { pipeline.run(source).await }))
+        } else {
+            None
+        };
+        let syslog_enabled = syslog.is_some();
+        let has_servers = !args.no_http || !args.no_grpc;
+
         // Set up graceful shutdown
         let shutdown = tokio::signal::ctrl_c();
 
@@ -226,6 +335,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             info!("  gRPC: localhost:{}", grpc_port);
         }
 
+        if let Some(addr) = args.syslog_udp {
+            info!("  Syslog UDP: {}", addr);
+        }
+        if let Some(addr) = args.syslog_tcp {
+            info!("  Syslog TCP: {}", addr);
+        }
+
         if let Ok(api_key) = std::env::var("SIGMA_API_KEY") {
             if !api_key.is_empty() {
                 info!("  API Key authentication enabled");
@@ -237,13 +353,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         info!("Press Ctrl+C to shutdown");
 
         // Run the service or wait for shutdown
+        let syslog = async move {
+            match syslog {
+                Some(task) => task.await,
+                None => std::future::pending().await,
+            }
+        };
+
         tokio::select! {
-            result = runner.run() => {
+            // Without HTTP or gRPC the service runs for the syslog input alone
+            result = runner.run(), if has_servers || !syslog_enabled => {
                 if let Err(e) = result {
                     error!("Service error: {}", e);
                     std::process::exit(1);
                 }
             }
+            result = syslog => {
+                let result = result.map_err(|e| e.to_string()).and_then(|r| r.map_err(|e| e.to_string()));
+                if let Err(e) = result {
+                    error!("Syslog input error: {}", e);
+                    std::process::exit(1);
+                }
+            }
             _ = shutdown => {
                 info!("Shutdown signal received");
             }
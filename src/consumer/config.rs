@@ -50,6 +50,9 @@ pub struct ConsumerConfig {
     pub dlq_topic: Option<String>,
     pub dlq_after_retries: u32,
 
+    /// Topic receiving alerts for matched events
+    pub output_topic: Option<String>,
+
     /// Backpressure settings
     pub channel_buffer_size: usize,
     pub max_inflight_messages: usize,
@@ -89,6 +92,7 @@ impl Default for ConsumerConfig {
             retry_policy: crate::consumer::retry::RetryPolicy::default(),
             dlq_topic: None,
             dlq_after_retries: 3,
+            output_topic: None,
             channel_buffer_size: 1000,
             max_inflight_messages: 500,
             pause_threshold: 0.8,
@@ -168,6 +172,12 @@ impl ConsumerConfigBuilder {
         self
     }
 
+    /// Set the topic alerts are published to
+    pub fn output_topic(mut self, topic: String) -> Self {
+        self.config.output_topic = Some(topic);
+        self
+    }
+
     /// Set the channel buffer size
     pub fn channel_buffer_size(mut self, size: usize) -> Self {
         self.config.channel_buffer_size = size;
@@ -1,6 +1,7 @@
 //! Dead Letter Queue (DLQ) handling for failed messages
 
 use crate::consumer::error::{ConsumerError, ConsumerResult};
+use crate::consumer::source::Envelope;
 use rdkafka::message::{Headers, OwnedMessage};
 use rdkafka::producer::{FutureProducer, FutureRecord};
 use rdkafka::Message;
@@ -83,29 +84,110 @@ impl DlqProducer {
         }
     }
 
+    /// Send an envelope from any source to the DLQ
+    ///
+    /// The payload is copied as is; with metadata enabled, the headers hold
+    /// the envelope's position, the error and the envelope's own headers.
+    pub async fn send_envelope(
+        &self,
+        envelope: &Envelope,
+        error: &str,
+        attempts: u32,
+    ) -> ConsumerResult<()> {
+        let mut record: FutureRecord<'_, (), [u8]> =
+            FutureRecord::to(&self.topic).payload(envelope.payload.as_slice());
+
+        if self.add_metadata {
+            let position = &envelope.position;
+            let mut headers = self.metadata_headers(
+                &position.source,
+                position.partition,
+                position.offset,
+                error,
+                attempts,
+            )?;
+            for (key, value) in &envelope.headers {
+                if !key.starts_with("dlq.") {
+                    headers = headers.insert(rdkafka::message::Header {
+                        key: &format!("dlq.original.header.{}", key),
+                        value: Some(value.as_bytes()),
+                    });
+                }
+            }
+            record = record.headers(headers);
+        }
+
+        match self.producer.send(record, self.timeout).await {
+            Ok((partition, offset)) => {
+                debug!(
+                    "Envelope sent to DLQ topic: {}, partition: {}, offset: {}",
+                    self.topic, partition, offset
+                );
+                Ok(())
+            }
+            Err((e, _)) => {
+                error!("Failed to send envelope to DLQ: {}", e);
+                Err(ConsumerError::DlqError(format!("DLQ send failed: {}", e)))
+            }
+        }
+    }
+
     /// Create DLQ headers with metadata
     fn create_dlq_headers(
         &self,
         original_message: &OwnedMessage,
         error: &str,
         attempts: u32,
+    ) -> ConsumerResult<rdkafka::message::OwnedHeaders> {
+        let mut headers = self.metadata_headers(
+            original_message.topic(),
+            original_message.partition(),
+            original_message.offset(),
+            error,
+            attempts,
+        )?;
+
+        // Copy original headers if present
+        if let Some(original_headers) = original_message.headers() {
+            for header in original_headers.iter() {
+                if !header.key.starts_with("dlq.") {
+                    let key = format!("dlq.original.header.{}", header.key);
+                    headers = headers.insert(rdkafka::message::Header {
+                        key: &key,
+                        value: header.value,
+                    });
+                }
+            }
+        }
+
+        Ok(headers)
+    }
+
+    /// Create headers recording where a failed record came from and why it failed
+    fn metadata_headers(
+        &self,
+        source: &str,
+        partition: i32,
+        offset: i64,
+        error: &str,
+        attempts: u32,
     ) -> ConsumerResult<rdkafka::message::OwnedHeaders> {
         let mut headers = rdkafka::message::OwnedHeaders::new();
 
         // Add original topic and partition
         headers = headers.insert(rdkafka::message::Header {
             key: "dlq.original.topic",
-            value: Some(original_message.topic().as_bytes()),
+            value: Some(source.as_bytes()),
         });
 
         headers = headers.insert(rdkafka::message::Header {
             key: "dlq.original.partition",
-            value: Some(original_message.partition().to_string().as_bytes()),
+            value: Some(partition.to_string().as_bytes()),
         });
 
         headers = headers.insert(rdkafka::message::Header {
             key: "dlq.original.offset",
-            value: Some(original_message.offset().to_string().as_bytes()),
+            value: Some(offset.to_string().as_bytes()),
         });
 
         // Add error information
@@ -129,19 +211,6 @@ impl DlqProducer {
             value: Some(timestamp.as_secs().to_string().as_bytes()),
         });
 
-        // Copy original headers if present
-        if let Some(original_headers) = original_message.headers() {
-            for header in original_headers.iter() {
-                if !header.key.starts_with("dlq.") {
-                    let key = format!("dlq.original.header.{}", header.key);
-                    headers = headers.insert(rdkafka::message::Header {
-                        key: &key,
-                        value: header.value,
-                    });
-                }
-            }
-        }
-
         Ok(headers)
     }
 
@@ -37,6 +37,10 @@ pub enum ConsumerError {
     #[error("DLQ error: {0}")]
     DlqError(String),
 
+    /// Alert sink errors
+    #[error("Sink error: {0}")]
+    SinkError(String),
+
     /// Timeout errors
     #[error("Timeout: {0}")]
     Timeout(String),
@@ -74,6 +78,7 @@ impl ConsumerError {
             ConsumerError::Timeout(_) => true,
             ConsumerError::IoError(_) => true,
             ConsumerError::ProcessingError(_) => true,
+            ConsumerError::SinkError(_) => true,
             ConsumerError::ParseError(_) => false,
             ConsumerError::ConfigError(_) => false,
             ConsumerError::DlqError(_) => false,
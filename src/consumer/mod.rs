@@ -4,31 +4,26 @@
 //! - Robust error handling and recovery
 //! - Manual offset management
 //! - Dead letter queue support
+//! - Alert sinks for detections, acknowledged before offsets are committed
+//! - Offsets committed only up to the first message not yet handled
 //! - Backpressure control
 //! - Comprehensive metrics
 //! - Graceful shutdown
 //!
+//! The consumer runs a [`KafkaSource`](source::KafkaSource) through the same
+//! [`SourcePipeline`] as the other event sources. A message that still fails
+//! after retries is sent to the dead letter queue, if configured, and its
+//! offset is committed once it is there. Without a dead letter queue, or when
+//! sending to it fails, the offset is not committed and the consumer stops, so
+//! the message is redelivered.
+//!
 //! # Example
 //!
 //! ```no_run
-//! use sigma_rs::consumer::{ConsumerConfig, create_sigma_consumer, MessageProcessor};
-//! use sigma_rs::{SigmaEngine, DynamicEvent};
-//! use async_trait::async_trait;
+//! use sigma_rs::consumer::{create_sigma_consumer, ConsumerConfig};
+//! use sigma_rs::SigmaEngineBuilder;
 //! use std::sync::Arc;
 //!
-//! struct SigmaProcessor {
-//!     engine: Arc<SigmaEngine>,
-//! }
-//!
-//! #[async_trait]
-//! impl MessageProcessor for SigmaProcessor {
-//!     async fn process_message(&self, event: DynamicEvent) -> anyhow::Result<()> {
-//!         // Process the event
-//!         self.engine.process_event(event).await?;
-//!         Ok(())
-//!     }
-//! }
-//!
 //! # async fn example() -> anyhow::Result<()> {
 //! let config = ConsumerConfig::builder()
 //!     .brokers("localhost:9092".to_string())
@@ -37,10 +32,9 @@
 //!     .batch_size(100)
 //!     .build();
 //!
-//! let engine = Arc::new(SigmaEngine::new());
-//! let processor = Arc::new(SigmaProcessor { engine });
+//! let engine = Arc::new(SigmaEngineBuilder::new().add_rule_dir("rules").build().await?);
 //!
-//! let consumer = create_sigma_consumer(processor, config).await?;
+//! let consumer = create_sigma_consumer(engine, config).await?;
 //! consumer.run().await?;
 //! # Ok(())
 //! # }
@@ -56,6 +50,8 @@ pub mod offset_manager;
 pub mod processor;
 pub mod retry;
 pub mod shutdown;
+pub mod sink;
+pub mod source;
 
 pub use backpressure::{
     AdaptiveBackpressureConfig, AdaptiveBackpressureController, BackpressureController,
@@ -69,8 +65,9 @@ pub use offset_manager::OffsetManager;
 pub use processor::MessageProcessor;
 pub use retry::{RetryExecutor, RetryPolicy, RetryResult};
 pub use shutdown::{ShutdownCoordinator, ShutdownState};
+pub use sink::{Alert, AlertSink, KafkaAlertSink, LogAlertSink};
+pub use source::{Envelope, EnvelopeProcessor, EventSource, SourcePipeline};
 
-use crate::DynamicEvent;
 use crate::SigmaEngine;
 use rdkafka::Message;
 use std::sync::Arc;
@@ -86,49 +83,170 @@ pub fn default_config() -> ConsumerConfig {
 }
 
 /// Create a consumer for the Sigma engine
+///
+/// When `config.output_topic` is set, alerts are published to that topic.
 pub async fn create_sigma_consumer(
     engine: Arc<SigmaEngine>,
     config: ConsumerConfig,
+) -> ConsumerResult<RedpandaConsumer<SigmaMessageProcessor>> {
+    create_sigma_consumer_with_sinks(engine, config, Vec::new()).await
+}
+
+/// Create a consumer for the Sigma engine delivering alerts to `sinks`
+///
+/// When `config.output_topic` is set, alerts are also published to that topic.
+pub async fn create_sigma_consumer_with_sinks(
+    engine: Arc<SigmaEngine>,
+    config: ConsumerConfig,
+    sinks: Vec<Arc<dyn AlertSink>>,
 ) -> ConsumerResult<RedpandaConsumer<SigmaMessageProcessor>> {
     info!("Creating Sigma consumer with config: {:?}", config);
-    let processor = SigmaMessageProcessor::new(engine);
+    let processor =
+        SigmaMessageProcessor::new(engine).with_retry_policy(config.retry_policy.clone());
+    let mut processor = sinks
+        .into_iter()
+        .fold(processor, |processor, sink| processor.with_sink(sink));
+
+    if let Some(output_topic) = &config.output_topic {
+        let producer: rdkafka::producer::FutureProducer = rdkafka::ClientConfig::new()
+            .set("bootstrap.servers", &config.brokers)
+            .set("message.timeout.ms", "30000")
+            .create()
+            .map_err(|e| {
+                ConsumerError::ConnectionError(format!("Failed to create alert producer: {}", e))
+            })?;
+
+        info!("Created alert producer for topic: {}", output_topic);
+        processor = processor.with_sink(Arc::new(KafkaAlertSink::new(
+            producer,
+            output_topic.clone(),
+        )));
+    }
+
     RedpandaConsumer::new(config, processor).await
 }
 
 /// Message processor implementation for Sigma engine
+///
+/// Alerts are raised for rule matches and for the correlation rules each
+/// message completes; a correlation alert carries the completing event.
+/// Each message is evaluated once, so aggregation and correlation state count
+/// it once; only alert delivery is retried. Its errors are therefore never
+/// retryable by the consumer.
 pub struct SigmaMessageProcessor {
     engine: Arc<SigmaEngine>,
+    sinks: Vec<Arc<dyn AlertSink>>,
+    retry_policy: RetryPolicy,
 }
 
 impl SigmaMessageProcessor {
     pub fn new(engine: Arc<SigmaEngine>) -> Self {
-        Self { engine }
+        Self {
+            engine,
+            sinks: Vec::new(),
+            retry_policy: RetryPolicy::default(),
+        }
     }
-}
 
-#[async_trait::async_trait]
-impl MessageProcessor for SigmaMessageProcessor {
-    type Error = ConsumerError;
+    /// Forward matches to an alert sink, in addition to any already added
+    ///
+    /// A message only succeeds once every sink has acknowledged its alerts;
+    /// a sink may receive an alert more than once when a delivery is retried.
+    pub fn with_sink(mut self, sink: Arc<dyn AlertSink>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
 
-    async fn process(&self, message: &rdkafka::message::OwnedMessage) -> Result<(), Self::Error> {
-        // Extract payload
-        let payload = message
-            .payload()
+    /// Set the policy for retrying a failed delivery to a sink
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Evaluate a JSON event payload and deliver its alerts to the sinks
+    ///
+    /// An `event_time` reported by the source takes precedence over the payload's own fields.
+    async fn process_payload(
+        &self,
+        payload: Option<&[u8]>,
+        event_time: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> ConsumerResult<()> {
+        let payload = payload
             .ok_or_else(|| ConsumerError::ParseError("Empty message payload".to_string()))?;
 
         // Parse JSON
         let json: serde_json::Value = serde_json::from_slice(payload)
             .map_err(|e| ConsumerError::ParseError(format!("JSON parse error: {}", e)))?;
 
+        // The event is only kept for the alerts when there is a sink
+        let alert_event = (!self.sinks.is_empty()).then(|| json.clone());
+
         // Create event and process
-        let event = DynamicEvent::new(json);
-        self.engine
-            .process_event(event)
+        let mut event = self.engine.event_from_json(json);
+        if let Some(event_time) = event_time {
+            event = event.with_time(event_time);
+        }
+        let result = self
+            .engine
+            .ruleset
+            .evaluate(&event)
             .await
             .map_err(|e| ConsumerError::ProcessingError(format!("Engine error: {}", e)))?;
 
+        // Correlation state must see every event, even without a sink
+        let correlated = self.engine.correlate(&event, &result);
+
+        let Some(alert_event) = alert_event else {
+            return Ok(());
+        };
+
+        let alerts: Vec<Alert> = result
+            .matches
+            .into_iter()
+            .filter(|m| m.matched)
+            .map(|m| (m.rule_id, m.rule_title))
+            .chain(
+                correlated
+                    .into_iter()
+                    .map(|alert| (alert.rule_id, alert.rule_title)),
+            )
+            .map(|(rule_id, rule_title)| Alert::new(rule_id, rule_title, alert_event.clone()))
+            .collect();
+
+        if alerts.is_empty() {
+            return Ok(());
+        }
+
+        let executor = RetryExecutor::new(self.retry_policy.clone());
+        for sink in &self.sinks {
+            let delivery = executor
+                .execute_with_predicate(|| sink.send(&alerts), |e| e.is_retryable())
+                .await;
+            if let RetryResult::Failed { error, .. } = delivery {
+                return Err(error);
+            }
+        }
+
         Ok(())
     }
+}
+
+#[async_trait::async_trait]
+impl MessageProcessor for SigmaMessageProcessor {
+    type Error = ConsumerError;
+
+    async fn process(&self, message: &rdkafka::message::OwnedMessage) -> Result<(), Self::Error> {
+        self.process_payload(message.payload(), None).await
+    }
+
+    async fn process_record(&self, envelope: &Envelope) -> Result<(), Self::Error> {
+        self.process_payload(Some(&envelope.payload), envelope.event_time)
+            .await
+    }
+
+    fn is_retryable(&self, _error: &Self::Error) -> bool {
+        false
+    }
 
     async fn on_success(&self, _message: &rdkafka::message::OwnedMessage) {
         // Metrics will be updated here
@@ -144,3 +262,219 @@ impl MessageProcessor for SigmaMessageProcessor {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SigmaEngineBuilder;
+    use rdkafka::message::{OwnedMessage, Timestamp};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempfile::TempDir;
+    use tokio::sync::Mutex;
+
+    /// Records alerts after failing the first `failures` deliveries
+    #[derive(Debug, Default)]
+    struct RecordingSink {
+        alerts: Mutex<Vec<Alert>>,
+        failures: AtomicUsize,
+    }
+
+    impl RecordingSink {
+        fn failing(failures: usize) -> Self {
+            Self {
+                failures: AtomicUsize::new(failures),
+                ..Default::default()
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl AlertSink for RecordingSink {
+        async fn send(&self, alerts: &[Alert]) -> ConsumerResult<()> {
+            let remaining = self.failures.load(Ordering::Relaxed);
+            if remaining > 0 {
+                self.failures.store(remaining - 1, Ordering::Relaxed);
+                return Err(ConsumerError::SinkError("unavailable".to_string()));
+            }
+            self.alerts.lock().await.extend_from_slice(alerts);
+            Ok(())
+        }
+    }
+
+    async fn create_test_engine() -> Arc<SigmaEngine> {
+        create_test_engine_with(&[]).await
+    }
+
+    async fn create_test_engine_with(extra_rules: &[&str]) -> Arc<SigmaEngine> {
+        let temp_dir = TempDir::new().unwrap();
+        let rule_content = r#"
+title: Whoami Execution
+id: 12345678-1234-1234-1234-123456789001
+name: whoami_execution
+detection:
+    selection:
+        Image|endswith: '\whoami.exe'
+    condition: selection
+"#;
+        std::fs::write(temp_dir.path().join("whoami.yml"), rule_content).unwrap();
+        for (index, rule) in extra_rules.iter().enumerate() {
+            std::fs::write(temp_dir.path().join(format!("extra{}.yml", index)), rule).unwrap();
+        }
+        let engine = SigmaEngineBuilder::new()
+            .add_rule_dir(temp_dir.path().to_string_lossy())
+            .build()
+            .await
+            .unwrap();
+        Arc::new(engine)
+    }
+
+    fn message(payload: &serde_json::Value) -> OwnedMessage {
+        OwnedMessage::new(
+            Some(serde_json::to_vec(payload).unwrap()),
+            None,
+            "events".to_string(),
+            Timestamp::NotAvailable,
+            0,
+            42,
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_processor_forwards_alerts() {
+        let sink = Arc::new(RecordingSink::default());
+        let processor =
+            SigmaMessageProcessor::new(create_test_engine().await).with_sink(sink.clone());
+
+        let event = serde_json::json!({"Image": "C:\\Windows\\System32\\whoami.exe"});
+        processor.process(&message(&event)).await.unwrap();
+        processor
+            .process(&message(&serde_json::json!({"Image": "C:\\cmd.exe"})))
+            .await
+            .unwrap();
+
+        let alerts = sink.alerts.lock().await;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].rule_id, "12345678-1234-1234-1234-123456789001");
+        assert_eq!(alerts[0].event, event);
+    }
+
+    #[tokio::test]
+    async fn test_processor_forwards_correlation_alerts() {
+        let correlation = r#"
+title: Repeated Whoami
+id: 12345678-1234-1234-1234-123456789002
+correlation:
+    type: event_count
+    rules: [whoami_execution]
+    group-by: [User]
+    timespan: 1m
+    condition:
+        gte: 2
+"#;
+        let sink = Arc::new(RecordingSink::default());
+        let processor = SigmaMessageProcessor::new(create_test_engine_with(&[correlation]).await)
+            .with_sink(sink.clone());
+
+        let event =
+            serde_json::json!({"Image": "C:\\Windows\\System32\\whoami.exe", "User": "alice"});
+        processor.process(&message(&event)).await.unwrap();
+        assert_eq!(sink.alerts.lock().await.len(), 1);
+
+        // Events without the group-by field never join a group
+        let anonymous = serde_json::json!({"Image": "C:\\Windows\\System32\\whoami.exe"});
+        processor.process(&message(&anonymous)).await.unwrap();
+        assert_eq!(sink.alerts.lock().await.len(), 2);
+
+        processor.process(&message(&event)).await.unwrap();
+        let alerts = sink.alerts.lock().await;
+        assert_eq!(alerts.len(), 4);
+        assert_eq!(alerts[3].rule_id, "12345678-1234-1234-1234-123456789002");
+        assert_eq!(alerts[3].rule_title, "Repeated Whoami");
+        assert_eq!(alerts[3].event, event);
+    }
+
+    #[tokio::test]
+    async fn test_processor_retries_only_delivery() {
+        let engine = create_test_engine().await;
+        let retry_policy = RetryPolicy::fixed(2, std::time::Duration::from_millis(1));
+        let event = serde_json::json!({"Image": "C:\\Windows\\System32\\whoami.exe"});
+
+        // A sink failing once is retried alone, without evaluating the event
+        // again or delivering to the other sinks twice
+        let healthy = Arc::new(RecordingSink::default());
+        let flaky = Arc::new(RecordingSink::failing(1));
+        let processor = SigmaMessageProcessor::new(engine.clone())
+            .with_sink(healthy.clone())
+            .with_sink(flaky.clone())
+            .with_retry_policy(retry_policy.clone());
+        processor.process(&message(&event)).await.unwrap();
+        assert_eq!(healthy.alerts.lock().await.len(), 1);
+        assert_eq!(flaky.alerts.lock().await.len(), 1);
+
+        // A sink still failing after the retries fails the message for good
+        let processor = SigmaMessageProcessor::new(engine.clone())
+            .with_sink(Arc::new(RecordingSink::failing(3)))
+            .with_retry_policy(retry_policy);
+        let error = processor.process(&message(&event)).await.unwrap_err();
+        assert!(matches!(error, ConsumerError::SinkError(_)));
+        assert!(!MessageProcessor::is_retryable(&processor, &error));
+    }
+
+    #[tokio::test]
+    async fn test_undelivered_alerts_hold_back_watermark() {
+        use source::{AckHandle, Acknowledge, SourcePosition};
+
+        struct OffsetAcknowledger(OffsetManager);
+
+        #[async_trait::async_trait]
+        impl Acknowledge for OffsetAcknowledger {
+            async fn ack(&self, position: &SourcePosition) -> ConsumerResult<()> {
+                self.0
+                    .mark_offset(&position.source, position.partition, position.offset)
+                    .await;
+                Ok(())
+            }
+        }
+
+        struct VecSource(std::collections::VecDeque<Envelope>);
+
+        #[async_trait::async_trait]
+        impl EventSource for VecSource {
+            async fn next_envelope(&mut self) -> ConsumerResult<Option<Envelope>> {
+                Ok(self.0.pop_front())
+            }
+        }
+
+        let offsets = OffsetManager::new(10, std::time::Duration::from_secs(60));
+        let ack = AckHandle::new(Arc::new(OffsetAcknowledger(offsets.clone())));
+        let events = [
+            serde_json::json!({"Image": "C:\\cmd.exe"}),
+            serde_json::json!({"Image": "C:\\Windows\\System32\\whoami.exe"}),
+            serde_json::json!({"Image": "C:\\cmd.exe"}),
+        ];
+        let mut envelopes = std::collections::VecDeque::new();
+        for (offset, event) in events.iter().enumerate() {
+            offsets.track_offset("events", 0, offset as i64).await;
+            envelopes.push_back(
+                Envelope::new(
+                    serde_json::to_vec(event).unwrap(),
+                    SourcePosition::new("events", 0, offset as i64),
+                )
+                .with_ack(ack.clone()),
+            );
+        }
+
+        // A sink that never accepts the alert, and no DLQ to park the message in
+        let mut config = ConsumerConfig::default();
+        config.retry_policy = RetryPolicy::fixed(1, std::time::Duration::from_millis(1));
+        let processor = SigmaMessageProcessor::new(create_test_engine().await)
+            .with_sink(Arc::new(RecordingSink::failing(usize::MAX)))
+            .with_retry_policy(config.retry_policy.clone());
+        let pipeline = SourcePipeline::new(processor, &config);
+        assert!(pipeline.run(VecSource(envelopes)).await.is_err());
+
+        let pending = offsets.get_pending_offsets().await;
+        assert_eq!(pending.get(&("events".to_string(), 0)), Some(&0));
+    }
+}
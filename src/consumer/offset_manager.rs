@@ -1,10 +1,14 @@
 //! Manual offset management
+//!
+//! Messages of a partition may finish processing out of order, so the offset
+//! committed for a partition is its low watermark: the highest offset such
+//! that it and every tracked offset before it have been processed.
 
 use rdkafka::consumer::Consumer;
 use rdkafka::error::KafkaError;
 use rdkafka::Offset;
 use rdkafka::TopicPartitionList;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use tokio::sync::{Mutex, RwLock};
 use tracing::{debug, error, info};
@@ -12,30 +16,61 @@ use tracing::{debug, error, info};
 /// Topic name cache for string interning
 type TopicCache = Arc<RwLock<HashMap<String, Arc<String>>>>;
 
+/// Interned topic name and partition
+type PartitionKey = (Arc<String>, i32);
+
+/// Offsets of a partition not yet below its watermark
+#[derive(Debug, Default)]
+struct PartitionOffsets {
+    /// Tracked offsets above the watermark and whether each was processed
+    outstanding: BTreeMap<i64, bool>,
+    /// Highest offset such that it and every tracked offset before it were processed
+    watermark: Option<i64>,
+}
+
+impl PartitionOffsets {
+    fn set(&mut self, offset: i64, processed: bool) {
+        if self.watermark.is_some_and(|watermark| offset <= watermark) {
+            return;
+        }
+        self.outstanding.insert(offset, processed);
+
+        while let Some(entry) = self.outstanding.first_entry() {
+            if !*entry.get() {
+                break;
+            }
+            self.watermark = Some(entry.remove_entry().0);
+        }
+    }
+}
+
 /// Manages Kafka offsets with batching and error handling
 #[derive(Clone)]
 pub struct OffsetManager {
-    /// Pending offsets to commit
-    pending_offsets: Arc<Mutex<HashMap<(Arc<String>, i32), i64>>>,
+    /// Tracked offsets and watermark of each partition
+    partitions: Arc<Mutex<HashMap<PartitionKey, PartitionOffsets>>>,
     /// Committed offsets
-    committed_offsets: Arc<Mutex<HashMap<(Arc<String>, i32), i64>>>,
+    committed_offsets: Arc<Mutex<HashMap<PartitionKey, i64>>>,
     /// Topic name cache for string interning
     topic_cache: TopicCache,
     /// Batch size for offset commits
     batch_size: usize,
     /// Maximum time between commits
-    _commit_interval: std::time::Duration,
+    commit_interval: std::time::Duration,
+    /// When offsets were last committed
+    last_commit: Arc<Mutex<std::time::Instant>>,
 }
 
 impl OffsetManager {
     /// Create a new offset manager
     pub fn new(batch_size: usize, commit_interval: std::time::Duration) -> Self {
         Self {
-            pending_offsets: Arc::new(Mutex::new(HashMap::new())),
+            partitions: Arc::new(Mutex::new(HashMap::new())),
             committed_offsets: Arc::new(Mutex::new(HashMap::new())),
             topic_cache: Arc::new(RwLock::new(HashMap::new())),
             batch_size,
-            _commit_interval: commit_interval,
+            commit_interval,
+            last_commit: Arc::new(Mutex::new(std::time::Instant::now())),
         }
     }
 
@@ -57,21 +92,50 @@ impl OffsetManager {
             .clone()
     }
 
-    /// Mark an offset for commit
-    pub async fn mark_offset(&self, topic: &str, partition: i32, offset: i64) {
+    async fn set_processed(&self, topic: &str, partition: i32, offset: i64, processed: bool) {
         let interned_topic = self.intern_topic(topic).await;
-        let mut pending = self.pending_offsets.lock().await;
-        pending.insert((interned_topic, partition), offset);
+        let mut partitions = self.partitions.lock().await;
+        partitions
+            .entry((interned_topic, partition))
+            .or_default()
+            .set(offset, processed);
+    }
+
+    /// Track a message read for processing
+    ///
+    /// The partition's watermark does not pass a tracked offset until it is
+    /// marked as processed.
+    pub async fn track_offset(&self, topic: &str, partition: i32, offset: i64) {
+        self.set_processed(topic, partition, offset, false).await;
+    }
+
+    /// Mark an offset as processed, advancing the watermark over every
+    /// processed offset directly above it
+    pub async fn mark_offset(&self, topic: &str, partition: i32, offset: i64) {
+        self.set_processed(topic, partition, offset, true).await;
 
         debug!(
-            "Marked offset {} for topic {} partition {} for commit",
+            "Marked offset {} for topic {} partition {} as processed",
             offset, topic, partition
         );
     }
 
-    /// Commit pending offsets
+    /// Watermarks above the committed offsets
+    async fn uncommitted(&self) -> Vec<(PartitionKey, i64)> {
+        let partitions = self.partitions.lock().await;
+        let committed = self.committed_offsets.lock().await;
+        partitions
+            .iter()
+            .filter_map(|(key, offsets)| {
+                let watermark = offsets.watermark?;
+                (committed.get(key) < Some(&watermark)).then(|| (key.clone(), watermark))
+            })
+            .collect()
+    }
+
+    /// Commit each partition's watermark
     pub async fn commit_offsets<C: Consumer>(&self, consumer: &C) -> Result<(), KafkaError> {
-        let mut pending = self.pending_offsets.lock().await;
+        let pending = self.uncommitted().await;
 
         if pending.is_empty() {
             return Ok(());
@@ -79,7 +143,7 @@ impl OffsetManager {
 
         let mut tpl = TopicPartitionList::new();
 
-        for ((topic, partition), offset) in pending.iter() {
+        for ((topic, partition), offset) in &pending {
             tpl.add_partition_offset(topic.as_str(), *partition, Offset::Offset(*offset + 1))?;
         }
 
@@ -89,11 +153,11 @@ impl OffsetManager {
             Ok(()) => {
                 info!("Successfully committed {} offsets", pending.len());
 
-                // Move to committed offsets
                 let mut committed = self.committed_offsets.lock().await;
-                for (key, value) in pending.drain() {
+                for (key, value) in pending {
                     committed.insert(key, value);
                 }
+                *self.last_commit.lock().await = std::time::Instant::now();
 
                 Ok(())
             }
@@ -111,31 +175,33 @@ impl OffsetManager {
         committed.get(&(interned_topic, partition)).copied()
     }
 
-    /// Get pending offsets count
+    /// Number of partitions whose watermark is above their committed offset
     pub async fn pending_count(&self) -> usize {
-        let pending = self.pending_offsets.lock().await;
-        pending.len()
+        self.uncommitted().await.len()
     }
 
-    /// Check if we should commit based on batch size
+    /// Check if we should commit, because `batch_size` partitions have
+    /// pending offsets or the commit interval has passed with any pending
     pub async fn should_commit(&self) -> bool {
-        self.pending_count().await >= self.batch_size
+        let pending = self.pending_count().await;
+        pending >= self.batch_size
+            || (pending > 0 && self.last_commit.lock().await.elapsed() >= self.commit_interval)
     }
 
     /// Reset all offsets (useful for testing)
     pub async fn reset(&self) {
-        let mut pending = self.pending_offsets.lock().await;
+        let mut partitions = self.partitions.lock().await;
         let mut committed = self.committed_offsets.lock().await;
-        pending.clear();
+        partitions.clear();
         committed.clear();
     }
 
-    /// Get all pending offsets (for debugging)
+    /// Get the watermarks waiting to be committed (for debugging)
     pub async fn get_pending_offsets(&self) -> HashMap<(String, i32), i64> {
-        let pending = self.pending_offsets.lock().await;
-        pending
-            .iter()
-            .map(|((topic, partition), offset)| ((topic.as_ref().clone(), *partition), *offset))
+        self.uncommitted()
+            .await
+            .into_iter()
+            .map(|((topic, partition), offset)| ((topic.as_ref().clone(), partition), offset))
             .collect()
     }
 
@@ -211,6 +277,36 @@ mod tests {
         assert_eq!(pending.get(&("test-topic".to_string(), 1)), Some(&200));
     }
 
+    #[tokio::test]
+    async fn test_watermark_waits_for_earlier_offsets() {
+        let manager = OffsetManager::new(10, std::time::Duration::from_secs(60));
+        let watermark = |manager: &OffsetManager| {
+            let manager = manager.clone();
+            async move {
+                manager
+                    .get_pending_offsets()
+                    .await
+                    .get(&("t".to_string(), 0))
+                    .copied()
+            }
+        };
+
+        for offset in 0..4 {
+            manager.track_offset("t", 0, offset).await;
+        }
+
+        // A later success does not commit past an unfinished message
+        manager.mark_offset("t", 0, 2).await;
+        assert_eq!(watermark(&manager).await, None);
+        manager.mark_offset("t", 0, 0).await;
+        assert_eq!(watermark(&manager).await, Some(0));
+        manager.mark_offset("t", 0, 1).await;
+        assert_eq!(watermark(&manager).await, Some(2));
+
+        manager.mark_offset("t", 0, 3).await;
+        assert_eq!(watermark(&manager).await, Some(3));
+    }
+
     #[test]
     fn test_commit_strategy() {
         let strategy = CommitStrategy::BatchOrInterval(100, std::time::Duration::from_secs(60));
@@ -1,10 +1,15 @@
 //! Message processor trait and implementations
 
+use crate::consumer::source::{Envelope, EnvelopeProcessor};
 use async_trait::async_trait;
 use rdkafka::message::OwnedMessage;
 use std::fmt::Debug;
 
 /// Trait for processing Kafka messages
+///
+/// Every `MessageProcessor` is an [`EnvelopeProcessor`], so it can run in a
+/// [`RedpandaConsumer`](crate::consumer::RedpandaConsumer) or any
+/// [`SourcePipeline`](crate::consumer::SourcePipeline).
 #[async_trait]
 pub trait MessageProcessor: Send + Sync + 'static {
     /// Error type for processing
@@ -13,6 +18,14 @@ pub trait MessageProcessor: Send + Sync + 'static {
     /// Process a single message
     async fn process(&self, message: &OwnedMessage) -> Result<(), Self::Error>;
 
+    /// Process an envelope read from an event source
+    ///
+    /// By default the envelope is rebuilt as a Kafka message for
+    /// [`process`](Self::process).
+    async fn process_record(&self, envelope: &Envelope) -> Result<(), Self::Error> {
+        self.process(&envelope.to_message()).await
+    }
+
     /// Called when a message is successfully processed
     async fn on_success(&self, message: &OwnedMessage);
 
@@ -40,6 +53,25 @@ pub trait MessageProcessor: Send + Sync + 'static {
     }
 }
 
+#[async_trait]
+impl<M: MessageProcessor> EnvelopeProcessor for M {
+    type Error = M::Error;
+
+    async fn process_envelope(&self, envelope: &Envelope) -> Result<(), Self::Error> {
+        self.process_record(envelope).await?;
+        self.on_success(&envelope.to_message()).await;
+        Ok(())
+    }
+
+    async fn on_envelope_failure(&self, error: &Self::Error, envelope: &Envelope) {
+        self.on_failure(error, &envelope.to_message()).await;
+    }
+
+    fn is_retryable(&self, error: &Self::Error) -> bool {
+        MessageProcessor::is_retryable(self, error)
+    }
+}
+
 /// Batch processor trait for processing multiple messages at once
 #[async_trait]
 pub trait BatchProcessor: Send + Sync + 'static {
@@ -16,6 +16,8 @@ pub struct ShutdownState {
     inflight_messages: AtomicUsize,
     /// Shutdown initiated timestamp
     shutdown_start: tokio::sync::RwLock<Option<Instant>>,
+    /// Wakes tasks waiting for shutdown to be initiated
+    shutdown_notify: tokio::sync::Notify,
 }
 
 impl ShutdownState {
@@ -26,6 +28,7 @@ impl ShutdownState {
             shutdown_complete: AtomicBool::new(false),
             inflight_messages: AtomicUsize::new(0),
             shutdown_start: tokio::sync::RwLock::new(None),
+            shutdown_notify: tokio::sync::Notify::new(),
         }
     }
 
@@ -34,9 +37,20 @@ impl ShutdownState {
         self.shutting_down.store(true, Ordering::Relaxed);
         let mut shutdown_start = self.shutdown_start.write().await;
         *shutdown_start = Some(Instant::now());
+        self.shutdown_notify.notify_waiters();
         info!("Shutdown initiated");
     }
 
+    /// Wait until shutdown has been initiated
+    pub async fn shutdown_requested(&self) {
+        // Register before checking so a concurrent begin_shutdown is not missed
+        let notified = self.shutdown_notify.notified();
+        if self.is_shutting_down() {
+            return;
+        }
+        notified.await;
+    }
+
     /// Check if shutdown is in progress
     pub fn is_shutting_down(&self) -> bool {
         self.shutting_down.load(Ordering::Relaxed)
@@ -171,6 +185,7 @@ mod tests {
 
         state.begin_shutdown().await;
         assert!(state.is_shutting_down());
+        state.shutdown_requested().await;
 
         state.add_inflight_message().await;
         assert!(state.has_inflight_messages().await);
@@ -183,6 +198,28 @@ mod tests {
         assert!(state.is_shutdown_complete());
     }
 
+    #[tokio::test]
+    async fn test_shutdown_requested() {
+        let state = Arc::new(ShutdownState::new());
+
+        let waiter = tokio::spawn({
+            let state = state.clone();
+            async move { state.shutdown_requested().await }
+        });
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!waiter.is_finished());
+
+        ShutdownCoordinator::new(state.clone(), Duration::from_secs(1))
+            .shutdown()
+            .await
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
     #[tokio::test]
     async fn test_shutdown_coordinator() {
         let state = Arc::new(ShutdownState::new());
@@ -0,0 +1,163 @@
+//! Alert sinks receiving the detections produced by the consumer
+
+use crate::consumer::error::{ConsumerError, ConsumerResult};
+use async_trait::async_trait;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// A rule match on a consumed event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Alert {
+    /// Detection time (RFC 3339)
+    pub timestamp: String,
+    /// The event that matched
+    pub event: serde_json::Value,
+    /// ID of the matching rule
+    pub rule_id: String,
+    /// Title of the matching rule
+    pub rule_title: String,
+}
+
+impl Alert {
+    /// Create an alert for a rule match detected now
+    pub fn new(rule_id: String, rule_title: String, event: serde_json::Value) -> Self {
+        Self {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            event,
+            rule_id,
+            rule_title,
+        }
+    }
+}
+
+/// Destination for alerts
+///
+/// A successful return acknowledges the alerts. Failed deliveries are
+/// retried without evaluating the message again; a message whose alerts still
+/// cannot be delivered is reported and sent to the dead letter queue, where it
+/// can be replayed, before its offset is committed. Without a dead letter
+/// queue its offset is never committed, so the message is redelivered.
+#[async_trait]
+pub trait AlertSink: std::fmt::Debug + Send + Sync + 'static {
+    /// Deliver the alerts raised by a single message
+    async fn send(&self, alerts: &[Alert]) -> ConsumerResult<()>;
+
+    /// Get sink name for logging
+    fn name(&self) -> &str {
+        "AlertSink"
+    }
+}
+
+/// Sink publishing alerts as JSON to a Kafka topic, keyed by rule ID
+#[derive(Clone)]
+pub struct KafkaAlertSink {
+    producer: Arc<FutureProducer>,
+    topic: String,
+    timeout: Duration,
+}
+
+impl KafkaAlertSink {
+    /// Create a new Kafka alert sink
+    pub fn new(producer: FutureProducer, topic: String) -> Self {
+        Self {
+            producer: Arc::new(producer),
+            topic,
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Set the send timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl std::fmt::Debug for KafkaAlertSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KafkaAlertSink")
+            .field("topic", &self.topic)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl AlertSink for KafkaAlertSink {
+    async fn send(&self, alerts: &[Alert]) -> ConsumerResult<()> {
+        let payloads = alerts
+            .iter()
+            .map(|alert| {
+                serde_json::to_vec(alert)
+                    .map(|payload| (alert.rule_id.as_str(), payload))
+                    .map_err(|e| {
+                        ConsumerError::SinkError(format!("JSON serialization error: {}", e))
+                    })
+            })
+            .collect::<ConsumerResult<Vec<_>>>()?;
+
+        // Wait for every delivery report before acknowledging
+        let deliveries = payloads.iter().map(|(key, payload)| {
+            let record = FutureRecord::to(&self.topic).key(*key).payload(payload);
+            self.producer.send(record, self.timeout)
+        });
+
+        for delivery in futures::future::join_all(deliveries).await {
+            let (partition, offset) = delivery
+                .map_err(|(e, _)| ConsumerError::SinkError(format!("Alert send failed: {}", e)))?;
+            debug!(
+                "Alert sent to topic: {}, partition: {}, offset: {}",
+                self.topic, partition, offset
+            );
+        }
+
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "KafkaAlertSink"
+    }
+}
+
+/// Sink writing alerts to the log
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogAlertSink;
+
+#[async_trait]
+impl AlertSink for LogAlertSink {
+    async fn send(&self, alerts: &[Alert]) -> ConsumerResult<()> {
+        for alert in alerts {
+            warn!(
+                "Rule matched: {} ({}) on event {}",
+                alert.rule_title, alert.rule_id, alert.event
+            );
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "LogAlertSink"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alert_serialization() {
+        let alert = Alert::new(
+            "rule-1".to_string(),
+            "Test Rule".to_string(),
+            serde_json::json!({"EventID": 1}),
+        );
+
+        let json = serde_json::to_value(&alert).unwrap();
+        assert_eq!(json["rule_id"], "rule-1");
+        assert_eq!(json["event"]["EventID"], 1);
+        assert!(chrono::DateTime::parse_from_rfc3339(json["timestamp"].as_str().unwrap()).is_ok());
+    }
+}
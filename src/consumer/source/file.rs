@@ -0,0 +1,395 @@
+//! Line-delimited files as an event source, following appended lines
+
+use super::{AckHandle, Acknowledge, Envelope, EventSource, SourcePosition};
+use crate::consumer::error::ConsumerResult;
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::fs::File;
+use tokio::io::{AsyncBufReadExt, AsyncSeekExt, BufReader};
+use tokio::sync::Mutex;
+use tracing::{debug, info};
+
+/// Source reading one envelope per line of a file, like `tail -F`
+///
+/// Blank lines are skipped. When following, the source waits for new lines
+/// at end of file and reopens the path when the file is truncated or
+/// replaced by rotation. Positions hold the byte offset of each line, with
+/// the partition counting reopenings.
+///
+/// With a checkpoint file, the offset up to which every line has been
+/// acknowledged is persisted, and reading resumes from it on the next open.
+pub struct FileTailSource {
+    path: PathBuf,
+    name: String,
+    reader: BufReader<File>,
+    file_id: u64,
+    /// Offset of the first byte of `partial`
+    line_start: u64,
+    /// Bytes read of the current line
+    partial: Vec<u8>,
+    /// Number of times the file was reopened after truncation or rotation
+    generation: i32,
+    follow: bool,
+    poll_interval: Duration,
+    checkpoint: Arc<FileCheckpoint>,
+}
+
+/// Tracks acknowledgements to find the offset every earlier line is processed by
+struct FileCheckpoint {
+    path: Option<PathBuf>,
+    state: Mutex<CheckpointState>,
+}
+
+struct CheckpointState {
+    generation: i32,
+    /// Line start to line end offsets of unacknowledged lines
+    inflight: BTreeMap<u64, u64>,
+    /// End offset of the last line handed out
+    read: u64,
+    acknowledged: u64,
+}
+
+impl FileTailSource {
+    /// Open a file, reading from its first line
+    pub async fn open(path: impl AsRef<Path>) -> ConsumerResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::open(&path).await?;
+        let file_id = file_id(&file.metadata().await?);
+
+        Ok(Self {
+            name: path.display().to_string(),
+            path,
+            reader: BufReader::new(file),
+            file_id,
+            line_start: 0,
+            partial: Vec::new(),
+            generation: 0,
+            follow: true,
+            poll_interval: Duration::from_millis(250),
+            checkpoint: Arc::new(FileCheckpoint::new(None, 0)),
+        })
+    }
+
+    /// Start reading at the current end of the file, skipping existing lines
+    pub async fn from_end(mut self) -> ConsumerResult<Self> {
+        let end = self.reader.seek(SeekFrom::End(0)).await?;
+        self.start_at(end);
+        Ok(self)
+    }
+
+    /// Persist acknowledged offsets to `path`, resuming from the offset it
+    /// holds if the file exists
+    ///
+    /// A checkpoint beyond the end of the file is ignored, as the file was
+    /// truncated or replaced since it was written.
+    pub async fn with_checkpoint(mut self, path: impl AsRef<Path>) -> ConsumerResult<Self> {
+        let path = path.as_ref().to_path_buf();
+
+        if let Ok(contents) = tokio::fs::read_to_string(&path).await {
+            let len = self.reader.get_ref().metadata().await?.len();
+            match contents.trim().parse::<u64>() {
+                Ok(offset) if offset <= len => {
+                    self.reader.seek(SeekFrom::Start(offset)).await?;
+                    self.start_at(offset);
+                    info!("Resuming {} from checkpoint offset {}", self.name, offset);
+                }
+                _ => info!("Ignoring stale checkpoint {}", path.display()),
+            }
+        }
+
+        self.checkpoint = Arc::new(FileCheckpoint::new(Some(path), self.line_start));
+        Ok(self)
+    }
+
+    /// Set whether to wait for new lines at end of file (default: true)
+    ///
+    /// When not following, the source is exhausted at end of file.
+    pub fn follow(mut self, follow: bool) -> Self {
+        self.follow = follow;
+        self
+    }
+
+    /// Set how often to check for new lines at end of file
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Get the offset up to which every line has been acknowledged
+    pub async fn acknowledged_offset(&self) -> u64 {
+        self.checkpoint.state.lock().await.acknowledged
+    }
+
+    fn start_at(&mut self, offset: u64) {
+        self.line_start = offset;
+        self.partial.clear();
+        self.checkpoint = Arc::new(FileCheckpoint::new(self.checkpoint.path.clone(), offset));
+    }
+
+    /// Reopen the path if the file was truncated or replaced
+    async fn reopen_if_rotated(&mut self) -> ConsumerResult<bool> {
+        let metadata = match tokio::fs::metadata(&self.path).await {
+            Ok(metadata) => metadata,
+            // Rotated away and not yet recreated
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+
+        let position = self.line_start + self.partial.len() as u64;
+        if metadata.len() >= position && file_id(&metadata) == self.file_id {
+            return Ok(false);
+        }
+
+        info!(
+            "{} was truncated or rotated, reading from the start",
+            self.name
+        );
+        let file = File::open(&self.path).await?;
+        self.file_id = file_id(&file.metadata().await?);
+        self.reader = BufReader::new(file);
+        self.line_start = 0;
+        self.partial.clear();
+        self.generation += 1;
+        self.checkpoint.reset(self.generation).await?;
+        Ok(true)
+    }
+}
+
+#[async_trait]
+impl EventSource for FileTailSource {
+    async fn next_envelope(&mut self) -> ConsumerResult<Option<Envelope>> {
+        loop {
+            // Bytes read before a cancellation stay in `partial`
+            let read = self.reader.read_until(b'\n', &mut self.partial).await?;
+            let terminated = self.partial.last() == Some(&b'\n');
+
+            if terminated || (read == 0 && !self.follow && !self.partial.is_empty()) {
+                let start = self.line_start;
+                let end = start + self.partial.len() as u64;
+                self.line_start = end;
+
+                let mut line = std::mem::take(&mut self.partial);
+                while matches!(line.last(), Some(b'\n' | b'\r')) {
+                    line.pop();
+                }
+                if line.iter().all(u8::is_ascii_whitespace) {
+                    continue;
+                }
+
+                self.checkpoint.register(self.generation, start, end).await;
+                let position = SourcePosition::new(&self.name, self.generation, start as i64);
+                return Ok(Some(
+                    Envelope::new(line, position).with_ack(AckHandle::new(self.checkpoint.clone())),
+                ));
+            }
+
+            if read > 0 {
+                // End of file in the middle of a line; wait for the rest
+                continue;
+            }
+            if !self.follow {
+                return Ok(None);
+            }
+            if !self.reopen_if_rotated().await? {
+                tokio::time::sleep(self.poll_interval).await;
+            }
+        }
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl FileCheckpoint {
+    fn new(path: Option<PathBuf>, offset: u64) -> Self {
+        Self {
+            path,
+            state: Mutex::new(CheckpointState {
+                generation: 0,
+                inflight: BTreeMap::new(),
+                read: offset,
+                acknowledged: offset,
+            }),
+        }
+    }
+
+    async fn register(&self, generation: i32, start: u64, end: u64) {
+        let mut state = self.state.lock().await;
+        if state.generation == generation {
+            state.inflight.insert(start, end);
+            state.read = end;
+        }
+    }
+
+    /// Start tracking a reopened file from its first byte
+    async fn reset(&self, generation: i32) -> ConsumerResult<()> {
+        let mut state = self.state.lock().await;
+        state.generation = generation;
+        state.inflight.clear();
+        state.read = 0;
+        state.acknowledged = 0;
+        self.persist(0).await
+    }
+
+    async fn persist(&self, offset: u64) -> ConsumerResult<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        // Replace the checkpoint atomically so a crash never leaves it partial
+        let tmp = path.with_extension("tmp");
+        tokio::fs::write(&tmp, offset.to_string()).await?;
+        tokio::fs::rename(&tmp, path).await?;
+        debug!("Checkpointed offset {} to {}", offset, path.display());
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Acknowledge for FileCheckpoint {
+    async fn ack(&self, position: &SourcePosition) -> ConsumerResult<()> {
+        let mut state = self.state.lock().await;
+
+        // Lines of a file since truncated or rotated no longer move the checkpoint
+        if position.partition != state.generation {
+            return Ok(());
+        }
+
+        state.inflight.remove(&(position.offset as u64));
+        let acknowledged = state.inflight.keys().next().copied().unwrap_or(state.read);
+
+        if acknowledged > state.acknowledged {
+            state.acknowledged = acknowledged;
+            self.persist(acknowledged).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn file_id(metadata: &std::fs::Metadata) -> u64 {
+    std::os::unix::fs::MetadataExt::ino(metadata)
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &std::fs::Metadata) -> u64 {
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use tokio::io::AsyncWriteExt;
+
+    async fn payloads(source: &mut FileTailSource, count: usize) -> Vec<Envelope> {
+        let mut envelopes = Vec::new();
+        for _ in 0..count {
+            let envelope = tokio::time::timeout(Duration::from_secs(5), source.next_envelope())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            envelopes.push(envelope);
+        }
+        envelopes
+    }
+
+    #[tokio::test]
+    async fn test_read_lines() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("events.jsonl");
+        std::fs::write(&path, "{\"a\":1}\r\n\n{\"a\":2}\n{\"a\":3}").unwrap();
+
+        let mut source = FileTailSource::open(&path).await.unwrap().follow(false);
+        let envelopes = payloads(&mut source, 3).await;
+        assert!(source.next_envelope().await.unwrap().is_none());
+
+        let lines: Vec<_> = envelopes.iter().map(|e| e.payload.as_slice()).collect();
+        assert_eq!(lines, [&b"{\"a\":1}"[..], b"{\"a\":2}", b"{\"a\":3}"]);
+        let offsets: Vec<_> = envelopes.iter().map(|e| e.position.offset).collect();
+        assert_eq!(offsets, [0, 10, 18]);
+    }
+
+    #[tokio::test]
+    async fn test_follow_appends_and_truncation() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("events.jsonl");
+        std::fs::write(&path, "old\n").unwrap();
+
+        let mut source = FileTailSource::open(&path)
+            .await
+            .unwrap()
+            .from_end()
+            .await
+            .unwrap()
+            .with_poll_interval(Duration::from_millis(10));
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .await
+            .unwrap();
+        file.write_all(b"par").await.unwrap();
+        file.flush().await.unwrap();
+
+        let reader = tokio::spawn(async move {
+            let envelopes = payloads(&mut source, 2).await;
+            (source, envelopes)
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        file.write_all(b"tial\n").await.unwrap();
+        file.flush().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        std::fs::write(&path, "new\n").unwrap();
+
+        let (_, envelopes) = reader.await.unwrap();
+        assert_eq!(envelopes[0].payload, b"partial");
+        assert_eq!(envelopes[0].position.offset, 4);
+        assert_eq!(envelopes[1].payload, b"new");
+        assert_eq!(
+            envelopes[1].position,
+            SourcePosition::new(path.display().to_string(), 1, 0)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_resume() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("events.jsonl");
+        let checkpoint = dir.path().join("events.offset");
+        std::fs::write(&path, "one\ntwo\nthree\n").unwrap();
+
+        let mut source = FileTailSource::open(&path)
+            .await
+            .unwrap()
+            .with_checkpoint(&checkpoint)
+            .await
+            .unwrap()
+            .follow(false);
+        let envelopes = payloads(&mut source, 3).await;
+
+        // Out of order acknowledgements only advance past contiguous lines
+        envelopes[1].ack().await.unwrap();
+        assert_eq!(source.acknowledged_offset().await, 0);
+        envelopes[0].ack().await.unwrap();
+        assert_eq!(source.acknowledged_offset().await, 8);
+        assert_eq!(std::fs::read_to_string(&checkpoint).unwrap(), "8");
+
+        let mut resumed = FileTailSource::open(&path)
+            .await
+            .unwrap()
+            .with_checkpoint(&checkpoint)
+            .await
+            .unwrap()
+            .follow(false);
+        let envelopes = payloads(&mut resumed, 1).await;
+        assert_eq!(envelopes[0].payload, b"three");
+        assert!(resumed.next_envelope().await.unwrap().is_none());
+    }
+}
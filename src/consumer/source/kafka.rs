@@ -0,0 +1,159 @@
+//! Kafka/Redpanda topics as an event source
+
+use super::{AckHandle, Acknowledge, Envelope, EventSource, SourcePosition};
+use crate::consumer::{
+    config::ConsumerConfig,
+    consumer::create_stream_consumer,
+    error::{ConsumerError, ConsumerResult},
+    metrics::ConsumerMetrics,
+    offset_manager::OffsetManager,
+};
+use async_trait::async_trait;
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::error::KafkaError;
+use rdkafka::message::{Headers, Message};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, warn};
+
+/// Longest time acknowledged offsets wait for a commit while messages arrive
+const COMMIT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Source reading envelopes from the configured Kafka topics
+///
+/// Offsets are committed through an [`OffsetManager`] up to each partition's
+/// first unacknowledged message, so acknowledgements arriving out of order
+/// never commit past a message still being processed. Commits happen once
+/// `batch_size` partitions have pending offsets or five seconds after the
+/// last one, and when the source is closed.
+///
+/// Consumption errors are logged, counted as `kafka_error` and skipped;
+/// only fatal client errors end the source.
+pub struct KafkaSource {
+    consumer: Arc<StreamConsumer>,
+    acknowledger: Arc<KafkaAcknowledger>,
+    metrics: Arc<ConsumerMetrics>,
+}
+
+struct KafkaAcknowledger {
+    consumer: Arc<StreamConsumer>,
+    offset_manager: Arc<OffsetManager>,
+}
+
+impl KafkaSource {
+    /// Create a consumer subscribed to the configured topics
+    pub async fn new(config: &ConsumerConfig) -> ConsumerResult<Self> {
+        config.validate().map_err(ConsumerError::ConfigError)?;
+
+        let consumer = Arc::new(create_stream_consumer(config).await?);
+        let offset_manager = Arc::new(OffsetManager::new(config.batch_size, COMMIT_INTERVAL));
+
+        Ok(Self {
+            acknowledger: Arc::new(KafkaAcknowledger {
+                consumer: consumer.clone(),
+                offset_manager,
+            }),
+            consumer,
+            metrics: Arc::new(ConsumerMetrics::new()),
+        })
+    }
+
+    /// Count consumption errors in `metrics`, such as a pipeline's
+    pub fn with_metrics(mut self, metrics: Arc<ConsumerMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Commit the offsets of all acknowledged messages
+    pub async fn commit(&self) -> ConsumerResult<()> {
+        self.acknowledger
+            .offset_manager
+            .commit_offsets(&*self.consumer)
+            .await
+            .map_err(ConsumerError::from)
+    }
+}
+
+#[async_trait]
+impl Acknowledge for KafkaAcknowledger {
+    async fn ack(&self, position: &SourcePosition) -> ConsumerResult<()> {
+        self.offset_manager
+            .mark_offset(&position.source, position.partition, position.offset)
+            .await;
+
+        if self.offset_manager.should_commit().await {
+            self.offset_manager
+                .commit_offsets(&*self.consumer)
+                .await
+                .map_err(ConsumerError::from)?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EventSource for KafkaSource {
+    async fn next_envelope(&mut self) -> ConsumerResult<Option<Envelope>> {
+        let message = loop {
+            match self.consumer.recv().await {
+                Ok(message) => break message,
+                Err(e @ KafkaError::MessageConsumptionFatal(_)) => {
+                    error!("Fatal Kafka error: {}", e);
+                    return Err(e.into());
+                }
+                Err(e) => {
+                    warn!("Kafka error: {}", e);
+                    self.metrics.record_error("kafka_error");
+                }
+            }
+        };
+        self.acknowledger
+            .offset_manager
+            .track_offset(message.topic(), message.partition(), message.offset())
+            .await;
+
+        let mut envelope = Envelope::new(
+            message.payload().map(<[u8]>::to_vec).unwrap_or_default(),
+            SourcePosition::new(message.topic(), message.partition(), message.offset()),
+        )
+        .with_ack(AckHandle::new(self.acknowledger.clone()));
+
+        if let Some(headers) = message.headers() {
+            for header in headers.iter() {
+                if let Some(value) = header.value {
+                    envelope = envelope.with_header(header.key, String::from_utf8_lossy(value));
+                }
+            }
+        }
+
+        Ok(Some(envelope))
+    }
+
+    async fn close(&mut self) -> ConsumerResult<()> {
+        self.commit().await
+    }
+
+    fn pause(&mut self) {
+        let paused = self
+            .consumer
+            .assignment()
+            .and_then(|assignment| self.consumer.pause(&assignment));
+        if let Err(e) = paused {
+            warn!("Failed to pause partitions: {}", e);
+        }
+    }
+
+    fn resume(&mut self) {
+        let resumed = self
+            .consumer
+            .assignment()
+            .and_then(|assignment| self.consumer.resume(&assignment));
+        if let Err(e) = resumed {
+            warn!("Failed to resume partitions: {}", e);
+        }
+    }
+
+    fn name(&self) -> &str {
+        "KafkaSource"
+    }
+}
@@ -0,0 +1,658 @@
+//! Source-agnostic event input
+//!
+//! An [`EventSource`] yields [`Envelope`]s: a raw payload with headers, the
+//! position it was read from and a handle acknowledging it once processed.
+//! [`SourcePipeline`] drives any source through backpressure, retries,
+//! dead-lettering and metrics; the Kafka consumer is a pipeline over a
+//! [`KafkaSource`], so file, socket and Kafka inputs behave alike.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sigma_rs::consumer::source::{FileTailSource, SourcePipeline};
+//! use sigma_rs::consumer::{ConsumerConfig, SigmaMessageProcessor};
+//! use sigma_rs::SigmaEngineBuilder;
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let engine = Arc::new(SigmaEngineBuilder::new().add_rule_dir("rules").build().await?);
+//! let source = FileTailSource::open("/var/log/events.jsonl")
+//!     .await?
+//!     .with_checkpoint("/var/lib/sigma/events.offset")
+//!     .await?;
+//!
+//! let pipeline = SourcePipeline::new(SigmaMessageProcessor::new(engine), &ConsumerConfig::default());
+//! pipeline.run(source).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod file;
+pub mod kafka;
+pub mod syslog;
+
+pub use file::FileTailSource;
+pub use kafka::KafkaSource;
+pub use syslog::{SyslogMessage, SyslogSource};
+
+use crate::consumer::{
+    backpressure::BackpressureController,
+    config::ConsumerConfig,
+    dlq::DlqProducer,
+    error::{ConsumerError, ConsumerResult},
+    metrics::ConsumerMetrics,
+    retry::{RetryExecutor, RetryPolicy, RetryResult},
+    shutdown::ShutdownState,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rdkafka::message::{Header, OwnedHeaders, OwnedMessage, Timestamp};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+/// Where an envelope was read from
+///
+/// For Kafka this is the topic, partition and offset; sources without
+/// partitions use partition 0 and an offset meaningful to the source, such
+/// as a byte offset into a file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SourcePosition {
+    /// Topic, file path or listener address
+    pub source: String,
+    /// Partition within the source
+    pub partition: i32,
+    /// Offset within the partition
+    pub offset: i64,
+}
+
+impl SourcePosition {
+    /// Create a new source position
+    pub fn new(source: impl Into<String>, partition: i32, offset: i64) -> Self {
+        Self {
+            source: source.into(),
+            partition,
+            offset,
+        }
+    }
+}
+
+/// Acknowledges processed envelopes back to their source
+#[async_trait]
+pub trait Acknowledge: Send + Sync + 'static {
+    /// Record the envelope read from `position` as processed
+    async fn ack(&self, position: &SourcePosition) -> ConsumerResult<()>;
+}
+
+/// Handle acknowledging an envelope to the source it came from
+#[derive(Clone, Default)]
+pub struct AckHandle {
+    acknowledger: Option<Arc<dyn Acknowledge>>,
+}
+
+impl AckHandle {
+    /// Create a handle acknowledging through `acknowledger`
+    pub fn new(acknowledger: Arc<dyn Acknowledge>) -> Self {
+        Self {
+            acknowledger: Some(acknowledger),
+        }
+    }
+
+    /// Create a handle for sources without acknowledgement
+    pub fn none() -> Self {
+        Self::default()
+    }
+}
+
+impl Debug for AckHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AckHandle")
+            .field("acknowledged", &self.acknowledger.is_some())
+            .finish()
+    }
+}
+
+/// A single input record and its metadata
+#[derive(Debug, Clone)]
+pub struct Envelope {
+    /// Raw record payload, usually a JSON event
+    pub payload: Vec<u8>,
+    /// Record headers, such as Kafka message headers
+    pub headers: HashMap<String, String>,
+    /// Where the record was read from
+    pub position: SourcePosition,
+    /// When the record's event occurred, if the source reports it
+    pub event_time: Option<DateTime<Utc>>,
+    ack: AckHandle,
+}
+
+impl Envelope {
+    /// Create an envelope that needs no acknowledgement
+    pub fn new(payload: Vec<u8>, position: SourcePosition) -> Self {
+        Self {
+            payload,
+            headers: HashMap::new(),
+            position,
+            event_time: None,
+            ack: AckHandle::none(),
+        }
+    }
+
+    /// Add a header
+    pub fn with_header(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set the time the record's event occurred
+    pub fn with_event_time(mut self, event_time: DateTime<Utc>) -> Self {
+        self.event_time = Some(event_time);
+        self
+    }
+
+    /// Set the handle acknowledging this envelope
+    pub fn with_ack(mut self, ack: AckHandle) -> Self {
+        self.ack = ack;
+        self
+    }
+
+    /// Acknowledge the envelope as processed
+    pub async fn ack(&self) -> ConsumerResult<()> {
+        match &self.ack.acknowledger {
+            Some(acknowledger) => acknowledger.ack(&self.position).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Rebuild the envelope as a Kafka message, for a
+    /// [`MessageProcessor`](crate::consumer::MessageProcessor)
+    ///
+    /// The event time becomes the message's create time.
+    pub fn to_message(&self) -> OwnedMessage {
+        let headers = (!self.headers.is_empty()).then(|| {
+            self.headers
+                .iter()
+                // Kafka header keys are C strings
+                .filter(|(key, _)| !key.contains('\0'))
+                .fold(OwnedHeaders::new(), |headers, (key, value)| {
+                    headers.insert(Header {
+                        key,
+                        value: Some(value.as_str()),
+                    })
+                })
+        });
+        let timestamp = self.event_time.map_or(Timestamp::NotAvailable, |time| {
+            Timestamp::CreateTime(time.timestamp_millis())
+        });
+
+        OwnedMessage::new(
+            Some(self.payload.clone()),
+            None,
+            self.position.source.clone(),
+            timestamp,
+            self.position.partition,
+            self.position.offset,
+            headers,
+        )
+    }
+}
+
+/// A source of envelopes
+#[async_trait]
+pub trait EventSource: Send + 'static {
+    /// Read the next envelope, or `None` once the source is exhausted
+    async fn next_envelope(&mut self) -> ConsumerResult<Option<Envelope>>;
+
+    /// Flush acknowledgements once no envelopes are in flight
+    async fn close(&mut self) -> ConsumerResult<()> {
+        Ok(())
+    }
+
+    /// Stop fetching new records while the pipeline is near its in-flight
+    /// limit, staying connected
+    fn pause(&mut self) {}
+
+    /// Fetch records again after [`pause`](Self::pause)
+    fn resume(&mut self) {}
+
+    /// Get source name for logging
+    fn name(&self) -> &str {
+        "EventSource"
+    }
+}
+
+/// Trait for processing envelopes from any source
+///
+/// Every [`MessageProcessor`](crate::consumer::MessageProcessor) is also an
+/// `EnvelopeProcessor`.
+#[async_trait]
+pub trait EnvelopeProcessor: Send + Sync + 'static {
+    /// Error type for processing
+    type Error: std::error::Error + Send + Sync + Debug;
+
+    /// Process a single envelope
+    async fn process_envelope(&self, envelope: &Envelope) -> Result<(), Self::Error>;
+
+    /// Called when processing fails for good, before the envelope is
+    /// dead-lettered
+    async fn on_envelope_failure(&self, error: &Self::Error, envelope: &Envelope) {
+        error!(
+            "Failed to process record from {} partition {} offset {}: {}",
+            envelope.position.source, envelope.position.partition, envelope.position.offset, error
+        );
+    }
+
+    /// Check if error is retryable
+    fn is_retryable(&self, _error: &Self::Error) -> bool {
+        true
+    }
+}
+
+/// Drives an [`EventSource`] through backpressure, retries and metrics
+///
+/// Envelopes are processed concurrently up to the configured number of
+/// in-flight messages. The source is paused at the pause threshold of that
+/// limit and resumed once enough envelopes finish. Envelopes that still fail
+/// after retries are reported to the processor and sent to the dead letter
+/// queue, if one is set. An envelope is acknowledged once processed or
+/// dead-lettered; one that is neither is left unacknowledged and the pipeline
+/// stops, so the source redelivers it when restarted.
+pub struct SourcePipeline<P: EnvelopeProcessor> {
+    processor: Arc<P>,
+    backpressure: Arc<BackpressureController>,
+    retry_policy: RetryPolicy,
+    metrics: Arc<ConsumerMetrics>,
+    shutdown_state: Arc<ShutdownState>,
+    dlq: Option<Arc<DlqProducer>>,
+}
+
+impl<P: EnvelopeProcessor> SourcePipeline<P> {
+    /// Create a pipeline using the consumer configuration's backpressure and
+    /// retry settings
+    pub fn new(processor: P, config: &ConsumerConfig) -> Self {
+        Self {
+            processor: Arc::new(processor),
+            backpressure: Arc::new(BackpressureController::new(
+                config.max_inflight_messages,
+                config.pause_threshold,
+                config.resume_threshold,
+            )),
+            retry_policy: config.retry_policy.clone(),
+            metrics: Arc::new(ConsumerMetrics::new()),
+            shutdown_state: Arc::new(ShutdownState::new()),
+            dlq: None,
+        }
+    }
+
+    /// Send envelopes that fail for good to a dead letter queue
+    pub fn with_dlq(mut self, dlq: Arc<DlqProducer>) -> Self {
+        self.dlq = Some(dlq);
+        self
+    }
+
+    /// Use a shared shutdown state, so that a
+    /// [`ShutdownCoordinator`](crate::consumer::ShutdownCoordinator) built
+    /// from it stops [`run`](Self::run) gracefully
+    pub fn with_shutdown_state(mut self, shutdown_state: Arc<ShutdownState>) -> Self {
+        self.shutdown_state = shutdown_state;
+        self
+    }
+
+    /// Get the pipeline metrics
+    pub fn metrics(&self) -> Arc<ConsumerMetrics> {
+        self.metrics.clone()
+    }
+
+    /// Process envelopes until the source is exhausted, shutdown begins or an
+    /// envelope can be neither processed nor dead-lettered, then wait for
+    /// in-flight envelopes to finish
+    pub async fn run<S: EventSource>(&self, mut source: S) -> ConsumerResult<()> {
+        info!("Starting pipeline for source {}", source.name());
+        let mut tasks = JoinSet::new();
+        let mut result = Ok(());
+        let unhandled = CancellationToken::new();
+
+        loop {
+            let next = tokio::select! {
+                _ = self.shutdown_state.shutdown_requested() => {
+                    info!("Shutdown requested, stopping source {}", source.name());
+                    break;
+                }
+                _ = unhandled.cancelled() => break,
+                next = source.next_envelope() => next,
+            };
+
+            let envelope = match next {
+                Ok(Some(envelope)) => envelope,
+                Ok(None) => break,
+                Err(e) => {
+                    error!("Source {} failed: {}", source.name(), e);
+                    self.metrics.record_error("source_error");
+                    result = Err(e);
+                    break;
+                }
+            };
+            self.metrics.increment_consumed();
+
+            // Let in-flight envelopes drain before fetching more
+            if self.backpressure.should_pause() {
+                source.pause();
+                while !self.backpressure.should_resume() {
+                    match tasks.join_next().await {
+                        Some(Err(e)) => error!("Processing task failed: {}", e),
+                        Some(Ok(())) => {}
+                        None => break,
+                    }
+                }
+                source.resume();
+            }
+
+            let permit = self.backpressure.acquire().await?;
+            self.shutdown_state.add_inflight_message().await;
+
+            let processor = self.processor.clone();
+            let backpressure = self.backpressure.clone();
+            let metrics = self.metrics.clone();
+            let shutdown_state = self.shutdown_state.clone();
+            let dlq = self.dlq.clone();
+            let unhandled = unhandled.clone();
+            let executor = RetryExecutor::new(self.retry_policy.clone());
+
+            tasks.spawn(async move {
+                let _permit = permit;
+                let start = std::time::Instant::now();
+                backpressure.update_avg_message_size(envelope.payload.len());
+
+                let outcome = executor
+                    .execute_with_predicate(
+                        || processor.process_envelope(&envelope),
+                        |e| processor.is_retryable(e),
+                    )
+                    .await;
+
+                let handled = match outcome {
+                    RetryResult::Success { .. } => {
+                        metrics.increment_processed();
+                        backpressure.record_success(start.elapsed()).await;
+                        true
+                    }
+                    RetryResult::Failed { error, attempts } => {
+                        metrics.increment_failed();
+                        backpressure.record_failure().await;
+                        processor.on_envelope_failure(&error, &envelope).await;
+
+                        match &dlq {
+                            Some(dlq) => match dlq
+                                .send_envelope(&envelope, &error.to_string(), attempts)
+                                .await
+                            {
+                                Ok(()) => {
+                                    metrics.increment_dlq();
+                                    true
+                                }
+                                Err(e) => {
+                                    error!("Failed to send record to DLQ: {}", e);
+                                    metrics.increment_dlq_failures();
+                                    metrics.record_error("dlq_send_failed");
+                                    false
+                                }
+                            },
+                            None => false,
+                        }
+                    }
+                };
+
+                // An unhandled record keeps its source position so it is redelivered
+                if handled {
+                    if let Err(e) = envelope.ack().await {
+                        warn!("Failed to acknowledge record: {}", e);
+                        metrics.record_error("ack_failed");
+                    }
+                } else {
+                    metrics.record_error("record_unhandled");
+                    unhandled.cancel();
+                }
+
+                metrics.record_processing_duration(start.elapsed());
+                shutdown_state.remove_inflight_message().await;
+            });
+
+            // Reap finished tasks so the set does not grow with the input
+            while tasks.try_join_next().is_some() {}
+        }
+
+        while let Some(joined) = tasks.join_next().await {
+            if let Err(e) = joined {
+                error!("Processing task failed: {}", e);
+            }
+        }
+
+        if unhandled.is_cancelled() {
+            error!(
+                "Stopped source {} at a record that was not handled",
+                source.name()
+            );
+            result = result.and(Err(ConsumerError::ProcessingError(
+                "record neither processed nor dead-lettered".to_string(),
+            )));
+        }
+
+        if let Err(e) = source.close().await {
+            error!("Failed to close source {}: {}", source.name(), e);
+            result = result.and(Err(e));
+        }
+
+        info!("Pipeline for source {} finished", source.name());
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::Mutex;
+
+    struct VecSource(VecDeque<Envelope>);
+
+    #[async_trait]
+    impl EventSource for VecSource {
+        async fn next_envelope(&mut self) -> ConsumerResult<Option<Envelope>> {
+            Ok(self.0.pop_front())
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingAcknowledger(Mutex<Vec<i64>>);
+
+    #[async_trait]
+    impl Acknowledge for RecordingAcknowledger {
+        async fn ack(&self, position: &SourcePosition) -> ConsumerResult<()> {
+            self.0.lock().await.push(position.offset);
+            Ok(())
+        }
+    }
+
+    /// Fails each payload "flaky" once and "bad" always
+    #[derive(Default)]
+    struct TestProcessor {
+        attempts: AtomicUsize,
+        failures: Mutex<Vec<i64>>,
+    }
+
+    #[async_trait]
+    impl EnvelopeProcessor for TestProcessor {
+        type Error = ConsumerError;
+
+        async fn process_envelope(&self, envelope: &Envelope) -> Result<(), Self::Error> {
+            let attempt = self.attempts.fetch_add(1, Ordering::Relaxed);
+            match envelope.payload.as_slice() {
+                b"bad" => Err(ConsumerError::ParseError("bad".to_string())),
+                b"flaky" if attempt == 1 => {
+                    Err(ConsumerError::ProcessingError("flaky".to_string()))
+                }
+                _ => Ok(()),
+            }
+        }
+
+        async fn on_envelope_failure(&self, _error: &Self::Error, envelope: &Envelope) {
+            self.failures.lock().await.push(envelope.position.offset);
+        }
+
+        fn is_retryable(&self, error: &Self::Error) -> bool {
+            error.is_retryable()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_acks_processed_envelopes() {
+        let acknowledger = Arc::new(RecordingAcknowledger::default());
+        let ack = AckHandle::new(acknowledger.clone());
+        let envelopes = ["ok", "flaky", "bad"]
+            .iter()
+            .enumerate()
+            .map(|(i, payload)| {
+                Envelope::new(
+                    payload.as_bytes().to_vec(),
+                    SourcePosition::new("test", 0, i as i64),
+                )
+                .with_ack(ack.clone())
+            })
+            .collect();
+
+        let mut config = ConsumerConfig::default();
+        config.max_inflight_messages = 1;
+        config.retry_policy = RetryPolicy::fixed(2, std::time::Duration::from_millis(1));
+
+        let pipeline = SourcePipeline::new(TestProcessor::default(), &config);
+        assert!(pipeline.run(VecSource(envelopes)).await.is_err());
+
+        // Without a DLQ the failed record stays unacknowledged
+        assert_eq!(*acknowledger.0.lock().await, [0, 1]);
+        assert_eq!(*pipeline.processor.failures.lock().await, [2]);
+
+        let metrics = pipeline.metrics();
+        assert_eq!(metrics.messages_consumed.load(Ordering::Relaxed), 3);
+        assert_eq!(metrics.messages_processed.load(Ordering::Relaxed), 2);
+        assert_eq!(metrics.messages_failed.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_lines_hold_back_checkpoint() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("events.jsonl");
+        std::fs::write(&path, "ok\nbad\nok\n").unwrap();
+
+        let source = FileTailSource::open(&path)
+            .await
+            .unwrap()
+            .with_checkpoint(dir.path().join("events.offset"))
+            .await
+            .unwrap()
+            .follow(false);
+
+        let mut config = ConsumerConfig::default();
+        config.retry_policy = RetryPolicy::no_retry();
+        let pipeline = SourcePipeline::new(TestProcessor::default(), &config);
+        assert!(pipeline.run(source).await.is_err());
+
+        // The pipeline stops at the failed line, so a restart reads it again
+        assert_eq!(*pipeline.processor.failures.lock().await, [3]);
+        let checkpoint = std::fs::read_to_string(dir.path().join("events.offset")).unwrap();
+        assert_eq!(checkpoint, "3");
+    }
+
+    /// Records the topic, offset and create time of each message
+    #[derive(Default)]
+    struct TestMessageProcessor(Mutex<Vec<(String, i64, Option<i64>)>>);
+
+    #[async_trait]
+    impl crate::consumer::MessageProcessor for TestMessageProcessor {
+        type Error = ConsumerError;
+
+        async fn process(&self, message: &OwnedMessage) -> Result<(), Self::Error> {
+            use rdkafka::Message;
+            self.0.lock().await.push((
+                message.topic().to_string(),
+                message.offset(),
+                message.timestamp().to_millis(),
+            ));
+            Ok(())
+        }
+
+        async fn on_success(&self, _message: &OwnedMessage) {}
+
+        async fn on_failure(&self, _error: &Self::Error, _message: &OwnedMessage) {}
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_runs_message_processors() {
+        let event_time = DateTime::from_timestamp_millis(1_704_164_645_000).unwrap();
+        let envelopes = VecDeque::from([
+            Envelope::new(b"{}".to_vec(), SourcePosition::new("events", 0, 7)),
+            Envelope::new(b"{}".to_vec(), SourcePosition::new("events", 0, 8))
+                .with_event_time(event_time),
+        ]);
+
+        let mut config = ConsumerConfig::default();
+        config.max_inflight_messages = 1;
+        let pipeline = SourcePipeline::new(TestMessageProcessor::default(), &config);
+        pipeline.run(VecSource(envelopes)).await.unwrap();
+
+        assert_eq!(
+            *pipeline.processor.0.lock().await,
+            [
+                ("events".to_string(), 7, None),
+                ("events".to_string(), 8, Some(1_704_164_645_000))
+            ]
+        );
+    }
+
+    /// Counts how often the pipeline pauses and resumes it
+    struct PausingSource {
+        inner: VecSource,
+        pauses: Arc<AtomicUsize>,
+        resumes: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl EventSource for PausingSource {
+        async fn next_envelope(&mut self) -> ConsumerResult<Option<Envelope>> {
+            self.inner.next_envelope().await
+        }
+
+        fn pause(&mut self) {
+            self.pauses.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn resume(&mut self) {
+            self.resumes.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_pauses_source_at_threshold() {
+        let envelopes = (0..3)
+            .map(|i| Envelope::new(b"ok".to_vec(), SourcePosition::new("test", 0, i)))
+            .collect();
+        let source = PausingSource {
+            inner: VecSource(envelopes),
+            pauses: Arc::default(),
+            resumes: Arc::default(),
+        };
+        let (pauses, resumes) = (source.pauses.clone(), source.resumes.clone());
+
+        // With room for one envelope, the source pauses before each new one
+        let mut config = ConsumerConfig::default();
+        config.max_inflight_messages = 1;
+        let pipeline = SourcePipeline::new(TestProcessor::default(), &config);
+        pipeline.run(source).await.unwrap();
+
+        assert_eq!(pauses.load(Ordering::Relaxed), 3);
+        assert_eq!(resumes.load(Ordering::Relaxed), 3);
+    }
+}
@@ -0,0 +1,831 @@
+//! Syslog listeners (RFC 5424 and RFC 3164) over UDP and TCP as an event source
+
+use super::{Envelope, EventSource, SourcePosition};
+use crate::consumer::error::{ConsumerError, ConsumerResult};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::net::{TcpListener, ToSocketAddrs, UdpSocket};
+use tokio::sync::{mpsc, Semaphore};
+use tokio::task::JoinSet;
+use tracing::{debug, info, warn};
+
+/// Messages buffered between the listeners and the pipeline
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// Open TCP connections per listener
+const DEFAULT_MAX_CONNECTIONS: usize = 256;
+
+/// Time a TCP connection may stay silent before it is closed
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Largest accepted message, matching the maximum UDP payload
+const MAX_MESSAGE_SIZE: usize = 65_535;
+
+const FACILITIES: [&str; 24] = [
+    "kern",
+    "user",
+    "mail",
+    "daemon",
+    "auth",
+    "syslog",
+    "lpr",
+    "news",
+    "uucp",
+    "cron",
+    "authpriv",
+    "ftp",
+    "ntp",
+    "security",
+    "console",
+    "solaris-cron",
+    "local0",
+    "local1",
+    "local2",
+    "local3",
+    "local4",
+    "local5",
+    "local6",
+    "local7",
+];
+
+const SEVERITIES: [&str; 8] = [
+    "emerg", "alert", "crit", "err", "warning", "notice", "info", "debug",
+];
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// A parsed syslog message
+///
+/// Header fields that are absent or NILVALUE (`-`) are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyslogMessage {
+    /// Facility code (0-23)
+    pub facility: u8,
+    /// Severity code (0-7)
+    pub severity: u8,
+    /// Protocol version, only present for RFC 5424 messages
+    pub version: Option<u32>,
+    /// Timestamp as sent, see [`SyslogMessage::event_time`]
+    pub timestamp: Option<String>,
+    /// Originating host
+    pub host: Option<String>,
+    /// Application name, the TAG of RFC 3164 messages
+    pub appname: Option<String>,
+    /// Process ID
+    pub procid: Option<String>,
+    /// Message type
+    pub msgid: Option<String>,
+    /// Structured data parameters keyed by SD-ID
+    pub structured_data: BTreeMap<String, BTreeMap<String, String>>,
+    /// Free-form message
+    pub message: String,
+}
+
+impl SyslogMessage {
+    /// Parse an RFC 5424 or RFC 3164 message
+    ///
+    /// Input without a valid PRI is treated as the message of a `user.notice`
+    /// event, as RFC 3164 asks of relays.
+    pub fn parse(input: &str) -> ConsumerResult<Self> {
+        let input = input.trim_end_matches(['\r', '\n', '\0']);
+
+        let Some((priority, rest)) = parse_priority(input) else {
+            return Ok(Self {
+                facility: 1,
+                severity: 5,
+                message: input.to_string(),
+                ..Self::default()
+            });
+        };
+
+        let mut message = Self {
+            facility: priority / 8,
+            severity: priority % 8,
+            ..Self::default()
+        };
+
+        // RFC 5424 headers start with a version number, RFC 3164 ones never do
+        match rest.split_once(' ') {
+            Some((version, header))
+                if (1..=2).contains(&version.len())
+                    && version.bytes().all(|b| b.is_ascii_digit()) =>
+            {
+                message.version = version.parse().ok();
+                message.parse_rfc5424(header)?;
+            }
+            _ => message.parse_rfc3164(rest),
+        }
+
+        Ok(message)
+    }
+
+    /// Facility keyword, such as `auth` or `local0`
+    pub fn facility_name(&self) -> &'static str {
+        FACILITIES.get(self.facility as usize).unwrap_or(&"unknown")
+    }
+
+    /// Severity keyword, such as `err` or `info`
+    pub fn severity_name(&self) -> &'static str {
+        SEVERITIES.get(self.severity as usize).unwrap_or(&"unknown")
+    }
+
+    /// Convert to a JSON event, omitting absent header fields
+    ///
+    /// `facility` and `severity` hold the keywords and `priority` the numeric
+    /// PRI value; structured data is nested as `structured_data.<SD-ID>.<name>`.
+    pub fn to_json(&self) -> Value {
+        let mut event = Map::new();
+        event.insert("facility".to_string(), json!(self.facility_name()));
+        event.insert("severity".to_string(), json!(self.severity_name()));
+        event.insert(
+            "priority".to_string(),
+            json!(u32::from(self.facility) * 8 + u32::from(self.severity)),
+        );
+
+        let headers = [
+            ("timestamp", &self.timestamp),
+            ("host", &self.host),
+            ("appname", &self.appname),
+            ("procid", &self.procid),
+            ("msgid", &self.msgid),
+        ];
+        if let Some(version) = self.version {
+            event.insert("version".to_string(), json!(version));
+        }
+        for (name, value) in headers {
+            if let Some(value) = value {
+                event.insert(name.to_string(), json!(value));
+            }
+        }
+        if !self.structured_data.is_empty() {
+            event.insert("structured_data".to_string(), json!(self.structured_data));
+        }
+        event.insert("message".to_string(), json!(self.message));
+
+        Value::Object(event)
+    }
+
+    /// Time the event occurred, from an RFC 5424 timestamp
+    ///
+    /// RFC 3164 timestamps carry neither year nor time zone, so they are left
+    /// to the receiving side.
+    pub fn event_time(&self) -> Option<DateTime<Utc>> {
+        self.version?;
+        let timestamp = DateTime::parse_from_rfc3339(self.timestamp.as_deref()?).ok()?;
+        Some(timestamp.with_timezone(&Utc))
+    }
+
+    /// `TIMESTAMP HOSTNAME APP-NAME PROCID MSGID STRUCTURED-DATA [MSG]`
+    fn parse_rfc5424(&mut self, header: &str) -> ConsumerResult<()> {
+        let mut rest = header;
+        let mut fields = [None, None, None, None, None];
+        for field in &mut fields {
+            let (value, remainder) = rest.split_once(' ').ok_or_else(|| {
+                ConsumerError::ParseError(format!("Truncated RFC 5424 header: {}", header))
+            })?;
+            *field = (value != "-").then(|| value.to_string());
+            rest = remainder;
+        }
+        let [timestamp, host, appname, procid, msgid] = fields;
+        self.timestamp = timestamp;
+        self.host = host;
+        self.appname = appname;
+        self.procid = procid;
+        self.msgid = msgid;
+
+        let rest = match rest.strip_prefix('-') {
+            Some(rest) => rest,
+            None => {
+                let (structured_data, rest) = parse_structured_data(rest)?;
+                self.structured_data = structured_data;
+                rest
+            }
+        };
+
+        if let Some(message) = rest.strip_prefix(' ') {
+            self.message = message.trim_start_matches('\u{feff}').to_string();
+        } else if !rest.is_empty() {
+            return Err(ConsumerError::ParseError(format!(
+                "Unexpected data after RFC 5424 structured data: {}",
+                rest
+            )));
+        }
+        Ok(())
+    }
+
+    /// `[TIMESTAMP HOSTNAME] [TAG[PID]:] MSG`
+    fn parse_rfc3164(&mut self, input: &str) {
+        let mut rest = input;
+
+        if let Some(timestamp) = rest.get(..15).filter(|t| is_rfc3164_timestamp(t)) {
+            self.timestamp = Some(timestamp.to_string());
+            rest = rest[15..].trim_start_matches(' ');
+
+            // Local senders omit the hostname, so the TAG follows the timestamp
+            if let Some((host, remainder)) = rest.split_once(' ') {
+                if !host.ends_with(':') && !host.contains('[') {
+                    self.host = Some(host.to_string());
+                    rest = remainder;
+                }
+            }
+        }
+
+        if let Some((tag, message)) = rest.split_once(' ') {
+            if let Some(tag) = tag.strip_suffix(':').filter(|tag| !tag.is_empty()) {
+                match tag.strip_suffix(']').and_then(|tag| tag.split_once('[')) {
+                    Some((appname, procid)) => {
+                        self.appname = Some(appname.to_string());
+                        self.procid = Some(procid.to_string());
+                    }
+                    None => self.appname = Some(tag.to_string()),
+                }
+                rest = message;
+            }
+        }
+
+        self.message = rest.to_string();
+    }
+}
+
+/// Split `<PRI>` from the rest of the message
+fn parse_priority(input: &str) -> Option<(u8, &str)> {
+    let (priority, rest) = input.strip_prefix('<')?.split_once('>')?;
+    if !(1..=3).contains(&priority.len()) || !priority.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let priority: u8 = priority.parse().ok()?;
+    (priority < 192).then_some((priority, rest))
+}
+
+/// `Mmm dd hh:mm:ss`, with the day padded by a space
+fn is_rfc3164_timestamp(timestamp: &str) -> bool {
+    let bytes = timestamp.as_bytes();
+    timestamp
+        .get(..3)
+        .is_some_and(|month| MONTHS.contains(&month))
+        && bytes[3] == b' '
+        && bytes[6] == b' '
+        && bytes[9] == b':'
+        && bytes[12] == b':'
+        && [4, 5, 7, 8, 10, 11, 13, 14]
+            .iter()
+            .all(|&i| bytes[i].is_ascii_digit() || (i == 4 && bytes[i] == b' '))
+}
+
+type StructuredData = BTreeMap<String, BTreeMap<String, String>>;
+
+/// Parse `[SD-ID PARAM="VALUE" ...]...`, returning the remaining input
+fn parse_structured_data(input: &str) -> ConsumerResult<(StructuredData, &str)> {
+    let malformed = || ConsumerError::ParseError(format!("Malformed structured data: {}", input));
+    let mut data = StructuredData::new();
+    let mut rest = input;
+
+    if !rest.starts_with('[') {
+        return Err(malformed());
+    }
+
+    while let Some(element) = rest.strip_prefix('[') {
+        let end = element.find([' ', ']']).ok_or_else(malformed)?;
+        let params = data.entry(element[..end].to_string()).or_default();
+        rest = &element[end..];
+
+        loop {
+            if let Some(remainder) = rest.strip_prefix(']') {
+                rest = remainder;
+                break;
+            }
+            let (name, remainder) = rest
+                .strip_prefix(' ')
+                .and_then(|param| param.split_once("=\""))
+                .ok_or_else(malformed)?;
+            let (value, remainder) = parse_param_value(remainder).ok_or_else(malformed)?;
+            params.insert(name.to_string(), value);
+            rest = remainder;
+        }
+    }
+
+    Ok((data, rest))
+}
+
+/// Read a PARAM-VALUE up to its closing quote, unescaping `\"`, `\\` and `\]`
+fn parse_param_value(input: &str) -> Option<(String, &str)> {
+    let mut value = String::new();
+    let mut chars = input.char_indices();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, &input[i + 1..])),
+            '\\' => {
+                let (_, escaped) = chars.next()?;
+                if !matches!(escaped, '"' | '\\' | ']') {
+                    value.push('\\');
+                }
+                value.push(escaped);
+            }
+            _ => value.push(c),
+        }
+    }
+    None
+}
+
+/// Source receiving syslog messages on UDP and TCP listeners
+///
+/// Each message becomes a JSON envelope (see [`SyslogMessage::to_json`])
+/// with `transport` and `peer` headers. TCP accepts both octet-counted and
+/// newline-delimited framing (RFC 6587). Listeners wait for room in a bounded
+/// buffer, so when the pipeline applies backpressure TCP senders are slowed
+/// through flow control and UDP datagrams queue in, and eventually overflow,
+/// the socket buffer. Messages that fail to parse are logged and dropped.
+/// RFC 5424 timestamps become the envelope's event time.
+///
+/// Each TCP listener serves a limited number of connections at a time (see
+/// [`SyslogSource::with_max_connections`]); further connections wait in the
+/// listen backlog until one closes. Connections that send nothing within the
+/// idle timeout (see [`SyslogSource::with_idle_timeout`]) are closed to free
+/// their slot.
+pub struct SyslogSource {
+    sender: mpsc::Sender<Envelope>,
+    receiver: mpsc::Receiver<Envelope>,
+    listeners: JoinSet<()>,
+    sequence: Arc<AtomicI64>,
+    max_connections: usize,
+    idle_timeout: Duration,
+}
+
+impl Default for SyslogSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SyslogSource {
+    /// Create a source without listeners
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Create a source buffering up to `capacity` received messages
+    pub fn with_capacity(capacity: usize) -> Self {
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        Self {
+            sender,
+            receiver,
+            listeners: JoinSet::new(),
+            sequence: Arc::new(AtomicI64::new(0)),
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+        }
+    }
+
+    /// Limit the connections each TCP listener started afterwards serves at once
+    pub fn with_max_connections(mut self, max_connections: usize) -> Self {
+        self.max_connections = max_connections.max(1);
+        self
+    }
+
+    /// Close TCP connections of listeners started afterwards once they wait
+    /// longer than `idle_timeout` for a message
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Receive datagrams on `addr`, returning the bound address
+    pub async fn listen_udp(&mut self, addr: impl ToSocketAddrs) -> ConsumerResult<SocketAddr> {
+        let socket = UdpSocket::bind(addr).await.map_err(|e| {
+            ConsumerError::ConnectionError(format!("Failed to bind syslog UDP listener: {}", e))
+        })?;
+        let local_addr = socket.local_addr()?;
+        info!("Listening for syslog over UDP on {}", local_addr);
+
+        let forwarder = self.forwarder(format!("udp://{}", local_addr));
+        self.listeners.spawn(async move {
+            let mut buffer = vec![0u8; MAX_MESSAGE_SIZE];
+            loop {
+                match socket.recv_from(&mut buffer).await {
+                    Ok((len, peer)) => {
+                        if !forwarder.forward(&buffer[..len], peer).await {
+                            break;
+                        }
+                    }
+                    Err(e) => warn!("Syslog UDP receive failed: {}", e),
+                }
+            }
+        });
+
+        Ok(local_addr)
+    }
+
+    /// Accept connections on `addr`, returning the bound address
+    pub async fn listen_tcp(&mut self, addr: impl ToSocketAddrs) -> ConsumerResult<SocketAddr> {
+        let listener = TcpListener::bind(addr).await.map_err(|e| {
+            ConsumerError::ConnectionError(format!("Failed to bind syslog TCP listener: {}", e))
+        })?;
+        let local_addr = listener.local_addr()?;
+        info!("Listening for syslog over TCP on {}", local_addr);
+
+        let forwarder = self.forwarder(format!("tcp://{}", local_addr));
+        let slots = Arc::new(Semaphore::new(self.max_connections));
+        let idle_timeout = self.idle_timeout;
+        self.listeners.spawn(async move {
+            // Dropped with the listener task, closing its connections
+            let mut connections = JoinSet::new();
+            loop {
+                // Leave connections beyond the limit in the backlog
+                let Ok(slot) = slots.clone().acquire_owned().await else {
+                    break;
+                };
+                while connections.try_join_next().is_some() {}
+
+                let (stream, peer) = match listener.accept().await {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        warn!("Syslog TCP accept failed: {}", e);
+                        continue;
+                    }
+                };
+                debug!("Accepted syslog connection from {}", peer);
+
+                let forwarder = forwarder.clone();
+                connections.spawn(async move {
+                    let _slot = slot;
+                    let mut reader = BufReader::new(stream);
+                    let mut frame = Vec::new();
+                    loop {
+                        let read = read_frame(&mut reader, &mut frame);
+                        match tokio::time::timeout(idle_timeout, read).await {
+                            Ok(Ok(true)) => {
+                                if !forwarder.forward(&frame, peer).await {
+                                    break;
+                                }
+                            }
+                            Ok(Ok(false)) => break,
+                            Ok(Err(e)) => {
+                                warn!("Closing syslog connection from {}: {}", peer, e);
+                                break;
+                            }
+                            Err(_) => {
+                                debug!("Closing idle syslog connection from {}", peer);
+                                break;
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        Ok(local_addr)
+    }
+
+    fn forwarder(&self, source: String) -> Forwarder {
+        Forwarder {
+            source: Arc::from(source),
+            sender: self.sender.clone(),
+            sequence: self.sequence.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Forwarder {
+    source: Arc<str>,
+    sender: mpsc::Sender<Envelope>,
+    sequence: Arc<AtomicI64>,
+}
+
+impl Forwarder {
+    /// Parse and queue a message, returning false once the source is gone
+    async fn forward(&self, raw: &[u8], peer: SocketAddr) -> bool {
+        let text = String::from_utf8_lossy(raw);
+        if text.trim().is_empty() {
+            return true;
+        }
+
+        let message = match SyslogMessage::parse(&text) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("Dropping syslog message from {}: {}", peer, e);
+                return true;
+            }
+        };
+
+        let payload = match serde_json::to_vec(&message.to_json()) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("Failed to serialize syslog message from {}: {}", peer, e);
+                return true;
+            }
+        };
+
+        let offset = self.sequence.fetch_add(1, Ordering::Relaxed);
+        let (transport, _) = self.source.split_once(':').unwrap_or_default();
+        let mut envelope = Envelope::new(payload, SourcePosition::new(&*self.source, 0, offset))
+            .with_header("transport", transport)
+            .with_header("peer", peer.to_string());
+        if let Some(event_time) = message.event_time() {
+            envelope = envelope.with_event_time(event_time);
+        }
+
+        self.sender.send(envelope).await.is_ok()
+    }
+}
+
+/// Read one RFC 6587 frame into `frame`, returning false at end of stream
+///
+/// Lines longer than [`MAX_MESSAGE_SIZE`] are truncated and the rest of the
+/// line is discarded.
+async fn read_frame<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    frame: &mut Vec<u8>,
+) -> std::io::Result<bool> {
+    frame.clear();
+    let first = match reader.fill_buf().await?.first() {
+        Some(&first) => first,
+        None => return Ok(false),
+    };
+
+    if first.is_ascii_digit() {
+        // Octet counting: MSG-LEN SP SYSLOG-MSG
+        let mut length = Vec::new();
+        (&mut *reader).take(6).read_until(b' ', &mut length).await?;
+        let length = std::str::from_utf8(&length)
+            .ok()
+            .and_then(|length| length.strip_suffix(' '))
+            .and_then(|length| length.parse::<usize>().ok())
+            .filter(|&length| length <= MAX_MESSAGE_SIZE)
+            .ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid frame length")
+            })?;
+        frame.resize(length, 0);
+        reader.read_exact(frame).await?;
+    } else {
+        // Non-transparent framing: messages end with LF
+        (&mut *reader)
+            .take(MAX_MESSAGE_SIZE as u64)
+            .read_until(b'\n', frame)
+            .await?;
+        if frame.len() == MAX_MESSAGE_SIZE && frame.last() != Some(&b'\n') {
+            skip_line(reader).await?;
+        }
+    }
+
+    Ok(true)
+}
+
+/// Discard input up to and including the next LF or the end of stream
+async fn skip_line<R: AsyncBufRead + Unpin>(reader: &mut R) -> std::io::Result<()> {
+    loop {
+        let buffer = reader.fill_buf().await?;
+        if buffer.is_empty() {
+            return Ok(());
+        }
+        match buffer.iter().position(|&byte| byte == b'\n') {
+            Some(end) => {
+                reader.consume(end + 1);
+                return Ok(());
+            }
+            None => {
+                let len = buffer.len();
+                reader.consume(len);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl EventSource for SyslogSource {
+    async fn next_envelope(&mut self) -> ConsumerResult<Option<Envelope>> {
+        // The source keeps a sender, so the channel stays open until shutdown
+        Ok(self.receiver.recv().await)
+    }
+
+    async fn close(&mut self) -> ConsumerResult<()> {
+        self.listeners.abort_all();
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "SyslogSource"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpStream;
+
+    #[test]
+    fn test_parse_rfc5424() {
+        let message = SyslogMessage::parse(
+            "<165>1 2003-10-11T22:14:15.003Z mymachine.example.com evntslog - ID47 \
+             [exampleSDID@32473 iut=\"3\" eventSource=\"Application\" eventID=\"1011\"]\
+             [examplePriority@32473 class=\"high \\\"a\\]\"] \u{feff}An application event",
+        )
+        .unwrap();
+
+        assert_eq!(message.facility_name(), "local4");
+        assert_eq!(message.severity_name(), "notice");
+        assert_eq!(message.version, Some(1));
+        assert_eq!(message.host.as_deref(), Some("mymachine.example.com"));
+        assert_eq!(message.appname.as_deref(), Some("evntslog"));
+        assert_eq!(message.procid, None);
+        assert_eq!(message.msgid.as_deref(), Some("ID47"));
+        assert_eq!(message.message, "An application event");
+        assert_eq!(
+            message.event_time(),
+            DateTime::parse_from_rfc3339("2003-10-11T22:14:15.003Z")
+                .ok()
+                .map(|time| time.with_timezone(&Utc))
+        );
+
+        let json = message.to_json();
+        assert_eq!(json["priority"], 165);
+        assert_eq!(
+            json["structured_data"]["exampleSDID@32473"]["eventID"],
+            "1011"
+        );
+        assert_eq!(
+            json["structured_data"]["examplePriority@32473"]["class"],
+            "high \"a]"
+        );
+        assert!(json.get("procid").is_none());
+
+        let message =
+            SyslogMessage::parse("<34>1 2003-10-11T22:14:15.003Z mymachine su - ID47 -").unwrap();
+        assert_eq!(message.severity_name(), "crit");
+        assert!(message.structured_data.is_empty());
+        assert_eq!(message.message, "");
+
+        assert!(SyslogMessage::parse("<34>1 2003-10-11T22:14:15.003Z host").is_err());
+        assert!(SyslogMessage::parse("<34>1 - - - - - [id a=\"1] msg").is_err());
+    }
+
+    #[test]
+    fn test_parse_rfc3164() {
+        let message = SyslogMessage::parse(
+            "<34>Oct 11 22:14:15 mymachine su: 'su root' failed for lonvick on /dev/pts/8\n",
+        )
+        .unwrap();
+        assert_eq!(message.facility_name(), "auth");
+        assert_eq!(message.severity_name(), "crit");
+        assert_eq!(message.version, None);
+        assert_eq!(message.timestamp.as_deref(), Some("Oct 11 22:14:15"));
+        assert_eq!(message.event_time(), None);
+        assert_eq!(message.host.as_deref(), Some("mymachine"));
+        assert_eq!(message.appname.as_deref(), Some("su"));
+        assert_eq!(
+            message.message,
+            "'su root' failed for lonvick on /dev/pts/8"
+        );
+
+        let message =
+            SyslogMessage::parse("<38>Feb  5 17:32:18 sshd[4123]: Accepted publickey for root")
+                .unwrap();
+        assert_eq!(message.timestamp.as_deref(), Some("Feb  5 17:32:18"));
+        assert_eq!(message.host, None);
+        assert_eq!(message.appname.as_deref(), Some("sshd"));
+        assert_eq!(message.procid.as_deref(), Some("4123"));
+        assert_eq!(message.message, "Accepted publickey for root");
+
+        let message = SyslogMessage::parse("no priority here").unwrap();
+        assert_eq!(message.facility_name(), "user");
+        assert_eq!(message.severity_name(), "notice");
+        assert_eq!(message.message, "no priority here");
+    }
+
+    #[tokio::test]
+    async fn test_udp_and_tcp_listeners() {
+        let mut source = SyslogSource::new();
+        let udp_addr = source.listen_udp("127.0.0.1:0").await.unwrap();
+        let tcp_addr = source.listen_tcp("127.0.0.1:0").await.unwrap();
+
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        socket
+            .send_to(b"<13>1 - host1 app - - - over udp", udp_addr)
+            .await
+            .unwrap();
+        let envelope = source.next_envelope().await.unwrap().unwrap();
+        let event: Value = serde_json::from_slice(&envelope.payload).unwrap();
+        assert_eq!(event["host"], "host1");
+        assert_eq!(event["message"], "over udp");
+        assert_eq!(envelope.headers["transport"], "udp");
+        assert_eq!(envelope.event_time, None);
+
+        let framed = "<13>1 2003-10-11T22:14:15+02:00 host2 app - - - octet counted";
+        let mut stream = TcpStream::connect(tcp_addr).await.unwrap();
+        stream
+            .write_all(format!("{} {}", framed.len(), framed).as_bytes())
+            .await
+            .unwrap();
+        stream
+            .write_all(b"<13>Oct 11 22:14:15 host3 cron: line framed\n")
+            .await
+            .unwrap();
+        stream.shutdown().await.unwrap();
+
+        let mut events = Vec::new();
+        let mut event_times = Vec::new();
+        for _ in 0..2 {
+            let envelope = source.next_envelope().await.unwrap().unwrap();
+            assert_eq!(envelope.headers["transport"], "tcp");
+            events.push(serde_json::from_slice::<Value>(&envelope.payload).unwrap());
+            event_times.push(envelope.event_time);
+        }
+        assert_eq!(events[0]["host"], "host2");
+        assert_eq!(events[0]["message"], "octet counted");
+        assert_eq!(events[1]["host"], "host3");
+        assert_eq!(events[1]["appname"], "cron");
+        assert_eq!(events[1]["message"], "line framed");
+        assert_eq!(
+            event_times[0].map(|time| time.to_rfc3339()),
+            Some("2003-10-11T20:14:15+00:00".to_string())
+        );
+        assert_eq!(event_times[1], None);
+
+        source.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tcp_connection_limit() {
+        let mut source = SyslogSource::new().with_max_connections(1);
+        let tcp_addr = source.listen_tcp("127.0.0.1:0").await.unwrap();
+
+        let mut first = TcpStream::connect(tcp_addr).await.unwrap();
+        first
+            .write_all(b"<13>1 - host1 app - - - first\n")
+            .await
+            .unwrap();
+        let envelope = source.next_envelope().await.unwrap().unwrap();
+        assert_eq!(envelope.headers["transport"], "tcp");
+
+        // The second connection waits in the backlog while the first is open
+        let mut second = TcpStream::connect(tcp_addr).await.unwrap();
+        second
+            .write_all(b"<13>1 - host2 app - - - second\n")
+            .await
+            .unwrap();
+        let waiting =
+            tokio::time::timeout(Duration::from_millis(100), source.next_envelope()).await;
+        assert!(waiting.is_err());
+
+        first.shutdown().await.unwrap();
+        let envelope = tokio::time::timeout(Duration::from_secs(5), source.next_envelope())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        let event: Value = serde_json::from_slice(&envelope.payload).unwrap();
+        assert_eq!(event["host"], "host2");
+
+        source.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tcp_idle_timeout_frees_slot() {
+        let mut source = SyslogSource::new()
+            .with_max_connections(1)
+            .with_idle_timeout(Duration::from_millis(100));
+        let tcp_addr = source.listen_tcp("127.0.0.1:0").await.unwrap();
+
+        // The silent connection is closed, letting the second one through
+        let _idle = TcpStream::connect(tcp_addr).await.unwrap();
+        let mut second = TcpStream::connect(tcp_addr).await.unwrap();
+        second
+            .write_all(b"<13>1 - host2 app - - - second\n")
+            .await
+            .unwrap();
+        let envelope = tokio::time::timeout(Duration::from_secs(5), source.next_envelope())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        let event: Value = serde_json::from_slice(&envelope.payload).unwrap();
+        assert_eq!(event["host"], "host2");
+
+        source.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_read_frame_discards_rest_of_long_line() {
+        let mut input = vec![b'a'; MAX_MESSAGE_SIZE + 100];
+        input.extend_from_slice(b"\n<13>1 - host1 app - - - next\n");
+        let mut reader = input.as_slice();
+
+        let mut frame = Vec::new();
+        assert!(read_frame(&mut reader, &mut frame).await.unwrap());
+        assert_eq!(frame.len(), MAX_MESSAGE_SIZE);
+        assert!(read_frame(&mut reader, &mut frame).await.unwrap());
+        assert_eq!(frame, b"<13>1 - host1 app - - - next\n");
+        assert!(!read_frame(&mut reader, &mut frame).await.unwrap());
+    }
+}
@@ -0,0 +1,670 @@
+//! Sigma v2 correlation over base-rule matches
+//!
+//! A [`CorrelationEngine`] is built from the correlation rules of a [`RuleSet`]
+//! and fed each event together with the [`RuleSetResult`] it produced. It keeps
+//! per-group windows of base-rule matches and emits a [`CorrelationAlert`] once
+//! a correlation's condition holds within its `timespan`. A
+//! [`SigmaEngine`](crate::SigmaEngine) builds one for its rules and feeds it
+//! through [`SigmaEngine::correlate`](crate::SigmaEngine::correlate).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sigma_rs::correlation::CorrelationEngine;
+//! use sigma_rs::{DynamicEvent, RuleSet};
+//! use serde_json::json;
+//!
+//! # async fn example(ruleset: RuleSet) -> anyhow::Result<()> {
+//! let engine = CorrelationEngine::new(&ruleset)?;
+//!
+//! let event = DynamicEvent::new(json!({"EventID": 4625, "SourceIp": "10.0.0.1"}));
+//! let result = ruleset.evaluate(&event).await?;
+//! for alert in engine.process(&event, &result) {
+//!     println!("{} fired for {:?}", alert.rule_title, alert.group);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::aggregation::evaluator::value_to_cow;
+use crate::error::{Result, SigmaError};
+use crate::event::{Event, Value};
+use crate::rule::{CorrelationCondition, CorrelationType, Rule};
+use crate::ruleset::{RuleSet, RuleSetResult};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use tracing::error;
+
+/// Maximum number of groups tracked per correlation before the oldest are evicted
+const MAX_GROUPS_PER_CORRELATION: usize = 10_000;
+
+/// Alert raised when a correlation rule's condition is satisfied
+#[derive(Debug, Clone)]
+pub struct CorrelationAlert {
+    /// The correlation rule ID
+    pub rule_id: String,
+    /// The correlation rule title
+    pub rule_title: String,
+    /// The correlation type that fired
+    pub correlation_type: CorrelationType,
+    /// Values of the `group-by` fields for the triggering group
+    pub group: Vec<(String, String)>,
+    /// The correlated value (event count, distinct values or matched rules)
+    pub value: f64,
+    /// Timestamp of the event that completed the correlation
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Correlates base-rule matches according to a set of correlation rules
+#[derive(Debug, Default)]
+pub struct CorrelationEngine {
+    correlations: Vec<CompiledCorrelation>,
+}
+
+#[derive(Debug)]
+struct CompiledCorrelation {
+    rule: Arc<Rule>,
+    correlation_type: CorrelationType,
+    /// Base rule IDs, in the order the correlation lists them
+    rule_ids: Vec<String>,
+    group_by: Vec<String>,
+    window: chrono::Duration,
+    condition: CorrelationCondition,
+    /// Hits per group, in event-time order
+    groups: Mutex<HashMap<String, VecDeque<Hit>>>,
+}
+
+/// A single event matching one or more of a correlation's base rules
+#[derive(Debug)]
+struct Hit {
+    timestamp: DateTime<Utc>,
+    /// Indices into `rule_ids` of the base rules the event matched
+    rules: Vec<usize>,
+    /// Value of the `value_count` field, if any
+    value: Option<String>,
+}
+
+impl CorrelationEngine {
+    /// Build an engine for the correlation rules in a ruleset
+    ///
+    /// Rule references are resolved against the ruleset's base rules by name or ID.
+    /// Correlations that fail to compile, e.g. because they reference a missing
+    /// rule, are logged and skipped.
+    pub fn new(ruleset: &RuleSet) -> Result<Self> {
+        Self::build(ruleset, false)
+    }
+
+    /// Build an engine like [`new`](Self::new), failing on the first
+    /// correlation that does not compile
+    pub fn new_strict(ruleset: &RuleSet) -> Result<Self> {
+        Self::build(ruleset, true)
+    }
+
+    fn build(ruleset: &RuleSet, fail_on_error: bool) -> Result<Self> {
+        let mut correlations = Vec::with_capacity(ruleset.correlation_rules().len());
+        for rule in ruleset.correlation_rules() {
+            match CompiledCorrelation::new(Arc::clone(rule), ruleset) {
+                Ok(correlation) => correlations.push(correlation),
+                Err(e) => {
+                    error!("Failed to load correlation rule {}: {}", rule.id, e);
+                    if fail_on_error {
+                        return Err(e);
+                    }
+                }
+            }
+        }
+
+        Ok(Self { correlations })
+    }
+
+    /// Get the number of correlation rules in the engine
+    pub fn len(&self) -> usize {
+        self.correlations.len()
+    }
+
+    /// Check if the engine has no correlation rules
+    pub fn is_empty(&self) -> bool {
+        self.correlations.is_empty()
+    }
+
+    /// Record the base-rule matches of an event and return any correlations it completes
+    pub fn process(&self, event: &dyn Event, result: &RuleSetResult) -> Vec<CorrelationAlert> {
+        let matched: HashSet<&str> = result
+            .matches
+            .iter()
+            .filter(|m| m.matched)
+            .map(|m| m.rule_id.as_str())
+            .collect();
+
+        if matched.is_empty() {
+            return Vec::new();
+        }
+
+        let timestamp = event
+            .event_time()
+            .or_else(|| DateTime::from_timestamp(event.timestamp(), 0))
+            .unwrap_or_else(Utc::now);
+
+        self.correlations
+            .iter()
+            .filter_map(|correlation| correlation.observe(event, &matched, timestamp))
+            .collect()
+    }
+}
+
+impl CompiledCorrelation {
+    fn new(rule: Arc<Rule>, ruleset: &RuleSet) -> Result<Self> {
+        let correlation = rule.correlation.as_ref().ok_or_else(|| {
+            SigmaError::InvalidRule(format!("Rule '{}' is not a correlation rule", rule.id))
+        })?;
+
+        let rule_ids = correlation
+            .rules
+            .iter()
+            .map(|reference| {
+                ruleset
+                    .resolve_reference(reference)
+                    .map(str::to_string)
+                    .ok_or_else(|| {
+                        SigmaError::RuleNotFound(format!(
+                            "'{}' referenced by correlation '{}'",
+                            reference, rule.id
+                        ))
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let window = chrono::Duration::from_std(correlation.window()?).map_err(|e| {
+            SigmaError::InvalidRule(format!("Correlation timespan out of range: {}", e))
+        })?;
+
+        Ok(Self {
+            correlation_type: correlation.correlation_type,
+            group_by: correlation.group_by.clone(),
+            condition: correlation.effective_condition(),
+            rule_ids,
+            window,
+            groups: Mutex::new(HashMap::new()),
+            rule,
+        })
+    }
+
+    fn observe(
+        &self,
+        event: &dyn Event,
+        matched: &HashSet<&str>,
+        timestamp: DateTime<Utc>,
+    ) -> Option<CorrelationAlert> {
+        let rules: Vec<usize> = self
+            .rule_ids
+            .iter()
+            .enumerate()
+            .filter(|(_, id)| matched.contains(id.as_str()))
+            .map(|(index, _)| index)
+            .collect();
+
+        if rules.is_empty() {
+            return None;
+        }
+
+        // Events lacking a group-by field belong to no group
+        let group: Vec<(String, String)> = self
+            .group_by
+            .iter()
+            .map(|field| match event.select(field) {
+                (Some(Value::Null), _) | (None, _) => None,
+                (Some(value), _) => Some((field.clone(), value_to_cow(&value).into_owned())),
+            })
+            .collect::<Option<_>>()?;
+        let group_key = group
+            .iter()
+            .map(|(_, value)| value.as_str())
+            .collect::<Vec<_>>()
+            .join("\u{1f}");
+
+        let value = self
+            .condition
+            .field
+            .as_ref()
+            .and_then(|field| match event.select(field) {
+                (Some(value), _) => Some(value_to_cow(&value).into_owned()),
+                _ => None,
+            });
+
+        let mut groups = self.groups.lock();
+
+        if groups.len() >= MAX_GROUPS_PER_CORRELATION && !groups.contains_key(&group_key) {
+            let stale_before = timestamp - self.window;
+            groups.retain(|_, hits| hits.iter().any(|hit| hit.timestamp >= stale_before));
+            // Every group is live, so drop the one whose latest match is oldest
+            if groups.len() >= MAX_GROUPS_PER_CORRELATION {
+                let oldest = groups
+                    .iter()
+                    .min_by_key(|(_, hits)| hits.iter().map(|hit| hit.timestamp).max())
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    groups.remove(&oldest);
+                }
+            }
+        }
+
+        let hits = groups.entry(group_key.clone()).or_default();
+
+        // The window ends at the newest event time seen for the group, so a
+        // late event neither prunes newer hits nor counts once outside it
+        let watermark = hits
+            .back()
+            .map_or(timestamp, |latest| latest.timestamp.max(timestamp));
+        let window_start = watermark - self.window;
+        if timestamp < window_start {
+            return None;
+        }
+
+        // Event time may arrive out of order, so keep the hits sorted by it
+        let position = hits.partition_point(|hit| hit.timestamp <= timestamp);
+        hits.insert(
+            position,
+            Hit {
+                timestamp,
+                rules,
+                value,
+            },
+        );
+        while hits.front().is_some_and(|hit| hit.timestamp < window_start) {
+            hits.pop_front();
+        }
+
+        let current = self.correlated_value(hits);
+        if !self.condition.evaluate(current) {
+            return None;
+        }
+
+        // Start a fresh window so each alert covers a new set of matches
+        groups.remove(&group_key);
+
+        Some(CorrelationAlert {
+            rule_id: self.rule.id.clone(),
+            rule_title: self.rule.title.clone(),
+            correlation_type: self.correlation_type,
+            group,
+            value: current,
+            timestamp,
+        })
+    }
+
+    fn correlated_value(&self, hits: &VecDeque<Hit>) -> f64 {
+        match self.correlation_type {
+            CorrelationType::EventCount => hits.len() as f64,
+            CorrelationType::ValueCount => hits
+                .iter()
+                .filter_map(|hit| hit.value.as_deref())
+                .collect::<HashSet<_>>()
+                .len() as f64,
+            CorrelationType::Temporal => hits
+                .iter()
+                .flat_map(|hit| hit.rules.iter())
+                .collect::<HashSet<_>>()
+                .len() as f64,
+            CorrelationType::TemporalOrdered => {
+                // Longest prefix of the rule list matched in time order
+                let mut next = 0;
+                for hit in hits {
+                    while next < self.rule_ids.len() && hit.rules.contains(&next) {
+                        next += 1;
+                    }
+                }
+                next as f64
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::DynamicEvent;
+    use crate::rule::rule_from_yaml;
+    use serde_json::json;
+
+    const FAILED_LOGON: &str = r#"
+title: Failed Logon
+id: 12345678-1234-1234-1234-123456789101
+name: failed_logon
+detection:
+    selection:
+        EventID: 4625
+    condition: selection
+"#;
+
+    const SUCCESSFUL_LOGON: &str = r#"
+title: Successful Logon
+id: 12345678-1234-1234-1234-123456789102
+name: successful_logon
+detection:
+    selection:
+        EventID: 4624
+    condition: selection
+"#;
+
+    const BASE_TIME: i64 = 1_700_000_000;
+
+    async fn engine_with(correlation: &str) -> (RuleSet, CorrelationEngine) {
+        let mut ruleset = RuleSet::new();
+        for yaml in [FAILED_LOGON, SUCCESSFUL_LOGON, correlation] {
+            ruleset
+                .add_rule(rule_from_yaml(yaml.as_bytes()).unwrap())
+                .await
+                .unwrap();
+        }
+        let engine = CorrelationEngine::new(&ruleset).unwrap();
+        (ruleset, engine)
+    }
+
+    async fn feed(
+        ruleset: &RuleSet,
+        engine: &CorrelationEngine,
+        offset_secs: i64,
+        data: serde_json::Value,
+    ) -> Vec<CorrelationAlert> {
+        let event = DynamicEvent::new(data).with_timestamp(BASE_TIME + offset_secs);
+        let result = ruleset.evaluate(&event).await.unwrap();
+        engine.process(&event, &result)
+    }
+
+    #[tokio::test]
+    async fn test_event_count_correlation() {
+        let (ruleset, engine) = engine_with(
+            r#"
+title: Brute Force
+id: 12345678-1234-1234-1234-123456789110
+correlation:
+    type: event_count
+    rules: [failed_logon]
+    group-by: [SourceIp]
+    timespan: 1m
+    condition:
+        gte: 3
+"#,
+        )
+        .await;
+        assert_eq!(ruleset.correlation_rules().len(), 1);
+        assert_eq!(engine.len(), 1);
+
+        let failed = |ip: &str| json!({"EventID": 4625, "SourceIp": ip});
+
+        assert!(feed(&ruleset, &engine, 0, failed("10.0.0.1"))
+            .await
+            .is_empty());
+        assert!(feed(&ruleset, &engine, 10, failed("10.0.0.1"))
+            .await
+            .is_empty());
+        // Other groups and non-matching events don't count
+        assert!(feed(&ruleset, &engine, 15, failed("10.0.0.2"))
+            .await
+            .is_empty());
+        let success = json!({"EventID": 4624, "SourceIp": "10.0.0.1"});
+        assert!(feed(&ruleset, &engine, 16, success).await.is_empty());
+
+        let alerts = feed(&ruleset, &engine, 20, failed("10.0.0.1")).await;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].rule_title, "Brute Force");
+        assert_eq!(alerts[0].correlation_type, CorrelationType::EventCount);
+        assert_eq!(
+            alerts[0].group,
+            vec![("SourceIp".to_string(), "10.0.0.1".to_string())]
+        );
+        assert_eq!(alerts[0].value, 3.0);
+
+        // Matches spread wider than the timespan never fire
+        assert!(feed(&ruleset, &engine, 100, failed("10.0.0.3"))
+            .await
+            .is_empty());
+        assert!(feed(&ruleset, &engine, 170, failed("10.0.0.3"))
+            .await
+            .is_empty());
+        assert!(feed(&ruleset, &engine, 240, failed("10.0.0.3"))
+            .await
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_missing_group_by_field_is_skipped() {
+        let (ruleset, engine) = engine_with(
+            r#"
+title: Brute Force
+id: 12345678-1234-1234-1234-123456789110
+correlation:
+    type: event_count
+    rules: [failed_logon]
+    group-by: [SourceIp]
+    timespan: 1m
+    condition:
+        gte: 2
+"#,
+        )
+        .await;
+
+        // Unrelated events without the field must not pool into one group
+        for offset in 0..3 {
+            assert!(feed(&ruleset, &engine, offset, json!({"EventID": 4625}))
+                .await
+                .is_empty());
+        }
+        assert!(engine.correlations[0].groups.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_live_groups_are_bounded() {
+        let (_ruleset, engine) = engine_with(
+            r#"
+title: Brute Force
+id: 12345678-1234-1234-1234-123456789110
+correlation:
+    type: event_count
+    rules: [failed_logon]
+    group-by: [SourceIp]
+    timespan: 1h
+    condition:
+        gte: 2
+"#,
+        )
+        .await;
+        let correlation = &engine.correlations[0];
+        let matched: HashSet<&str> = correlation.rule_ids.iter().map(String::as_str).collect();
+        let time =
+            |millis: i64| DateTime::from_timestamp_millis(BASE_TIME * 1000 + millis).unwrap();
+
+        for i in 0..=MAX_GROUPS_PER_CORRELATION {
+            let event = DynamicEvent::new(json!({"SourceIp": format!("10.0.{}", i)}));
+            assert!(correlation
+                .observe(&event, &matched, time(i as i64))
+                .is_none());
+        }
+
+        // All groups are inside the timespan, so the oldest one makes room
+        let groups = correlation.groups.lock();
+        assert_eq!(groups.len(), MAX_GROUPS_PER_CORRELATION);
+        assert!(!groups.contains_key("10.0.0"));
+        assert!(groups.contains_key(&format!("10.0.{}", MAX_GROUPS_PER_CORRELATION)));
+    }
+
+    #[tokio::test]
+    async fn test_value_count_correlation() {
+        let (ruleset, engine) = engine_with(
+            r#"
+title: Password Spraying
+id: 12345678-1234-1234-1234-123456789111
+correlation:
+    type: value_count
+    rules: [failed_logon]
+    group-by: [SourceIp]
+    timespan: 5m
+    condition:
+        gte: 3
+        field: TargetUserName
+"#,
+        )
+        .await;
+
+        let failed =
+            |user: &str| json!({"EventID": 4625, "SourceIp": "10.0.0.1", "TargetUserName": user});
+
+        assert!(feed(&ruleset, &engine, 0, failed("alice")).await.is_empty());
+        // Repeated values are only counted once
+        assert!(feed(&ruleset, &engine, 5, failed("alice")).await.is_empty());
+        assert!(feed(&ruleset, &engine, 10, failed("bob")).await.is_empty());
+
+        let alerts = feed(&ruleset, &engine, 15, failed("carol")).await;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].value, 3.0);
+    }
+
+    #[tokio::test]
+    async fn test_temporal_correlation() {
+        let (ruleset, engine) = engine_with(
+            r#"
+title: Logon After Failures
+id: 12345678-1234-1234-1234-123456789112
+correlation:
+    type: temporal
+    rules:
+        - failed_logon
+        - 12345678-1234-1234-1234-123456789102
+    group-by: [TargetUserName]
+    timespan: 1m
+"#,
+        )
+        .await;
+
+        let event = |id: u32, user: &str| json!({"EventID": id, "TargetUserName": user});
+
+        // Order does not matter for temporal correlations
+        assert!(feed(&ruleset, &engine, 0, event(4624, "alice"))
+            .await
+            .is_empty());
+        assert!(feed(&ruleset, &engine, 5, event(4625, "bob"))
+            .await
+            .is_empty());
+        let alerts = feed(&ruleset, &engine, 10, event(4625, "alice")).await;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].value, 2.0);
+
+        // Outside the timespan the pair does not correlate
+        assert!(feed(&ruleset, &engine, 100, event(4624, "bob"))
+            .await
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_temporal_ordered_correlation() {
+        let (ruleset, engine) = engine_with(
+            r#"
+title: Successful Brute Force
+id: 12345678-1234-1234-1234-123456789113
+correlation:
+    type: temporal_ordered
+    rules:
+        - failed_logon
+        - successful_logon
+    group-by: [TargetUserName]
+    timespan: 1m
+"#,
+        )
+        .await;
+
+        let event = |id: u32| json!({"EventID": id, "TargetUserName": "alice"});
+
+        // Success before failure is the wrong order
+        assert!(feed(&ruleset, &engine, 0, event(4624)).await.is_empty());
+        assert!(feed(&ruleset, &engine, 5, event(4625)).await.is_empty());
+
+        let alerts = feed(&ruleset, &engine, 10, event(4624)).await;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].correlation_type, CorrelationType::TemporalOrdered);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_rule_reference() {
+        let dangling = r#"
+title: Dangling Correlation
+id: 12345678-1234-1234-1234-123456789114
+correlation:
+    type: event_count
+    rules: [does_not_exist]
+    timespan: 1m
+    condition:
+        gt: 1
+"#;
+        let brute_force = r#"
+title: Brute Force
+id: 12345678-1234-1234-1234-123456789110
+correlation:
+    type: event_count
+    rules: [failed_logon]
+    group-by: [SourceIp]
+    timespan: 1m
+    condition:
+        gte: 2
+"#;
+        let mut ruleset = RuleSet::new();
+        for yaml in [FAILED_LOGON, dangling, brute_force] {
+            ruleset
+                .add_rule(rule_from_yaml(yaml.as_bytes()).unwrap())
+                .await
+                .unwrap();
+        }
+
+        assert!(matches!(
+            CorrelationEngine::new_strict(&ruleset),
+            Err(SigmaError::RuleNotFound(_))
+        ));
+
+        // Only the dangling correlation is skipped
+        let engine = CorrelationEngine::new(&ruleset).unwrap();
+        assert_eq!(engine.len(), 1);
+        let failed = json!({"EventID": 4625, "SourceIp": "10.0.0.1"});
+        assert!(feed(&ruleset, &engine, 0, failed.clone()).await.is_empty());
+        let alerts = feed(&ruleset, &engine, 10, failed).await;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].rule_title, "Brute Force");
+    }
+
+    #[tokio::test]
+    async fn test_out_of_order_events() {
+        let (ruleset, engine) = engine_with(
+            r#"
+title: Brute Force
+id: 12345678-1234-1234-1234-123456789110
+correlation:
+    type: event_count
+    rules: [failed_logon]
+    group-by: [SourceIp]
+    timespan: 1m
+    condition:
+        gte: 3
+"#,
+        )
+        .await;
+
+        let failed = json!({"EventID": 4625, "SourceIp": "10.0.0.1"});
+
+        assert!(feed(&ruleset, &engine, 100, failed.clone())
+            .await
+            .is_empty());
+        // Older than the group's window, so it is not counted
+        assert!(feed(&ruleset, &engine, 30, failed.clone()).await.is_empty());
+        // Late but inside the window
+        assert!(feed(&ruleset, &engine, 50, failed.clone()).await.is_empty());
+        let hits: Vec<i64> = engine.correlations[0].groups.lock()["10.0.0.1"]
+            .iter()
+            .map(|hit| hit.timestamp.timestamp() - BASE_TIME)
+            .collect();
+        assert_eq!(hits, [50, 100]);
+
+        let alerts = feed(&ruleset, &engine, 105, failed).await;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].value, 3.0);
+    }
+}
@@ -1,6 +1,10 @@
 //! Core Sigma engine implementation
 
-use crate::{Result, RuleSet, SigmaEngineBuilder};
+use crate::consumer::{
+    create_sigma_consumer_with_sinks, ConsumerConfig, ConsumerError, RetryPolicy, ShutdownState,
+};
+use crate::correlation::{CorrelationAlert, CorrelationEngine};
+use crate::{KafkaConfig, Result, RuleSet, SigmaEngineBuilder, SigmaError};
 use std::sync::Arc;
 
 /// The main Sigma rule evaluation engine
@@ -8,6 +12,8 @@ use std::sync::Arc;
 pub struct SigmaEngine {
     /// The loaded ruleset
     pub ruleset: Arc<RuleSet>,
+    /// Correlation rules of the ruleset, fed every evaluated event
+    pub correlations: Arc<CorrelationEngine>,
     /// Engine configuration
     pub config: SigmaEngineBuilder,
 }
@@ -17,6 +23,17 @@ impl SigmaEngine {
     pub async fn new(builder: SigmaEngineBuilder) -> Result<Self> {
         // Load rules from directories
         let mut ruleset = RuleSet::new();
+        if let Some(placeholders) = &builder.placeholders {
+            ruleset.set_placeholders(placeholders.clone());
+        }
+        if let Some(pipeline) = &builder.pipeline {
+            ruleset.set_pipeline(pipeline.clone());
+        }
+        if let Some(classifier) = &builder.classifier {
+            ruleset.set_classifier(classifier.clone());
+        }
+        ruleset.set_strict(builder.strict);
+        ruleset.set_aggregation_config(builder.aggregation_config.clone());
 
         for dir in &builder.rule_dirs {
             match ruleset.load_directory(dir).await {
@@ -31,8 +48,15 @@ impl SigmaEngine {
             }
         }
 
+        let correlations = if builder.fail_on_parse_error {
+            CorrelationEngine::new_strict(&ruleset)?
+        } else {
+            CorrelationEngine::new(&ruleset)?
+        };
+
         Ok(Self {
             ruleset: Arc::new(ruleset),
+            correlations: Arc::new(correlations),
             config: builder,
         })
     }
@@ -42,16 +66,103 @@ impl SigmaEngine {
         &self.ruleset
     }
 
+    /// Create an event from JSON, taking its event time from the configured
+    /// [timestamp extractor](SigmaEngineBuilder::with_timestamp_extractor)
+    pub fn event_from_json(&self, data: serde_json::Value) -> crate::DynamicEvent {
+        let event = crate::DynamicEvent::new(data);
+        match &self.config.timestamp_extractor {
+            Some(extractor) => event.with_event_time(extractor),
+            None => event,
+        }
+    }
+
     /// Process a single event
     pub async fn process_event(&self, event: crate::DynamicEvent) -> Result<crate::RuleSetResult> {
         self.ruleset.evaluate(&event).await
     }
 
-    /// Run the engine (placeholder for actual implementation)
+    /// Record an evaluated event's matches and return the correlations it completes
+    ///
+    /// Call this once per event with the result of [`process_event`](Self::process_event).
+    pub fn correlate(
+        &self,
+        event: &dyn crate::Event,
+        result: &crate::RuleSetResult,
+    ) -> Vec<CorrelationAlert> {
+        self.correlations.process(event, result)
+    }
+
+    /// Process a single event, explaining each rule match
+    pub async fn process_event_explained(
+        &self,
+        event: crate::DynamicEvent,
+    ) -> Result<crate::RuleSetResult> {
+        self.ruleset.explain(&event).await
+    }
+
+    /// Run the engine until Ctrl-C: consume events from the configured Kafka
+    /// topics, evaluate them and deliver alerts to the configured sinks
     pub async fn run(self) -> Result<()> {
-        // This would be implemented with actual engine logic
-        // For now, just a placeholder
-        Ok(())
+        self.run_with_shutdown(Arc::new(ShutdownState::new())).await
+    }
+
+    /// Run the engine until Ctrl-C or until shutdown of `shutdown_state` begins
+    ///
+    /// Stop the engine gracefully with a
+    /// [`ShutdownCoordinator`](crate::consumer::ShutdownCoordinator) sharing
+    /// the state: in-flight events finish, their alerts are delivered and the
+    /// final offsets are committed before this returns.
+    pub async fn run_with_shutdown(self, shutdown_state: Arc<ShutdownState>) -> Result<()> {
+        let kafka = self.config.kafka_config.clone().ok_or_else(|| {
+            SigmaError::Configuration("Kafka must be configured to run the engine".to_string())
+        })?;
+        let config = consumer_config(&kafka);
+        let sinks = self.config.alert_sinks.clone();
+
+        let consumer = create_sigma_consumer_with_sinks(Arc::new(self), config, sinks)
+            .await
+            .map_err(consumer_error)?;
+
+        consumer
+            .with_shutdown_state(shutdown_state)
+            .run()
+            .await
+            .map_err(consumer_error)
+    }
+}
+
+/// Build the consumer configuration for the engine's Kafka settings
+fn consumer_config(kafka: &KafkaConfig) -> ConsumerConfig {
+    let defaults = ConsumerConfig::default();
+    let max_retries = kafka
+        .max_retries
+        .unwrap_or(defaults.retry_policy.max_retries);
+
+    ConsumerConfig {
+        brokers: kafka.brokers.clone(),
+        group_id: kafka.group_id.clone(),
+        topics: kafka.topics.clone(),
+        kafka_properties: kafka.properties.clone(),
+        batch_size: kafka.batch_size.unwrap_or(defaults.batch_size),
+        retry_policy: RetryPolicy {
+            max_retries,
+            ..defaults.retry_policy
+        },
+        dlq_after_retries: defaults.dlq_after_retries.min(max_retries),
+        dlq_topic: kafka.dlq_topic.clone(),
+        output_topic: kafka.output_topic.clone(),
+        max_inflight_messages: kafka
+            .backpressure_buffer_size
+            .unwrap_or(defaults.max_inflight_messages),
+        enable_detailed_metrics: kafka.enable_metrics,
+        ..defaults
+    }
+}
+
+fn consumer_error(error: ConsumerError) -> SigmaError {
+    match error {
+        ConsumerError::ConfigError(e) => SigmaError::Configuration(e),
+        e => SigmaError::Kafka(e.to_string()),
     }
 }
 
@@ -65,4 +176,55 @@ mod tests {
         let engine = SigmaEngine::new(builder).await.unwrap();
         assert_eq!(engine.ruleset().len(), 0);
     }
+
+    #[tokio::test]
+    async fn test_event_from_json_uses_extractor() {
+        use crate::event::{Event, TimestampExtractor};
+
+        let data = serde_json::json!({"@timestamp": "2024-01-02T03:04:05Z"});
+        let engine = SigmaEngineBuilder::new().build().await.unwrap();
+        assert_eq!(engine.event_from_json(data.clone()).event_time(), None);
+
+        let engine = SigmaEngineBuilder::new()
+            .with_timestamp_extractor(TimestampExtractor::default())
+            .build()
+            .await
+            .unwrap();
+        let event = engine.event_from_json(data);
+        assert_eq!(
+            event.event_time().map(|t| t.timestamp()),
+            Some(1_704_164_645)
+        );
+        assert_eq!(event.timestamp(), 1_704_164_645);
+    }
+
+    #[tokio::test]
+    async fn test_run_requires_kafka() {
+        let engine = SigmaEngineBuilder::new().build().await.unwrap();
+        assert!(matches!(
+            engine.run().await,
+            Err(SigmaError::Configuration(_))
+        ));
+    }
+
+    #[test]
+    fn test_consumer_config() {
+        let kafka = KafkaConfig {
+            topics: vec!["security-events".to_string()],
+            max_retries: Some(1),
+            dlq_topic: Some("dlq-events".to_string()),
+            output_topic: Some("sigma-alerts".to_string()),
+            backpressure_buffer_size: Some(64),
+            ..Default::default()
+        };
+
+        let config = consumer_config(&kafka);
+        assert_eq!(config.topics, ["security-events"]);
+        assert_eq!(config.batch_size, 1000);
+        assert_eq!(config.retry_policy.max_retries, 1);
+        assert_eq!(config.dlq_after_retries, 1);
+        assert_eq!(config.output_topic.as_deref(), Some("sigma-alerts"));
+        assert_eq!(config.max_inflight_messages, 64);
+        assert!(config.validate().is_ok());
+    }
 }
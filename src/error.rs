@@ -77,6 +77,15 @@ pub enum SigmaError {
     #[error("Kafka error: {0}")]
     Kafka(String),
 
+    /// Rule rejected by strict loading
+    #[error("Rule '{rule_id}' rejected by strict validation: {violation}")]
+    StrictValidation {
+        /// ID of the rejected rule
+        rule_id: String,
+        /// What the rule violated
+        violation: crate::parser::StrictViolation,
+    },
+
     /// Configuration is invalid or incomplete
     #[error("Invalid configuration: {0}")]
     Configuration(String),
@@ -5,6 +5,7 @@ use std::sync::Arc;
 
 // Export EventBuilder for tests
 pub use builder::EventBuilder;
+pub use timestamp::{TimestampExtractor, TimestampFormat};
 
 /// Trait for events that can provide keyword fields for matching
 pub trait Keyworder {
@@ -27,6 +28,13 @@ pub trait Event: Keyworder + Selector + Send + Sync {
 
     /// Get event timestamp
     fn timestamp(&self) -> i64;
+
+    /// Get the time the event occurred, if known
+    ///
+    /// Windowed aggregations use this and fall back to processing time when it is `None`.
+    fn event_time(&self) -> Option<DateTime<Utc>> {
+        None
+    }
 }
 
 /// Value type that can be returned from selection
@@ -136,6 +144,10 @@ impl<'de> Deserialize<'de> for Value {
 
 /// Module with event builder for testing
 pub mod builder;
+/// Windows EVTX file reader
+pub mod evtx;
+/// Event-time extraction from timestamp fields
+pub mod timestamp;
 
 use chrono::{DateTime, Utc};
 use serde_json::Value as JsonValue;
@@ -272,6 +284,10 @@ impl Event for SimpleEvent {
     fn timestamp(&self) -> i64 {
         self.timestamp.timestamp()
     }
+
+    fn event_time(&self) -> Option<DateTime<Utc>> {
+        Some(self.timestamp)
+    }
 }
 
 impl Value {
@@ -315,6 +331,8 @@ pub struct DynamicEvent {
     data: serde_json::Value,
     id: String,
     timestamp: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    event_time: Option<DateTime<Utc>>,
 }
 
 impl DynamicEvent {
@@ -326,8 +344,34 @@ impl DynamicEvent {
             data,
             id,
             timestamp,
+            event_time: None,
         }
     }
+
+    /// Set the event timestamp, in seconds since the Unix epoch
+    pub fn with_timestamp(mut self, timestamp: i64) -> Self {
+        self.timestamp = timestamp;
+        self.event_time = DateTime::from_timestamp(timestamp, 0);
+        self
+    }
+
+    /// Set the time the event occurred, as reported by its source
+    pub fn with_time(mut self, event_time: DateTime<Utc>) -> Self {
+        self.timestamp = event_time.timestamp();
+        self.event_time = Some(event_time);
+        self
+    }
+
+    /// Set the event time from the event's own fields
+    ///
+    /// Keeps the processing-time stamp if no candidate field holds a parseable timestamp.
+    pub fn with_event_time(mut self, extractor: &TimestampExtractor) -> Self {
+        if let Some(event_time) = extractor.extract(&self) {
+            self.timestamp = event_time.timestamp();
+            self.event_time = Some(event_time);
+        }
+        self
+    }
 }
 
 impl Keyworder for DynamicEvent {
@@ -410,6 +454,10 @@ impl Event for DynamicEvent {
     fn timestamp(&self) -> i64 {
         self.timestamp
     }
+
+    fn event_time(&self) -> Option<DateTime<Utc>> {
+        self.event_time
+    }
 }
 
 impl DynamicEvent {
@@ -509,6 +557,28 @@ mod tests {
         assert!(!found);
     }
 
+    #[test]
+    fn test_dynamic_event_time_extraction() {
+        let extractor = TimestampExtractor::default();
+
+        let event = DynamicEvent::new(serde_json::json!({
+            "@timestamp": "2024-01-01T00:00:00Z",
+            "message": "historical"
+        }))
+        .with_event_time(&extractor);
+        assert_eq!(event.timestamp(), 1_704_067_200);
+        assert_eq!(
+            event.event_time().map(|t| t.timestamp()),
+            Some(1_704_067_200)
+        );
+
+        // Without a timestamp field the processing time is kept
+        let event =
+            DynamicEvent::new(serde_json::json!({"message": "live"})).with_event_time(&extractor);
+        assert!(event.event_time().is_none());
+        assert!(event.timestamp() > 1_704_067_200);
+    }
+
     #[test]
     fn test_dynamic_event_keywords() {
         let data = serde_json::json!({
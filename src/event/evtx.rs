@@ -0,0 +1,361 @@
+//! Windows XML Event Log (EVTX) reader
+//!
+//! Records are decoded by the [`evtx`] crate and flattened into the field
+//! layout Sigma rules for Windows expect:
+//!
+//! - `System` children become fields named after the element (`EventID`,
+//!   `Channel`, `Computer`, ...) and their attributes `<Element>_<Attribute>`
+//!   (`Provider_Name`, `Execution_ProcessID`, ...). `TimeCreated` holds the
+//!   `SystemTime` attribute.
+//! - `EventData` `<Data Name="...">` values become fields named by `Name`
+//!   (`Image`, `CommandLine`, ...); unnamed values are collected in `Data`.
+//! - `UserData` leaf elements become fields named after the element.
+//!
+//! [`EvtxRecord::into_event`] uses the time the record was written as the
+//! event time.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sigma_rs::event::evtx::EvtxReader;
+//!
+//! # fn example() -> sigma_rs::Result<()> {
+//! for record in EvtxReader::open("Security.evtx")? {
+//!     let record = record?;
+//!     println!("{} {}", record.record_id, record.event["EventID"]);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{Result, SigmaError};
+use crate::event::DynamicEvent;
+use chrono::{DateTime, Utc};
+use evtx::err::EvtxError;
+use evtx::{EvtxChunkData, EvtxParser, IntoIterChunks, ParserSettings};
+use serde_json::{Map, Value as JsonValue};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::Path;
+use std::sync::Arc;
+
+/// An event record read from an EVTX file
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvtxRecord {
+    /// Event record identifier
+    pub record_id: u64,
+    /// Time the record was written
+    pub timestamp: DateTime<Utc>,
+    /// Flattened event fields
+    pub event: JsonValue,
+}
+
+impl EvtxRecord {
+    /// Convert into an event for rule evaluation, occurring at the record's timestamp
+    pub fn into_event(self) -> DynamicEvent {
+        DynamicEvent::new(self.event).with_time(self.timestamp)
+    }
+}
+
+/// Streams the records of an EVTX file, one chunk at a time
+///
+/// Chunks are read until the end of the input rather than trusting the
+/// header's chunk count, which is stale in files copied from a live system.
+/// A record or chunk that fails to parse is yielded as an error and reading
+/// continues with the next one.
+pub struct EvtxReader<R: Read + Seek> {
+    chunks: IntoIterChunks<R>,
+    settings: Arc<ParserSettings>,
+    records: std::vec::IntoIter<Result<EvtxRecord>>,
+}
+
+impl EvtxReader<BufReader<File>> {
+    /// Open an EVTX file
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read + Seek> EvtxReader<R> {
+    /// Read EVTX data, validating the file header
+    pub fn new(reader: R) -> Result<Self> {
+        let settings = ParserSettings::new();
+        let parser = EvtxParser::from_read_seek(reader)
+            .map_err(corrupt)?
+            .with_configuration(settings.clone());
+
+        Ok(Self {
+            chunks: parser.into_chunks(),
+            settings: Arc::new(settings),
+            records: Vec::new().into_iter(),
+        })
+    }
+}
+
+impl<R: Read + Seek> Iterator for EvtxReader<R> {
+    type Item = Result<EvtxRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(record) = self.records.next() {
+                return Some(record);
+            }
+            let chunk = self.chunks.next()?;
+            self.records = parse_chunk(chunk, &self.settings).into_iter();
+        }
+    }
+}
+
+fn corrupt(error: impl std::fmt::Display) -> SigmaError {
+    SigmaError::Parse(format!("Invalid EVTX data: {}", error))
+}
+
+/// Decode and flatten every record of a chunk
+fn parse_chunk(
+    chunk: std::result::Result<EvtxChunkData, EvtxError>,
+    settings: &Arc<ParserSettings>,
+) -> Vec<Result<EvtxRecord>> {
+    let mut data = match chunk {
+        Ok(data) => data,
+        Err(e) => return vec![Err(corrupt(e))],
+    };
+    let mut chunk = match data.parse(settings.clone()) {
+        Ok(chunk) => chunk,
+        Err(e) => return vec![Err(corrupt(e))],
+    };
+
+    chunk
+        .iter()
+        .map(|record| {
+            let record = record
+                .map_err(corrupt)?
+                .into_json_value()
+                .map_err(corrupt)?;
+            Ok(EvtxRecord {
+                record_id: record.event_record_id,
+                timestamp: record.timestamp,
+                event: flatten(&record.data).map_err(corrupt)?,
+            })
+        })
+        .collect()
+}
+
+/// Child elements of a decoded element, leaving out `#attributes` and `#text`
+fn children(element: &Map<String, JsonValue>) -> impl Iterator<Item = (&String, &JsonValue)> {
+    element.iter().filter(|(name, _)| !name.starts_with('#'))
+}
+
+/// Content of an element, empty when it has none
+fn text(value: &JsonValue) -> JsonValue {
+    match value {
+        JsonValue::Object(element) => element
+            .get("#text")
+            .cloned()
+            .unwrap_or_else(|| JsonValue::String(String::new())),
+        JsonValue::Null => JsonValue::String(String::new()),
+        value => value.clone(),
+    }
+}
+
+/// Flatten an `<Event>` into Sigma's Windows field layout
+fn flatten(record: &JsonValue) -> std::result::Result<JsonValue, &'static str> {
+    let event = record
+        .get("Event")
+        .and_then(JsonValue::as_object)
+        .ok_or("record has no Event element")?;
+
+    let mut fields = Map::new();
+    for (section, value) in children(event) {
+        let Some(section_element) = value.as_object() else {
+            continue;
+        };
+        match section.as_str() {
+            "System" => flatten_system(section_element, &mut fields),
+            "EventData" => flatten_event_data(section_element, &mut fields),
+            "UserData" => children(section_element)
+                .for_each(|(name, value)| flatten_leaves(name, value, &mut fields)),
+            _ => {}
+        }
+    }
+    Ok(JsonValue::Object(fields))
+}
+
+fn flatten_system(system: &Map<String, JsonValue>, fields: &mut Map<String, JsonValue>) {
+    for (name, value) in children(system) {
+        let JsonValue::Object(element) = value else {
+            if !value.is_null() {
+                fields.insert(name.clone(), value.clone());
+            }
+            continue;
+        };
+
+        if let Some(value) = element.get("#text") {
+            fields.insert(name.clone(), value.clone());
+        }
+        let attributes = element.get("#attributes").and_then(JsonValue::as_object);
+        for (attribute, value) in attributes.into_iter().flatten() {
+            let field = if name == "TimeCreated" && attribute == "SystemTime" {
+                name.clone()
+            } else {
+                format!("{}_{}", name, attribute)
+            };
+            fields.insert(field, value.clone());
+        }
+    }
+}
+
+/// Named `Data` values arrive keyed by their `Name` attribute
+fn flatten_event_data(data: &Map<String, JsonValue>, fields: &mut Map<String, JsonValue>) {
+    for (name, value) in children(data) {
+        fields.insert(name.clone(), text(value));
+    }
+}
+
+fn flatten_leaves(name: &str, value: &JsonValue, fields: &mut Map<String, JsonValue>) {
+    match value {
+        JsonValue::Object(element) if children(element).next().is_some() => {
+            children(element).for_each(|(name, value)| flatten_leaves(name, value, fields))
+        }
+        value => {
+            fields.insert(name.to_string(), text(value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Event;
+    use std::io::Cursor;
+
+    fn fixture(name: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/evtx")
+            .join(name)
+    }
+
+    fn read_all(name: &str) -> Vec<EvtxRecord> {
+        EvtxReader::open(fixture(name))
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_read_sysmon_records() {
+        // Two chunks, the first with a template shared by its two records
+        let records = read_all("sysmon.evtx");
+        let ids: Vec<u64> = records.iter().map(|r| r.record_id).collect();
+        assert_eq!(ids, [1, 2, 3]);
+
+        let event = &records[0].event;
+        assert_eq!(event["EventID"], 1);
+        assert_eq!(event["Channel"], "Microsoft-Windows-Sysmon/Operational");
+        assert_eq!(event["Provider_Name"], "Microsoft-Windows-Sysmon");
+        assert_eq!(
+            event["Provider_Guid"],
+            "5770385F-C22A-43E0-BF4C-06F5698FFBD9"
+        );
+        assert_eq!(event["Computer"], "WKS01.contoso.local");
+        assert_eq!(event["Keywords"], "0x8000000000000000");
+        assert_eq!(event["TimeCreated"], "2024-03-05T10:15:30.123456Z");
+        assert_eq!(event["Security_UserID"], "S-1-5-18");
+        assert_eq!(event["ProcessId"], 6001);
+        assert_eq!(
+            event["Image"],
+            "C:\\Windows\\System32\\WindowsPowerShell\\v1.0\\powershell.exe"
+        );
+        assert!(event.get("EventID_Qualifiers").is_none());
+        assert!(event.get("Correlation_ActivityID").is_none());
+
+        assert_eq!(records[1].event["CommandLine"], "whoami /all");
+        assert_eq!(
+            records[2].event["Image"],
+            "C:\\Windows\\System32\\notepad.exe"
+        );
+        assert_eq!(
+            records[2].timestamp,
+            "2024-03-05T10:16:02Z".parse::<DateTime<Utc>>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_read_security_records() {
+        let records = read_all("security.evtx");
+        assert_eq!(records.len(), 2);
+
+        let logon = &records[0].event;
+        assert_eq!(logon["EventID"], 4624);
+        assert_eq!(logon["LogonType"], 10);
+        assert_eq!(
+            logon["TargetUserSid"],
+            "S-1-5-21-3623811015-3361044348-30300820-1013"
+        );
+        assert_eq!(logon["IpAddress"], "10.0.0.5");
+
+        // UserData from a BinXML substitution with its own template
+        let cleared = &records[1].event;
+        assert_eq!(cleared["EventID"], 1102);
+        assert_eq!(cleared["SubjectUserName"], "alice");
+        assert_eq!(cleared["SubjectLogonId"], "0x3e7a1");
+    }
+
+    #[test]
+    fn test_corrupt_record_is_skipped() {
+        // File header, chunk header and record header precede the first record's BinXML
+        const FIRST_RECORD_DATA: usize = 4096 + 512 + 24;
+
+        let mut data = std::fs::read(fixture("sysmon.evtx")).unwrap();
+        // Replace the first record's fragment header with an unknown token
+        data[FIRST_RECORD_DATA] = 0x3f;
+
+        let records: Vec<_> = EvtxReader::new(Cursor::new(data)).unwrap().collect();
+        assert_eq!(records.len(), 3);
+        assert!(records[0].is_err());
+        assert_eq!(
+            records[1].as_ref().unwrap().event["CommandLine"],
+            "whoami /all"
+        );
+        assert_eq!(records[2].as_ref().unwrap().record_id, 3);
+
+        assert!(EvtxReader::new(Cursor::new(b"not an event log")).is_err());
+        assert!(EvtxReader::new(Cursor::new([0u8; 4096])).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_records() -> Result<()> {
+        let mut ruleset = crate::RuleSet::new();
+        ruleset
+            .add_rule(crate::rule::rule_from_yaml(
+                br#"
+        title: Encoded PowerShell
+        id: 12345678-1234-1234-1234-123456789090
+        logsource:
+            product: windows
+            category: process_creation
+        detection:
+            selection:
+                EventID: 1
+                Image|endswith: '\powershell.exe'
+                CommandLine|contains: ' -enc '
+            condition: selection
+        "#,
+            )?)
+            .await?;
+
+        let mut matched = Vec::new();
+        for record in EvtxReader::open(fixture("sysmon.evtx"))? {
+            let record = record?;
+            let record_id = record.record_id;
+            let timestamp = record.timestamp;
+            let event = record.into_event();
+            assert_eq!(event.event_time(), Some(timestamp));
+            let result = ruleset.evaluate(&event).await?;
+            if result.matches.iter().any(|m| m.matched) {
+                matched.push(record_id);
+            }
+        }
+        assert_eq!(matched, [1]);
+        Ok(())
+    }
+}
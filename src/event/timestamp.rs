@@ -0,0 +1,392 @@
+use super::{Selector, Value};
+use chrono::{DateTime, Datelike, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Seconds between the Windows FILETIME epoch (1601-01-01) and the Unix epoch
+const FILETIME_UNIX_OFFSET_SECS: i64 = 11_644_473_600;
+
+/// FILETIME ticks (100ns intervals) per second
+const FILETIME_TICKS_PER_SEC: i64 = 10_000_000;
+
+/// Epoch values at or above this magnitude are read as milliseconds by [`TimestampFormat::Auto`]
+///
+/// 10^11 seconds is in the year 5138, while 10^11 milliseconds is in 1973.
+const EPOCH_MILLIS_THRESHOLD: i64 = 100_000_000_000;
+
+/// Epoch values at or above this magnitude are read as microseconds by [`TimestampFormat::Auto`]
+///
+/// 10^14 milliseconds is in the year 5138, while 10^14 microseconds is in 1973.
+const EPOCH_MICROS_THRESHOLD: i64 = 100_000_000_000_000;
+
+/// Values at or above this magnitude are read as FILETIME ticks by [`TimestampFormat::Auto`]
+///
+/// 10^16 microseconds is in the year 318857, while 10^16 ticks is in 1632.
+const FILETIME_THRESHOLD: i64 = 10_000_000_000_000_000;
+
+/// Latest year [`TimestampFormat::Auto`] accepts
+///
+/// A value in a unit the magnitudes do not tell apart, such as epoch
+/// nanoseconds, would otherwise land far in the future and hold back every
+/// later event behind it.
+const AUTO_MAX_YEAR: i32 = 3000;
+
+/// Offset-less timestamp layouts tried by [`TimestampFormat::Auto`], read as UTC
+///
+/// Covers ISO 8601 without an offset, Sysmon `UtcTime` and the Windows
+/// `SystemTime` renderings produced by event log exports and PowerShell.
+const NAIVE_TIMESTAMP_FORMATS: [&str; 5] = [
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%m/%d/%Y %I:%M:%S %p",
+    "%m/%d/%Y %H:%M:%S",
+    "%Y/%m/%d %H:%M:%S%.f",
+];
+
+/// Format of a timestamp field in an event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimestampFormat {
+    /// Any common rendering: RFC 3339, offset-less ISO 8601 and Windows
+    /// `SystemTime` strings (taken as UTC), and numbers read by magnitude as
+    /// epoch seconds, milliseconds, microseconds or FILETIME ticks
+    ///
+    /// Times after the year 3000 are rejected. Epoch nanoseconds overlap
+    /// FILETIME ticks and need [`EpochNanos`](Self::EpochNanos).
+    Auto,
+    /// RFC 3339 / ISO 8601 string, e.g. `2024-01-01T12:00:00.123Z`
+    Rfc3339,
+    /// Seconds since the Unix epoch, optionally fractional
+    EpochSeconds,
+    /// Milliseconds since the Unix epoch
+    EpochMillis,
+    /// Nanoseconds since the Unix epoch
+    EpochNanos,
+    /// Windows FILETIME: 100ns intervals since 1601-01-01 UTC
+    WindowsFileTime,
+    /// Custom `strftime` pattern; values without an offset are taken as UTC
+    Strftime(String),
+}
+
+impl TimestampFormat {
+    /// Parse an event value in this format
+    ///
+    /// Numeric formats accept numbers and numeric strings.
+    pub fn parse(&self, value: &Value) -> Option<DateTime<Utc>> {
+        match value {
+            Value::String(s) => self.parse_str(s),
+            Value::Integer(i) => self.parse_integer(*i).filter(|t| self.plausible(t)),
+            Value::Float(f) => self.parse_float(*f).filter(|t| self.plausible(t)),
+            _ => None,
+        }
+    }
+
+    /// Parse a textual timestamp in this format
+    pub fn parse_str(&self, text: &str) -> Option<DateTime<Utc>> {
+        let text = text.trim();
+        match self {
+            TimestampFormat::Rfc3339 => parse_rfc3339(text),
+            TimestampFormat::Strftime(pattern) => DateTime::parse_from_str(text, pattern)
+                .map(|dt| dt.with_timezone(&Utc))
+                .or_else(|_| NaiveDateTime::parse_from_str(text, pattern).map(|dt| dt.and_utc()))
+                .ok(),
+            TimestampFormat::Auto => parse_rfc3339(text)
+                .or_else(|| {
+                    NAIVE_TIMESTAMP_FORMATS
+                        .iter()
+                        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+                        .map(|dt| dt.and_utc())
+                })
+                .or_else(|| self.parse_numeric_str(text))
+                .filter(|t| self.plausible(t)),
+            _ => self.parse_numeric_str(text),
+        }
+    }
+
+    /// Whether a parsed time is believable; only [`Auto`](Self::Auto) guesses units
+    fn plausible(&self, time: &DateTime<Utc>) -> bool {
+        *self != TimestampFormat::Auto || time.year() <= AUTO_MAX_YEAR
+    }
+
+    fn parse_numeric_str(&self, text: &str) -> Option<DateTime<Utc>> {
+        match text.parse::<i64>() {
+            Ok(integer) => self.parse_integer(integer),
+            Err(_) => self.parse_float(text.parse().ok()?),
+        }
+    }
+
+    fn parse_integer(&self, value: i64) -> Option<DateTime<Utc>> {
+        match self {
+            TimestampFormat::Auto if value.saturating_abs() >= FILETIME_THRESHOLD => {
+                TimestampFormat::WindowsFileTime.parse_integer(value)
+            }
+            TimestampFormat::Auto if value.saturating_abs() >= EPOCH_MICROS_THRESHOLD => {
+                DateTime::from_timestamp_micros(value)
+            }
+            TimestampFormat::Auto if value.saturating_abs() >= EPOCH_MILLIS_THRESHOLD => {
+                DateTime::from_timestamp_millis(value)
+            }
+            TimestampFormat::Auto | TimestampFormat::EpochSeconds => {
+                DateTime::from_timestamp(value, 0)
+            }
+            TimestampFormat::EpochMillis => DateTime::from_timestamp_millis(value),
+            TimestampFormat::EpochNanos => DateTime::from_timestamp(
+                value.div_euclid(1_000_000_000),
+                value.rem_euclid(1_000_000_000) as u32,
+            ),
+            TimestampFormat::WindowsFileTime => {
+                let secs = value.div_euclid(FILETIME_TICKS_PER_SEC) - FILETIME_UNIX_OFFSET_SECS;
+                let nanos = value.rem_euclid(FILETIME_TICKS_PER_SEC) * 100;
+                DateTime::from_timestamp(secs, nanos as u32)
+            }
+            TimestampFormat::Rfc3339 | TimestampFormat::Strftime(_) => None,
+        }
+    }
+
+    fn parse_float(&self, value: f64) -> Option<DateTime<Utc>> {
+        if !value.is_finite() {
+            return None;
+        }
+        let fractional_seconds = match self {
+            TimestampFormat::EpochSeconds => true,
+            TimestampFormat::Auto => value.abs() < EPOCH_MILLIS_THRESHOLD as f64,
+            _ => false,
+        };
+        if fractional_seconds {
+            let secs = value.floor();
+            let nanos = ((value - secs) * 1e9).round().min(999_999_999.0) as u32;
+            DateTime::from_timestamp(secs as i64, nanos)
+        } else if value.fract() == 0.0 || *self == TimestampFormat::Auto {
+            // Whole units; Auto drops the fraction of milliseconds and ticks
+            (value.abs() < i64::MAX as f64)
+                .then(|| self.parse_integer(value.trunc() as i64))
+                .flatten()
+        } else {
+            None
+        }
+    }
+}
+
+fn parse_rfc3339(text: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+impl std::str::FromStr for TimestampFormat {
+    type Err = String;
+
+    /// Parse a format name such as `epoch_millis`, or a `strftime` pattern containing `%`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(TimestampFormat::Auto),
+            "rfc3339" => Ok(TimestampFormat::Rfc3339),
+            "epoch_seconds" => Ok(TimestampFormat::EpochSeconds),
+            "epoch_millis" => Ok(TimestampFormat::EpochMillis),
+            "epoch_nanos" => Ok(TimestampFormat::EpochNanos),
+            "windows_file_time" => Ok(TimestampFormat::WindowsFileTime),
+            pattern if pattern.contains('%') => Ok(TimestampFormat::Strftime(pattern.to_string())),
+            other => Err(format!(
+                "unknown timestamp format '{}': expected auto, rfc3339, epoch_seconds, epoch_millis, \
+                 epoch_nanos, windows_file_time or a strftime pattern",
+                other
+            )),
+        }
+    }
+}
+
+/// Extracts event time from a list of candidate fields
+///
+/// Fields are tried in order and each present field is parsed with every
+/// format in order; the first successful parse wins. Events without a
+/// parseable timestamp fall back to processing time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimestampExtractor {
+    /// Candidate field paths, in dot notation for nested fields
+    pub fields: Vec<String>,
+    /// Formats to try for each field
+    pub formats: Vec<TimestampFormat>,
+}
+
+impl Default for TimestampExtractor {
+    fn default() -> Self {
+        Self {
+            fields: vec![
+                "@timestamp".to_string(),
+                "timestamp".to_string(),
+                "UtcTime".to_string(),
+                "EventTime".to_string(),
+                "TimeCreated".to_string(),
+            ],
+            formats: vec![TimestampFormat::Auto],
+        }
+    }
+}
+
+impl TimestampExtractor {
+    /// Create an extractor for the given fields and formats
+    pub fn new(fields: Vec<String>, formats: Vec<TimestampFormat>) -> Self {
+        Self { fields, formats }
+    }
+
+    /// Extract the event time, if any candidate field holds a parseable timestamp
+    pub fn extract(&self, event: &dyn Selector) -> Option<DateTime<Utc>> {
+        self.fields
+            .iter()
+            .filter_map(|field| event.select(field).0)
+            .find_map(|value| self.formats.iter().find_map(|f| f.parse(&value)))
+    }
+
+    /// Extract the event time, falling back to the current processing time
+    pub fn extract_or_now(&self, event: &dyn Selector) -> DateTime<Utc> {
+        self.extract(event).unwrap_or_else(Utc::now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::DynamicEvent;
+    use chrono::TimeZone;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn expected() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap()
+    }
+
+    #[test]
+    fn test_parse_formats() {
+        let rfc = Value::String(Arc::from("2024-01-02T04:04:05+01:00"));
+        assert_eq!(TimestampFormat::Rfc3339.parse(&rfc), Some(expected()));
+
+        let secs = expected().timestamp();
+        assert_eq!(
+            TimestampFormat::EpochSeconds.parse(&Value::Integer(secs)),
+            Some(expected())
+        );
+        assert_eq!(
+            TimestampFormat::EpochSeconds.parse(&Value::Float(secs as f64 + 0.5)),
+            Some(expected() + chrono::Duration::milliseconds(500))
+        );
+        assert_eq!(
+            TimestampFormat::EpochMillis
+                .parse(&Value::String(Arc::from((secs * 1000).to_string()))),
+            Some(expected())
+        );
+        assert_eq!(
+            TimestampFormat::EpochNanos.parse(&Value::Integer(secs * 1_000_000_000)),
+            Some(expected())
+        );
+
+        let filetime = (secs + FILETIME_UNIX_OFFSET_SECS) * FILETIME_TICKS_PER_SEC;
+        assert_eq!(
+            TimestampFormat::WindowsFileTime.parse(&Value::Integer(filetime)),
+            Some(expected())
+        );
+
+        let custom = TimestampFormat::Strftime("%d/%m/%Y %H:%M:%S".to_string());
+        assert_eq!(
+            custom.parse(&Value::String(Arc::from("02/01/2024 03:04:05"))),
+            Some(expected())
+        );
+
+        assert_eq!(TimestampFormat::Rfc3339.parse(&Value::Integer(secs)), None);
+        assert_eq!(
+            TimestampFormat::EpochMillis.parse(&Value::String(Arc::from("soon"))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_format_from_str() {
+        assert_eq!("epoch_millis".parse(), Ok(TimestampFormat::EpochMillis));
+        assert_eq!(
+            "windows_file_time".parse(),
+            Ok(TimestampFormat::WindowsFileTime)
+        );
+        assert_eq!(
+            "%d/%m/%Y".parse(),
+            Ok(TimestampFormat::Strftime("%d/%m/%Y".to_string()))
+        );
+        assert!("yesterday".parse::<TimestampFormat>().is_err());
+    }
+
+    #[test]
+    fn test_extractor_candidate_order() {
+        let extractor = TimestampExtractor::new(
+            vec![
+                "missing".to_string(),
+                "meta.ts".to_string(),
+                "@timestamp".to_string(),
+            ],
+            vec![TimestampFormat::Rfc3339, TimestampFormat::EpochSeconds],
+        );
+
+        let event = DynamicEvent::new(json!({
+            "meta": {"ts": expected().timestamp()},
+            "@timestamp": "2030-01-01T00:00:00Z"
+        }));
+        assert_eq!(extractor.extract(&event), Some(expected()));
+
+        // Unparseable candidates are skipped
+        let event = DynamicEvent::new(json!({
+            "meta": {"ts": "not a time"},
+            "@timestamp": "2024-01-02T03:04:05Z"
+        }));
+        assert_eq!(extractor.extract(&event), Some(expected()));
+
+        let event = DynamicEvent::new(json!({"other": 1}));
+        assert_eq!(extractor.extract(&event), None);
+        assert!(extractor.extract_or_now(&event) > expected());
+    }
+
+    #[test]
+    fn test_default_extractor_sysmon_time() {
+        let event = DynamicEvent::new(json!({"UtcTime": "2024-01-02 03:04:05.000"}));
+        assert_eq!(
+            TimestampExtractor::default().extract(&event),
+            Some(expected())
+        );
+    }
+
+    #[test]
+    fn test_auto_format() {
+        let secs = expected().timestamp();
+        for value in [
+            Value::String(Arc::from("2024-01-02T03:04:05Z")),
+            Value::String(Arc::from("2024-01-02 03:04:05")),
+            Value::String(Arc::from("1/2/2024 3:04:05 AM")),
+            Value::Integer(secs),
+            Value::String(Arc::from(secs.to_string())),
+            Value::Integer(secs * 1000),
+            Value::Float((secs * 1000) as f64),
+            Value::Integer(secs * 1_000_000),
+            Value::String(Arc::from((secs * 1_000_000).to_string())),
+            Value::Integer((secs + FILETIME_UNIX_OFFSET_SECS) * FILETIME_TICKS_PER_SEC),
+        ] {
+            assert_eq!(
+                TimestampFormat::Auto.parse(&value),
+                Some(expected()),
+                "{:?}",
+                value
+            );
+        }
+        assert_eq!(
+            TimestampFormat::Auto.parse(&Value::String(Arc::from("soon"))),
+            None
+        );
+        assert_eq!(TimestampFormat::Auto.parse(&Value::Boolean(true)), None);
+
+        // Guesses landing far in the future are rejected, not trusted
+        for value in [
+            Value::Integer(secs * 1_000_000_000),
+            Value::String(Arc::from("9999-01-01T00:00:00Z")),
+            Value::Float(9e10),
+        ] {
+            assert_eq!(TimestampFormat::Auto.parse(&value), None, "{:?}", value);
+        }
+        assert!(TimestampFormat::Rfc3339
+            .parse(&Value::String(Arc::from("9999-01-01T00:00:00Z")))
+            .is_some());
+    }
+}
@@ -27,7 +27,7 @@ pub enum LexState {
     AccumulateBeforeWhitespace,
     /// Processing whitespace
     Whitespace,
-    /// Aggregation state (unsupported)
+    /// Aggregation expression after a pipe
     Aggregation,
 }
 
@@ -87,6 +87,13 @@ impl Lexer {
 
     /// Lex pipe separator
     pub async fn lex_pipe(&mut self) -> Result<Option<LexState>, LexError> {
+        // Emit any identifier directly preceding the pipe, e.g. "selection|"
+        self.backup();
+        if self.position > self.start {
+            let token = check_keyword(self.collected());
+            self.emit(token).await?;
+        }
+        self.next_char();
         self.emit(Token::SepPipe).await?;
         Ok(Some(LexState::Aggregation))
     }
@@ -154,16 +161,80 @@ impl Lexer {
         }
     }
 
-    /// Lex aggregation (currently unsupported)
+    /// Lex aggregation expression following a pipe, e.g. `count(field) by group > 10`
     pub async fn lex_aggregation(&mut self) -> Result<Option<LexState>, LexError> {
-        // Consume all remaining input
-        self.position = self.input.len();
-        self.emit(Token::Unsupported).await?;
+        loop {
+            let ch = match self.next_char() {
+                None => break,
+                Some(c) => c,
+            };
+
+            match ch {
+                c if c.is_whitespace() => self.ignore(),
+                '(' => self.emit(Token::SepLpar).await?,
+                ')' => self.emit(Token::SepRpar).await?,
+                '>' | '<' | '=' | '!' => {
+                    let followed_by_eq = self.remaining().starts_with('=');
+                    if followed_by_eq {
+                        self.next_char();
+                    }
+                    let token = match (ch, followed_by_eq) {
+                        ('>', false) => Token::OpGt,
+                        ('>', true) => Token::OpGte,
+                        ('<', false) => Token::OpLt,
+                        ('<', true) => Token::OpLte,
+                        ('=', _) => Token::OpEq,
+                        ('!', true) => Token::OpNeq,
+                        _ => Token::Unsupported,
+                    };
+                    self.emit(token).await?;
+                    if token == Token::Unsupported {
+                        break;
+                    }
+                }
+                c if is_aggregation_word_char(c) => {
+                    while let Some(next) = self.next_char() {
+                        if !is_aggregation_word_char(next) {
+                            self.backup();
+                            break;
+                        }
+                    }
+                    let token = check_aggregation_word(self.collected());
+                    self.emit(token).await?;
+                }
+                _ => {
+                    self.position = self.input.len();
+                    self.emit(Token::Unsupported).await?;
+                    break;
+                }
+            }
+        }
+
+        self.ignore();
         self.emit(Token::LitEof).await?;
         Ok(None)
     }
 }
 
+/// Characters allowed in aggregation function names, field names and numbers
+fn is_aggregation_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | '-')
+}
+
+/// Classify a word inside an aggregation expression
+fn check_aggregation_word(word: &str) -> Token {
+    if word.parse::<f64>().is_ok() {
+        Token::LitNumber
+    } else if word.eq_ignore_ascii_case("by") {
+        Token::KeywordBy
+    } else {
+        match check_keyword(word) {
+            Token::KeywordAgg => Token::KeywordAgg,
+            _ => Token::Identifier,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -198,4 +269,71 @@ mod tests {
         assert_eq!(item.token, Token::StmtAllOf);
         assert_eq!(item.value, "all of");
     }
+
+    async fn collect_tokens(input: &str) -> Vec<(Token, String)> {
+        let (lexer, mut rx) = create_test_lexer(input);
+        tokio::spawn(async move { lexer.scan().await });
+
+        let mut items = Vec::new();
+        while let Some(item) = rx.recv().await {
+            items.push((item.token, item.value));
+        }
+        items
+    }
+
+    #[tokio::test]
+    async fn test_lex_aggregation_expression() {
+        let items = collect_tokens("selection | count(TargetUserName) by SourceIp >= 10").await;
+        let tokens: Vec<Token> = items.iter().map(|(t, _)| *t).collect();
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Identifier,
+                Token::SepPipe,
+                Token::KeywordAgg,
+                Token::SepLpar,
+                Token::Identifier,
+                Token::SepRpar,
+                Token::KeywordBy,
+                Token::Identifier,
+                Token::OpGte,
+                Token::LitNumber,
+                Token::LitEof,
+            ]
+        );
+        assert_eq!(items[4].1, "TargetUserName");
+        assert_eq!(items[7].1, "SourceIp");
+        assert_eq!(items[9].1, "10");
+    }
+
+    #[tokio::test]
+    async fn test_lex_pipe_without_whitespace() {
+        let items = collect_tokens("selection|count()>5").await;
+        let tokens: Vec<Token> = items.iter().map(|(t, _)| *t).collect();
+
+        assert_eq!(items[0].1, "selection");
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Identifier,
+                Token::SepPipe,
+                Token::KeywordAgg,
+                Token::SepLpar,
+                Token::SepRpar,
+                Token::OpGt,
+                Token::LitNumber,
+                Token::LitEof,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lex_aggregation_unsupported_character() {
+        let items = collect_tokens("selection | count() > 5 & x").await;
+        let tokens: Vec<Token> = items.iter().map(|(t, _)| *t).collect();
+
+        assert_eq!(tokens[tokens.len() - 2], Token::Unsupported);
+        assert_eq!(tokens[tokens.len() - 1], Token::LitEof);
+    }
 }
@@ -59,6 +59,14 @@ pub enum Token {
     StmtOneOf = 19,
     /// "all of" statement
     StmtAllOf = 20,
+
+    // Aggregation expression
+    /// BY keyword for grouping aggregations
+    KeywordBy = 21,
+    /// Numeric literal in aggregation comparisons
+    LitNumber = 22,
+    /// Not equal operator
+    OpNeq = 23,
 }
 
 impl Token {
@@ -75,9 +83,11 @@ impl Token {
             Token::OpGte => ">=",
             Token::OpLt => "<",
             Token::OpLte => "<=",
+            Token::OpNeq => "!=",
             Token::KeywordAnd => "and",
             Token::KeywordOr => "or",
             Token::KeywordNot => "not",
+            Token::KeywordBy => "by",
             Token::StmtAllOf => "all of",
             Token::StmtOneOf => "1 of",
             Token::LitEof | Token::Nil => "",
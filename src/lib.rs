@@ -40,6 +40,7 @@
 //!     brokers: "localhost:9092".to_string(),
 //!     group_id: "sigma-processor".to_string(),
 //!     topics: vec!["security-events".to_string()],
+//!     output_topic: Some("sigma-alerts".to_string()),
 //!     ..Default::default()
 //! };
 //!
@@ -61,7 +62,7 @@
 #![allow(clippy::module_inception)]
 
 // Re-export commonly used items
-pub use ast::{Branch, MatchResult};
+pub use ast::{Branch, Explanation, FieldMatch, MatchResult, SyncBranch};
 pub use error::{Result, SigmaError};
 pub use event::{DynamicEvent, Event, Keyworder, Selector, Value};
 pub use ruleset::{ConcurrentRuleSet, RuleMatch, RuleSet, RuleSetResult};
@@ -92,6 +93,9 @@ pub mod rule;
 /// Pattern matching implementations
 pub mod pattern;
 
+/// Processing pipelines for field-name mapping and value transformation
+pub mod pipeline;
+
 /// AST tree structure
 pub mod tree;
 
@@ -115,6 +119,9 @@ pub mod consumer;
 /// Aggregation support for Sigma rules  
 pub mod aggregation;
 
+/// Correlation of rule matches (Sigma v2 correlation rules)
+pub mod correlation;
+
 /// OpenTelemetry integration for distributed tracing
 pub mod telemetry;
 
@@ -138,12 +145,26 @@ pub struct SigmaEngineBuilder {
     pub rule_dirs: Vec<String>,
     /// Whether to fail on rule parse errors
     pub fail_on_parse_error: bool,
+    /// Whether to reject rules with unknown modifiers or malformed detections
+    pub strict: bool,
     /// Whether to collapse whitespace in patterns
     pub collapse_whitespace: bool,
     /// Number of worker threads
     pub worker_threads: usize,
     /// Redpanda configuration
     pub kafka_config: Option<KafkaConfig>,
+    /// Placeholder definitions for `|expand` fields
+    pub placeholders: Option<std::sync::Arc<parser::PlaceholderRegistry>>,
+    /// Processing pipeline applied to rules before compilation
+    pub pipeline: Option<std::sync::Arc<pipeline::ProcessingPipeline>>,
+    /// Classifier routing events to the rules of their logsource
+    pub classifier: Option<std::sync::Arc<ruleset::LogsourceClassifier>>,
+    /// Sinks receiving alerts from [`SigmaEngine::run`]
+    pub alert_sinks: Vec<std::sync::Arc<dyn consumer::AlertSink>>,
+    /// Extractor taking event time from event fields instead of processing time
+    pub timestamp_extractor: Option<std::sync::Arc<event::TimestampExtractor>>,
+    /// Limits on the state kept by aggregation conditions
+    pub aggregation_config: aggregation::AggregationConfig,
 }
 
 /// Kafka/Redpanda configuration
@@ -163,7 +184,9 @@ pub struct KafkaConfig {
     pub max_retries: Option<u32>,
     /// Dead letter queue topic
     pub dlq_topic: Option<String>,
-    /// Backpressure buffer size
+    /// Topic alerts are published to
+    pub output_topic: Option<String>,
+    /// Maximum messages processed at once before consumption pauses
     pub backpressure_buffer_size: Option<usize>,
     /// Enable detailed metrics
     pub enable_metrics: bool,
@@ -179,6 +202,7 @@ impl Default for KafkaConfig {
             batch_size: Some(1000),
             max_retries: Some(3),
             dlq_topic: None,
+            output_topic: None,
             backpressure_buffer_size: Some(10000),
             enable_metrics: true,
         }
@@ -190,9 +214,16 @@ impl Default for SigmaEngineBuilder {
         Self {
             rule_dirs: vec![],
             fail_on_parse_error: false,
+            strict: false,
             collapse_whitespace: true,
             worker_threads: num_cpus::get(),
             kafka_config: None,
+            placeholders: None,
+            pipeline: None,
+            classifier: None,
+            alert_sinks: Vec::new(),
+            timestamp_extractor: None,
+            aggregation_config: aggregation::AggregationConfig::default(),
         }
     }
 }
@@ -215,6 +246,17 @@ impl SigmaEngineBuilder {
         self
     }
 
+    /// Set whether to load rules strictly
+    ///
+    /// Strict loading fails a rule that uses an unknown modifier, defines a
+    /// search identifier the condition never uses, references a missing
+    /// identifier, or has an empty value list. Combine with
+    /// [`fail_on_parse_error`](Self::fail_on_parse_error) to abort loading.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
     /// Set whether to collapse whitespace
     pub fn collapse_whitespace(mut self, collapse: bool) -> Self {
         self.collapse_whitespace = collapse;
@@ -233,6 +275,57 @@ impl SigmaEngineBuilder {
         self
     }
 
+    /// Resolve `|expand` placeholders in loaded rules from a registry
+    pub fn with_placeholders(mut self, placeholders: parser::PlaceholderRegistry) -> Self {
+        self.placeholders = Some(std::sync::Arc::new(placeholders));
+        self
+    }
+
+    /// Transform loaded rules with a processing pipeline before compiling them
+    ///
+    /// Use [`ProcessingPipeline::chain`](pipeline::ProcessingPipeline::chain)
+    /// to combine several pipelines.
+    pub fn with_pipeline(mut self, pipeline: pipeline::ProcessingPipeline) -> Self {
+        self.pipeline = Some(std::sync::Arc::new(pipeline));
+        self
+    }
+
+    /// Route events to the rules of their logsource using a classifier
+    ///
+    /// Events the classifier cannot place are evaluated against every rule.
+    pub fn with_logsource_classifier(mut self, classifier: ruleset::LogsourceClassifier) -> Self {
+        self.classifier = Some(std::sync::Arc::new(classifier));
+        self
+    }
+
+    /// Deliver alerts from [`SigmaEngine::run`] to a sink
+    ///
+    /// Alerts also go to `output_topic` when the Kafka configuration sets one.
+    pub fn with_alert_sink(mut self, sink: impl consumer::AlertSink) -> Self {
+        self.alert_sinks.push(std::sync::Arc::new(sink));
+        self
+    }
+
+    /// Take event time from event fields using an extractor
+    ///
+    /// Aggregation and correlation windows then follow the time events
+    /// happened rather than the time they were processed, so replayed or
+    /// delayed logs are counted correctly. Events without a parseable
+    /// timestamp keep processing time.
+    pub fn with_timestamp_extractor(mut self, extractor: event::TimestampExtractor) -> Self {
+        self.timestamp_extractor = Some(std::sync::Arc::new(extractor));
+        self
+    }
+
+    /// Set the limits on the state kept by aggregation conditions
+    ///
+    /// Raise the per-group limits for rules whose thresholds exceed them,
+    /// such as `count() > 10000`.
+    pub fn with_aggregation_config(mut self, config: aggregation::AggregationConfig) -> Self {
+        self.aggregation_config = config;
+        self
+    }
+
     /// Build the Sigma engine
     pub async fn build(self) -> Result<SigmaEngine> {
         SigmaEngine::new(self).await
@@ -248,9 +341,15 @@ mod tests {
         let builder = SigmaEngineBuilder::new();
         assert_eq!(builder.rule_dirs.len(), 0);
         assert!(!builder.fail_on_parse_error);
+        assert!(!builder.strict);
         assert!(builder.collapse_whitespace);
         assert_eq!(builder.worker_threads, num_cpus::get());
         assert!(builder.kafka_config.is_none());
+        assert!(builder.placeholders.is_none());
+        assert!(builder.pipeline.is_none());
+        assert!(builder.classifier.is_none());
+        assert!(builder.alert_sinks.is_empty());
+        assert!(builder.timestamp_extractor.is_none());
     }
 
     #[test]
@@ -263,6 +362,7 @@ mod tests {
             batch_size: Some(100),
             max_retries: Some(3),
             dlq_topic: Some("dlq-events".to_string()),
+            output_topic: Some("sigma-alerts".to_string()),
             backpressure_buffer_size: Some(1000),
             enable_metrics: true,
         };
@@ -270,12 +370,14 @@ mod tests {
         let builder = SigmaEngineBuilder::new()
             .add_rule_dir("/path/to/rules")
             .fail_on_parse_error(true)
+            .strict(true)
             .collapse_whitespace(false)
             .worker_threads(4)
             .with_kafka(kafka_config);
 
         assert_eq!(builder.rule_dirs, vec!["/path/to/rules"]);
         assert!(builder.fail_on_parse_error);
+        assert!(builder.strict);
         assert!(!builder.collapse_whitespace);
         assert_eq!(builder.worker_threads, 4);
         assert!(builder.kafka_config.is_some());
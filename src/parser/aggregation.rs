@@ -0,0 +1,238 @@
+//! Parsing of aggregation expressions following the condition pipe
+//!
+//! Sigma aggregations have the form
+//! `agg-function(agg-field) [by group-field] comparison-op value`,
+//! e.g. `count() by SourceIp > 10`. The optional `timeframe` from the
+//! detection section bounds the window the aggregation is computed over.
+
+use crate::aggregation::AggregationFunction;
+use crate::ast::nodes::{ComparisonOp, NodeAggregation};
+use crate::lexer::token::{Item, Token};
+use crate::parser::ParseError;
+use std::time::Duration;
+
+/// Build an aggregation node from the tokens following the pipe separator
+pub fn parse_aggregation(
+    tokens: &[Item],
+    timeframe: Option<&str>,
+) -> Result<NodeAggregation, ParseError> {
+    let expression = tokens
+        .iter()
+        .map(|t| t.value.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let invalid = |reason: &str| ParseError::invalid_aggregation(&expression, reason);
+
+    let mut iter = tokens.iter().peekable();
+
+    let function_name = match iter.next() {
+        Some(item) if item.token == Token::KeywordAgg => item.value.to_lowercase(),
+        _ => return Err(invalid("expected aggregation function")),
+    };
+
+    if !matches!(iter.next(), Some(item) if item.token == Token::SepLpar) {
+        return Err(invalid("expected '(' after aggregation function"));
+    }
+
+    let agg_field = match iter.peek() {
+        Some(item) if item.token == Token::Identifier => iter.next().map(|i| i.value.clone()),
+        _ => None,
+    };
+
+    if !matches!(iter.next(), Some(item) if item.token == Token::SepRpar) {
+        return Err(invalid("expected ')' after aggregation field"));
+    }
+
+    let function = match (function_name.as_str(), agg_field) {
+        ("count", None) => AggregationFunction::Count,
+        ("count", Some(field)) => AggregationFunction::CountDistinct(field),
+        ("sum", Some(field)) => AggregationFunction::Sum(field),
+        ("avg", Some(field)) => AggregationFunction::Average(field),
+        ("min", Some(field)) => AggregationFunction::Min(field),
+        ("max", Some(field)) => AggregationFunction::Max(field),
+        (name, None) => {
+            return Err(invalid(&format!("{}() requires a field argument", name)));
+        }
+        (name, Some(_)) => {
+            return Err(invalid(&format!("unknown aggregation function '{}'", name)));
+        }
+    };
+
+    let by_field = match iter.peek() {
+        Some(item) if item.token == Token::KeywordBy => {
+            iter.next();
+            match iter.next() {
+                Some(item) if item.token == Token::Identifier => Some(item.value.clone()),
+                _ => return Err(invalid("expected field name after 'by'")),
+            }
+        }
+        _ => None,
+    };
+
+    let comparison = match iter.next().map(|item| item.token) {
+        Some(Token::OpGt) => ComparisonOp::GreaterThan,
+        Some(Token::OpGte) => ComparisonOp::GreaterOrEqual,
+        Some(Token::OpLt) => ComparisonOp::LessThan,
+        Some(Token::OpLte) => ComparisonOp::LessOrEqual,
+        Some(Token::OpEq) => ComparisonOp::Equal,
+        Some(Token::OpNeq) => ComparisonOp::NotEqual,
+        _ => return Err(invalid("expected comparison operator")),
+    };
+
+    let threshold = match iter.next() {
+        Some(item) if item.token == Token::LitNumber => item
+            .value
+            .parse::<f64>()
+            .map_err(|_| invalid("threshold is not a number"))?,
+        _ => return Err(invalid("expected numeric threshold")),
+    };
+
+    if iter.next().is_some() {
+        return Err(invalid("unexpected tokens after threshold"));
+    }
+
+    let time_window = timeframe
+        .map(|tf| {
+            parse_timeframe(tf).ok_or_else(|| invalid(&format!("invalid timeframe '{}'", tf)))
+        })
+        .transpose()?;
+
+    Ok(NodeAggregation::new(
+        function,
+        comparison,
+        threshold,
+        by_field,
+        time_window,
+    ))
+}
+
+/// Parse a Sigma timeframe such as `30s`, `5m`, `12h`, `7d` or `1M`
+pub fn parse_timeframe(timeframe: &str) -> Option<Duration> {
+    let timeframe = timeframe.trim();
+    let unit = timeframe.chars().last()?;
+    let amount: u64 = timeframe[..timeframe.len() - unit.len_utf8()]
+        .trim()
+        .parse()
+        .ok()?;
+
+    let seconds = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        'M' => 30 * 24 * 60 * 60,
+        _ => return None,
+    };
+
+    amount.checked_mul(seconds).map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(tokens: &[(Token, &str)]) -> Vec<Item> {
+        tokens
+            .iter()
+            .map(|(t, v)| Item::new(*t, v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_count_by_field() {
+        let tokens = items(&[
+            (Token::KeywordAgg, "count"),
+            (Token::SepLpar, "("),
+            (Token::SepRpar, ")"),
+            (Token::KeywordBy, "by"),
+            (Token::Identifier, "SourceIp"),
+            (Token::OpGt, ">"),
+            (Token::LitNumber, "10"),
+        ]);
+
+        let node = parse_aggregation(&tokens, Some("5m")).unwrap();
+        assert_eq!(node.function, AggregationFunction::Count);
+        assert_eq!(node.comparison, ComparisonOp::GreaterThan);
+        assert_eq!(node.threshold, 10.0);
+        assert_eq!(node.by_field.as_deref(), Some("SourceIp"));
+        assert_eq!(node.time_window, Some(Duration::from_secs(300)));
+    }
+
+    #[test]
+    fn test_parse_field_functions() {
+        let tokens = items(&[
+            (Token::KeywordAgg, "count"),
+            (Token::SepLpar, "("),
+            (Token::Identifier, "TargetUserName"),
+            (Token::SepRpar, ")"),
+            (Token::OpGte, ">="),
+            (Token::LitNumber, "3"),
+        ]);
+        let node = parse_aggregation(&tokens, None).unwrap();
+        assert_eq!(
+            node.function,
+            AggregationFunction::CountDistinct("TargetUserName".to_string())
+        );
+        assert!(node.by_field.is_none());
+        assert!(node.time_window.is_none());
+
+        let tokens = items(&[
+            (Token::KeywordAgg, "avg"),
+            (Token::SepLpar, "("),
+            (Token::Identifier, "bytes"),
+            (Token::SepRpar, ")"),
+            (Token::OpLt, "<"),
+            (Token::LitNumber, "1.5"),
+        ]);
+        let node = parse_aggregation(&tokens, None).unwrap();
+        assert_eq!(node.function, AggregationFunction::Average("bytes".into()));
+        assert_eq!(node.threshold, 1.5);
+    }
+
+    #[test]
+    fn test_parse_invalid_aggregations() {
+        // sum() needs a field
+        let tokens = items(&[
+            (Token::KeywordAgg, "sum"),
+            (Token::SepLpar, "("),
+            (Token::SepRpar, ")"),
+            (Token::OpGt, ">"),
+            (Token::LitNumber, "1"),
+        ]);
+        assert!(matches!(
+            parse_aggregation(&tokens, None),
+            Err(ParseError::InvalidAggregation { .. })
+        ));
+
+        // Missing threshold
+        let tokens = items(&[
+            (Token::KeywordAgg, "count"),
+            (Token::SepLpar, "("),
+            (Token::SepRpar, ")"),
+            (Token::OpGt, ">"),
+        ]);
+        assert!(parse_aggregation(&tokens, None).is_err());
+
+        // Bad timeframe
+        let tokens = items(&[
+            (Token::KeywordAgg, "count"),
+            (Token::SepLpar, "("),
+            (Token::SepRpar, ")"),
+            (Token::OpGt, ">"),
+            (Token::LitNumber, "1"),
+        ]);
+        assert!(parse_aggregation(&tokens, Some("5x")).is_err());
+    }
+
+    #[test]
+    fn test_parse_timeframe() {
+        assert_eq!(parse_timeframe("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_timeframe("5m"), Some(Duration::from_secs(300)));
+        assert_eq!(parse_timeframe("12h"), Some(Duration::from_secs(43_200)));
+        assert_eq!(parse_timeframe("7d"), Some(Duration::from_secs(604_800)));
+        assert_eq!(parse_timeframe("1M"), Some(Duration::from_secs(2_592_000)));
+        assert_eq!(parse_timeframe("m"), None);
+        assert_eq!(parse_timeframe(""), None);
+        assert_eq!(parse_timeframe("10y"), None);
+    }
+}
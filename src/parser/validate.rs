@@ -1,4 +1,7 @@
-use crate::lexer::token::Token;
+use crate::lexer::token::{Item, Token};
+use crate::rule::Detection;
+use serde_json::Value;
+use thiserror::Error;
 
 /// Validates that two tokens can appear in sequence
 pub fn valid_token_sequence(t1: Token, t2: Token) -> bool {
@@ -22,6 +25,7 @@ pub fn valid_token_sequence(t1: Token, t2: Token) -> bool {
         },
         Token::IdentifierAll => matches!(t1, Token::StmtAllOf | Token::StmtOneOf),
         Token::Identifier | Token::IdentifierWithWildcard => match t1 {
+            Token::KeywordBy => t2 == Token::Identifier,
             Token::SepLpar
             | Token::KeywordAnd
             | Token::KeywordOr
@@ -42,7 +46,11 @@ pub fn valid_token_sequence(t1: Token, t2: Token) -> bool {
             _ => is_begin_token(t1),
         },
         Token::SepLpar => match t1 {
-            Token::KeywordAnd | Token::KeywordOr | Token::KeywordNot | Token::SepLpar => true,
+            Token::KeywordAnd
+            | Token::KeywordOr
+            | Token::KeywordNot
+            | Token::SepLpar
+            | Token::KeywordAgg => true,
             _ => is_begin_token(t1),
         },
         Token::SepRpar => matches!(
@@ -59,6 +67,7 @@ pub fn valid_token_sequence(t1: Token, t2: Token) -> bool {
                 | Token::IdentifierAll
                 | Token::IdentifierWithWildcard
                 | Token::SepRpar
+                | Token::LitNumber
         ),
         Token::SepPipe => matches!(
             t1,
@@ -67,6 +76,16 @@ pub fn valid_token_sequence(t1: Token, t2: Token) -> bool {
                 | Token::IdentifierWithWildcard
                 | Token::SepRpar
         ),
+        // Aggregation expression: `| agg(field) [by group] op number`
+        Token::KeywordAgg => t1 == Token::SepPipe,
+        Token::KeywordBy => t1 == Token::SepRpar,
+        Token::OpEq | Token::OpNeq | Token::OpGt | Token::OpGte | Token::OpLt | Token::OpLte => {
+            matches!(t1, Token::SepRpar | Token::Identifier)
+        }
+        Token::LitNumber => matches!(
+            t1,
+            Token::OpEq | Token::OpNeq | Token::OpGt | Token::OpGte | Token::OpLt | Token::OpLte
+        ),
         _ => false,
     }
 }
@@ -78,6 +97,138 @@ fn is_begin_token(_token: Token) -> bool {
     false // This will be checked in the parser
 }
 
+/// Rule defect rejected when rules are loaded in strict mode
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum StrictViolation {
+    /// A field key carries a modifier the engine does not know, e.g. `|contians`
+    #[error("unknown modifier '{modifier}' on field '{field}' in '{identifier}'")]
+    UnknownModifier {
+        /// Search identifier holding the field
+        identifier: String,
+        /// Field key including its modifiers
+        field: String,
+        /// The unrecognised modifier
+        modifier: String,
+    },
+    /// The condition names a search identifier, or a wildcard matching none, that is not defined
+    #[error("condition references missing search identifier '{identifier}'")]
+    MissingIdentifier {
+        /// Identifier or wildcard pattern from the condition
+        identifier: String,
+    },
+    /// A search identifier is defined but never used by the condition
+    #[error("search identifier '{identifier}' is not referenced by the condition")]
+    UnreferencedIdentifier {
+        /// The unused identifier
+        identifier: String,
+    },
+    /// A field, or a keyword identifier, has an empty list of values
+    #[error("empty value list for field '{field}' in '{identifier}'")]
+    EmptyValueList {
+        /// Search identifier holding the list
+        identifier: String,
+        /// Field key, or the identifier itself for keyword lists
+        field: String,
+    },
+}
+
+/// Check a detection against the strict loading rules
+///
+/// `condition` holds the collected condition tokens; anything after an
+/// aggregation pipe names event fields rather than search identifiers and is
+/// ignored. Identifiers are checked in sorted order so the reported violation
+/// is stable.
+pub fn validate_strict(detection: &Detection, condition: &[Item]) -> Result<(), StrictViolation> {
+    let mut identifiers: Vec<&String> = detection.iter().map(|(name, _)| name).collect();
+    identifiers.sort();
+
+    let condition = match condition.iter().position(|t| t.token == Token::SepPipe) {
+        Some(pipe) => &condition[..pipe],
+        None => condition,
+    };
+
+    let mut referenced = vec![false; identifiers.len()];
+    for item in condition {
+        match item.token {
+            Token::Identifier => match identifiers.iter().position(|name| **name == item.value) {
+                Some(index) => referenced[index] = true,
+                None => {
+                    return Err(StrictViolation::MissingIdentifier {
+                        identifier: item.value.clone(),
+                    })
+                }
+            },
+            Token::IdentifierWithWildcard => {
+                let pattern = glob::Pattern::new(&item.value).ok();
+                let mut found = false;
+                for (index, name) in identifiers.iter().enumerate() {
+                    if pattern.as_ref().is_some_and(|p| p.matches(name)) {
+                        referenced[index] = true;
+                        found = true;
+                    }
+                }
+                if !found {
+                    return Err(StrictViolation::MissingIdentifier {
+                        identifier: item.value.clone(),
+                    });
+                }
+            }
+            Token::IdentifierAll => referenced.iter_mut().for_each(|r| *r = true),
+            _ => {}
+        }
+    }
+
+    for name in &identifiers {
+        if let Some(value) = detection.get(name) {
+            validate_identifier(name, value)?;
+        }
+    }
+
+    match identifiers
+        .iter()
+        .zip(&referenced)
+        .find(|(_, used)| !**used)
+    {
+        Some((name, _)) => Err(StrictViolation::UnreferencedIdentifier {
+            identifier: name.to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Check the field keys and value lists of a single search identifier
+fn validate_identifier(identifier: &str, value: &Value) -> Result<(), StrictViolation> {
+    let empty = |field: &str| StrictViolation::EmptyValueList {
+        identifier: identifier.to_string(),
+        field: field.to_string(),
+    };
+
+    let selections = match value {
+        Value::Array(items) if items.is_empty() => return Err(empty(identifier)),
+        Value::Array(items) => items.iter().filter_map(Value::as_object).collect(),
+        Value::Object(selection) => vec![selection],
+        _ => Vec::new(),
+    };
+
+    for selection in selections {
+        for (field, value) in selection {
+            let (_, modifiers) = super::parse_field_modifier(field);
+            if let Some(modifier) = modifiers.unknown.first() {
+                return Err(StrictViolation::UnknownModifier {
+                    identifier: identifier.to_string(),
+                    field: field.clone(),
+                    modifier: modifier.clone(),
+                });
+            }
+            if matches!(value, Value::Array(values) if values.is_empty()) {
+                return Err(empty(field));
+            }
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -101,6 +252,15 @@ mod tests {
         // Test parentheses
         assert!(valid_token_sequence(Token::SepLpar, Token::Identifier));
         assert!(valid_token_sequence(Token::Identifier, Token::SepRpar));
+
+        // Test aggregation sequences
+        assert!(valid_token_sequence(Token::SepPipe, Token::KeywordAgg));
+        assert!(valid_token_sequence(Token::KeywordAgg, Token::SepLpar));
+        assert!(valid_token_sequence(Token::SepRpar, Token::KeywordBy));
+        assert!(valid_token_sequence(Token::KeywordBy, Token::Identifier));
+        assert!(valid_token_sequence(Token::Identifier, Token::OpGt));
+        assert!(valid_token_sequence(Token::OpGt, Token::LitNumber));
+        assert!(valid_token_sequence(Token::LitNumber, Token::LitEof));
     }
 
     #[test]
@@ -110,5 +270,11 @@ mod tests {
         assert!(!valid_token_sequence(Token::KeywordAnd, Token::KeywordAnd));
         assert!(!valid_token_sequence(Token::KeywordNot, Token::KeywordAnd));
         assert!(!valid_token_sequence(Token::SepRpar, Token::SepLpar));
+        assert!(!valid_token_sequence(Token::Identifier, Token::KeywordAgg));
+        assert!(!valid_token_sequence(
+            Token::KeywordBy,
+            Token::IdentifierWithWildcard
+        ));
+        assert!(!valid_token_sequence(Token::OpGt, Token::Identifier));
     }
 }
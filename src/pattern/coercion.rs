@@ -1,8 +1,11 @@
 //! Type coercion for Sigma pattern matching
 //!
 //! Implements type coercion for numeric values and string conversions
-//! to match the behavior of the Go implementation.
+//! to match the behavior of the Go implementation, plus timestamp parsing
+//! for the time-part modifiers.
 
+use crate::event::{TimestampFormat, Value as EventValue};
+use chrono::{DateTime, Datelike, Timelike, Utc};
 use serde_json::Value;
 use std::borrow::Cow;
 
@@ -134,6 +137,41 @@ pub fn coerce_for_numeric_match(value: &Value) -> Option<i64> {
     value.to_int_match()
 }
 
+/// Numeric value of an event field, keeping integers exact
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumericValue {
+    /// Integer value, or a numeric string holding one
+    Integer(i64),
+    /// Floating point value, or a numeric string holding one
+    Float(f64),
+}
+
+/// Coerce a value for numeric comparison
+///
+/// Unlike [`coerce_for_numeric_match`], fractional values are preserved so
+/// that comparison modifiers such as `gt` see the full value.
+pub fn coerce_for_number(value: &Value) -> Option<NumericValue> {
+    match value {
+        Value::Number(n) => n
+            .as_i64()
+            .map(NumericValue::Integer)
+            .or_else(|| n.as_f64().map(NumericValue::Float)),
+        Value::String(s) => {
+            let s = s.trim();
+            s.parse::<i64>()
+                .map(NumericValue::Integer)
+                .ok()
+                .or_else(|| {
+                    s.parse::<f64>()
+                        .ok()
+                        .filter(|f| f.is_finite())
+                        .map(NumericValue::Float)
+                })
+        }
+        _ => None,
+    }
+}
+
 /// Check if a value can be coerced to a number
 pub fn can_coerce_to_number(value: &Value) -> bool {
     matches!(value, Value::Number(_))
@@ -143,6 +181,69 @@ pub fn can_coerce_to_number(value: &Value) -> bool {
         })
 }
 
+/// Coerce a value to a UTC timestamp
+///
+/// Parses with [`TimestampFormat::Auto`], the same rules event time
+/// extraction uses by default: RFC 3339 / ISO 8601 strings (UTC when no
+/// offset is given), Windows `SystemTime` strings such as
+/// `2024-01-02 03:04:05.1234567` or `1/2/2024 3:04:05 AM`, and numbers or
+/// numeric strings holding Unix epoch seconds or milliseconds or FILETIME ticks.
+pub fn coerce_for_timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    let format = TimestampFormat::Auto;
+    match value {
+        Value::String(s) => format.parse_str(s),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => format.parse(&EventValue::Integer(i)),
+            None => format.parse(&EventValue::Float(n.as_f64()?)),
+        },
+        _ => None,
+    }
+}
+
+/// Timestamp component extracted by a time-part modifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimePart {
+    /// Minute of the hour (0-59)
+    Minute,
+    /// Hour of the day (0-23)
+    Hour,
+    /// Day of the month (1-31)
+    Day,
+    /// ISO 8601 week of the year (1-53)
+    Week,
+    /// Month of the year (1-12)
+    Month,
+    /// Calendar year
+    Year,
+}
+
+impl TimePart {
+    /// Map a Sigma field modifier name to a time part
+    pub fn from_modifier(modifier: &str) -> Option<Self> {
+        match modifier {
+            "minute" => Some(Self::Minute),
+            "hour" => Some(Self::Hour),
+            "day" => Some(Self::Day),
+            "week" => Some(Self::Week),
+            "month" => Some(Self::Month),
+            "year" => Some(Self::Year),
+            _ => None,
+        }
+    }
+
+    /// Extract this component from a timestamp
+    pub fn extract(self, timestamp: &DateTime<Utc>) -> i64 {
+        match self {
+            Self::Minute => timestamp.minute() as i64,
+            Self::Hour => timestamp.hour() as i64,
+            Self::Day => timestamp.day() as i64,
+            Self::Week => timestamp.iso_week().week() as i64,
+            Self::Month => timestamp.month() as i64,
+            Self::Year => timestamp.year() as i64,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -166,6 +267,33 @@ mod tests {
         assert_eq!(coerce_for_numeric_match(&json!(true)), None);
     }
 
+    #[test]
+    fn test_coerce_for_number() {
+        assert_eq!(
+            coerce_for_number(&json!(42)),
+            Some(NumericValue::Integer(42))
+        );
+        assert_eq!(
+            coerce_for_number(&json!(1.5)),
+            Some(NumericValue::Float(1.5))
+        );
+        assert_eq!(
+            coerce_for_number(&json!(" 443 ")),
+            Some(NumericValue::Integer(443))
+        );
+        assert_eq!(
+            coerce_for_number(&json!("2.25")),
+            Some(NumericValue::Float(2.25))
+        );
+        assert_eq!(
+            coerce_for_number(&json!(u64::MAX)),
+            Some(NumericValue::Float(u64::MAX as f64))
+        );
+        assert_eq!(coerce_for_number(&json!("inf")), None);
+        assert_eq!(coerce_for_number(&json!("abc")), None);
+        assert_eq!(coerce_for_number(&json!(true)), None);
+    }
+
     #[test]
     fn test_can_coerce_to_number() {
         assert!(can_coerce_to_number(&json!(123)));
@@ -226,4 +354,57 @@ mod tests {
         assert_eq!(coerce_for_numeric_match(&json!(f64::NEG_INFINITY)), None);
         assert_eq!(coerce_for_numeric_match(&json!(f64::NAN)), None);
     }
+
+    #[test]
+    fn test_coerce_for_timestamp() {
+        let expected = DateTime::parse_from_rfc3339("2024-03-05T04:07:09Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        for value in [
+            json!("2024-03-05T04:07:09Z"),
+            json!("2024-03-05T06:07:09+02:00"),
+            json!("2024-03-05T04:07:09"),
+            json!("2024-03-05 04:07:09"),
+            json!("3/5/2024 4:07:09 AM"),
+            json!(" 03/05/2024 04:07:09 "),
+            json!(1709611629),
+            json!("1709611629"),
+            json!(1709611629000i64),
+        ] {
+            assert_eq!(coerce_for_timestamp(&value), Some(expected), "{}", value);
+        }
+
+        // Windows SystemTime with 100ns precision
+        let precise = coerce_for_timestamp(&json!("2024-03-05T04:07:09.1234567Z")).unwrap();
+        assert_eq!(precise.nanosecond(), 123_456_700);
+        let precise = coerce_for_timestamp(&json!("2024-03-05 04:07:09.1234567")).unwrap();
+        assert_eq!(precise.nanosecond(), 123_456_700);
+        let fractional = coerce_for_timestamp(&json!(1709611629.5)).unwrap();
+        assert_eq!(fractional.timestamp_millis(), 1709611629500);
+        // FILETIME ticks, as in an exported TimeCreated
+        let filetime = (1709611629 + 11_644_473_600i64) * 10_000_000;
+        assert_eq!(coerce_for_timestamp(&json!(filetime)), Some(expected));
+
+        assert_eq!(coerce_for_timestamp(&json!("yesterday")), None);
+        assert_eq!(coerce_for_timestamp(&json!("2024-13-01T00:00:00Z")), None);
+        assert_eq!(coerce_for_timestamp(&json!(true)), None);
+        assert_eq!(coerce_for_timestamp(&json!(null)), None);
+    }
+
+    #[test]
+    fn test_time_part_extract() {
+        let timestamp = coerce_for_timestamp(&json!("2024-12-30T23:59:01Z")).unwrap();
+
+        assert_eq!(TimePart::Minute.extract(&timestamp), 59);
+        assert_eq!(TimePart::Hour.extract(&timestamp), 23);
+        assert_eq!(TimePart::Day.extract(&timestamp), 30);
+        // ISO week 1 of 2025 starts on Monday 2024-12-30
+        assert_eq!(TimePart::Week.extract(&timestamp), 1);
+        assert_eq!(TimePart::Month.extract(&timestamp), 12);
+        assert_eq!(TimePart::Year.extract(&timestamp), 2024);
+
+        assert_eq!(TimePart::from_modifier("hour"), Some(TimePart::Hour));
+        assert_eq!(TimePart::from_modifier("hours"), None);
+    }
 }
@@ -2,9 +2,10 @@
 
 use crate::pattern::{
     escape::{escape_sigma_for_glob, escape_sigma_for_glob_cow},
-    intern::intern_pattern,
-    num_matcher::{NumMatchers, NumPattern},
-    security::safe_regex_compile,
+    intern::{intern_pattern, intern_regex},
+    ip_matcher::IpNetworkPattern,
+    num_matcher::{NumComparePattern, NumComparison, NumMatchers, NumMatchersConj, NumPattern},
+    security::RegexFlags,
     string_matcher::{
         ContentPattern, GlobPatternMatcher, PrefixPattern, RegexPattern, StringMatchers,
         StringMatchersConj, SuffixPattern,
@@ -25,7 +26,30 @@ fn create_contains_pattern(escaped: Cow<'_, str>) -> String {
     result
 }
 
+/// Compile a glob matcher, folding the pattern for case-insensitive matching
+fn new_glob_matcher(
+    glob_pattern: &str,
+    lowercase: bool,
+    no_collapse_ws: bool,
+) -> Result<Box<dyn StringMatcher>, String> {
+    let glob_pattern = if lowercase {
+        Cow::Owned(glob_pattern.to_lowercase())
+    } else {
+        Cow::Borrowed(glob_pattern)
+    };
+    let glob =
+        GlobPattern::new(&glob_pattern).map_err(|e| format!("Invalid glob pattern: {}", e))?;
+    Ok(Box::new(GlobPatternMatcher {
+        glob,
+        lowercase,
+        no_collapse_ws,
+    }))
+}
+
 /// Create a new string matcher based on patterns and modifiers
+///
+/// With `lowercase` set, plain, contains, prefix, suffix and glob patterns
+/// match case-insensitively. Regular expressions are unaffected.
 pub fn new_string_matcher(
     modifier: TextPatternModifier,
     lowercase: bool,
@@ -43,20 +67,16 @@ pub fn new_string_matcher(
     for pattern in patterns {
         let matcher: Box<dyn StringMatcher> = match modifier {
             TextPatternModifier::Regex => {
-                let re = safe_regex_compile(&pattern)
+                let re = intern_regex(&pattern, RegexFlags::default())
                     .map_err(|e| format!("Unsafe regex pattern: {}", e))?;
                 Box::new(RegexPattern { regex: re })
             }
             TextPatternModifier::Contains => {
                 let escaped = escape_sigma_for_glob_cow(&pattern);
                 let glob_pattern = create_contains_pattern(escaped);
-                let glob = GlobPattern::new(&glob_pattern)
-                    .map_err(|e| format!("Invalid glob pattern: {}", e))?;
-                Box::new(GlobPatternMatcher {
-                    glob,
-                    no_collapse_ws,
-                })
+                new_glob_matcher(&glob_pattern, lowercase, no_collapse_ws)?
             }
+            TextPatternModifier::Cidr => Box::new(IpNetworkPattern::new(&pattern)?),
             TextPatternModifier::Suffix => Box::new(SuffixPattern {
                 token: intern_pattern(&pattern),
                 lowercase,
@@ -72,7 +92,7 @@ pub fn new_string_matcher(
                 if pattern.starts_with('/') && pattern.ends_with('/') && pattern.len() > 2 {
                     // Regex pattern in /pattern/ format
                     let regex_str = &pattern[1..pattern.len() - 1];
-                    let re = safe_regex_compile(regex_str)
+                    let re = intern_regex(regex_str, RegexFlags::default())
                         .map_err(|e| format!("Unsafe regex pattern: {}", e))?;
                     Box::new(RegexPattern { regex: re })
                 } else if modifier == TextPatternModifier::Keyword || pattern.contains('*') {
@@ -83,12 +103,7 @@ pub fn new_string_matcher(
                     } else {
                         escape_sigma_for_glob(&pattern)
                     };
-                    let glob = GlobPattern::new(&glob_pattern)
-                        .map_err(|e| format!("Invalid glob pattern: {}", e))?;
-                    Box::new(GlobPatternMatcher {
-                        glob,
-                        no_collapse_ws,
-                    })
+                    new_glob_matcher(&glob_pattern, lowercase, no_collapse_ws)?
                 } else {
                     // Default to content pattern
                     Box::new(ContentPattern {
@@ -119,6 +134,60 @@ pub fn new_string_matcher(
     }
 }
 
+/// Create a regex matcher with Sigma regex flags
+///
+/// Compiled regexes are shared across rules through the global regex cache.
+pub fn new_regex_matcher(
+    flags: RegexFlags,
+    all: bool,
+    patterns: Vec<String>,
+) -> Result<Box<dyn StringMatcher>, String> {
+    if patterns.is_empty() {
+        return Err("No patterns defined for matcher object".to_string());
+    }
+
+    let mut matchers = patterns
+        .iter()
+        .map(|pattern| {
+            intern_regex(pattern, flags)
+                .map(|regex| Box::new(RegexPattern { regex }) as Box<dyn StringMatcher>)
+                .map_err(|e| format!("Unsafe regex pattern: {}", e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match matchers.len() {
+        1 => matchers
+            .pop()
+            .ok_or_else(|| "Internal error: Vec with length 1 has no element".to_string()),
+        _ if all => Ok(Box::new(StringMatchersConj::new(matchers).optimize())),
+        _ => Ok(Box::new(StringMatchers::new(matchers).optimize())),
+    }
+}
+
+/// Create a string matcher for values that each expand into alternative patterns
+///
+/// Encoding modifiers turn one rule value into several variants. A value matches
+/// when any of its variants does; with `all` every value must match.
+pub fn new_expanded_string_matcher(
+    modifier: TextPatternModifier,
+    lowercase: bool,
+    all: bool,
+    no_collapse_ws: bool,
+    values: Vec<Vec<String>>,
+) -> Result<Box<dyn StringMatcher>, String> {
+    if !all || values.iter().all(|variants| variants.len() == 1) {
+        let patterns = values.into_iter().flatten().collect();
+        return new_string_matcher(modifier, lowercase, all, no_collapse_ws, patterns);
+    }
+
+    let matchers = values
+        .into_iter()
+        .map(|variants| new_string_matcher(modifier, lowercase, false, no_collapse_ws, variants))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Box::new(StringMatchersConj::new(matchers).optimize()))
+}
+
 /// Create a new numeric matcher from a list of values
 pub fn new_num_matcher(values: Vec<i64>) -> Result<Box<dyn NumMatcher>, String> {
     if values.is_empty() {
@@ -141,6 +210,35 @@ pub fn new_num_matcher(values: Vec<i64>) -> Result<Box<dyn NumMatcher>, String>
     }
 }
 
+/// Create a numeric matcher comparing values against a list of bounds
+///
+/// A value matches when it satisfies any bound, or every bound when `all` is set.
+pub fn new_num_comparison_matcher(
+    comparison: NumComparison,
+    all: bool,
+    bounds: Vec<f64>,
+) -> Result<Box<dyn NumMatcher>, String> {
+    if bounds.is_empty() {
+        return Err("No patterns defined for matcher object".to_string());
+    }
+    if let Some(bound) = bounds.iter().find(|b| !b.is_finite()) {
+        return Err(format!("Invalid numeric bound: {}", bound));
+    }
+
+    let mut matchers: Vec<Box<dyn NumMatcher>> = bounds
+        .into_iter()
+        .map(|bound| Box::new(NumComparePattern { comparison, bound }) as Box<dyn NumMatcher>)
+        .collect();
+
+    match matchers.len() {
+        1 => matchers
+            .pop()
+            .ok_or_else(|| "Internal error: Vec with length 1 has no element".to_string()),
+        _ if all => Ok(Box::new(NumMatchersConj::new(matchers))),
+        _ => Ok(Box::new(NumMatchers::new(matchers))),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -262,6 +360,73 @@ mod tests {
         assert!(!matcher.string_match("neither"));
     }
 
+    #[test]
+    fn test_new_expanded_string_matcher_all() {
+        // Each value has two alternative encodings; all values must be present
+        let matcher = new_expanded_string_matcher(
+            TextPatternModifier::Contains,
+            false,
+            true,
+            false,
+            vec![
+                vec!["aaa".to_string(), "AAA".to_string()],
+                vec!["bbb".to_string(), "BBB".to_string()],
+            ],
+        )
+        .unwrap();
+
+        assert!(matcher.string_match("xx aaa yy BBB"));
+        assert!(matcher.string_match("AAA bbb"));
+        assert!(!matcher.string_match("aaa AAA"));
+        assert!(!matcher.string_match("bbb"));
+    }
+
+    #[test]
+    fn test_new_string_matcher_cidr() {
+        let matcher = new_string_matcher(
+            TextPatternModifier::Cidr,
+            false,
+            false,
+            false,
+            vec!["10.0.0.0/8".to_string(), "fd00::/8".to_string()],
+        )
+        .unwrap();
+
+        assert!(matcher.string_match("10.20.30.40"));
+        assert!(matcher.string_match("fd12::1"));
+        assert!(!matcher.string_match("192.168.0.1"));
+        assert!(!matcher.string_match("-"));
+
+        let result = new_string_matcher(
+            TextPatternModifier::Cidr,
+            false,
+            false,
+            false,
+            vec!["10.0.0.0/99".to_string()],
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_new_regex_matcher() {
+        let flags = RegexFlags {
+            case_insensitive: true,
+            ..Default::default()
+        };
+        let matcher =
+            new_regex_matcher(flags, false, vec![r"\\cmd\.exe$".into(), "^pwsh".into()]).unwrap();
+        assert!(matcher.string_match("C:\\Windows\\CMD.EXE"));
+        assert!(matcher.string_match("PWSH -c x"));
+        assert!(!matcher.string_match("bash"));
+
+        let matcher = new_regex_matcher(flags, true, vec!["a".into(), "b".into()]).unwrap();
+        assert!(matcher.string_match("AB"));
+        assert!(!matcher.string_match("A"));
+
+        assert!(new_regex_matcher(flags, false, vec!["(a+)+".into()]).is_err());
+        assert!(new_regex_matcher(flags, false, vec![]).is_err());
+    }
+
     #[test]
     fn test_new_num_matcher() {
         let matcher = new_num_matcher(vec![1, 2, 3]).unwrap();
@@ -272,6 +437,25 @@ mod tests {
         assert!(!matcher.num_match(4));
     }
 
+    #[test]
+    fn test_new_num_comparison_matcher() {
+        let any =
+            new_num_comparison_matcher(NumComparison::GreaterThan, false, vec![10.0, 5.5]).unwrap();
+        assert!(any.num_match(6));
+        assert!(any.float_match(5.6));
+        assert!(!any.num_match(5));
+
+        let all =
+            new_num_comparison_matcher(NumComparison::GreaterThan, true, vec![10.0, 5.5]).unwrap();
+        assert!(!all.num_match(6));
+        assert!(all.num_match(11));
+
+        assert!(new_num_comparison_matcher(NumComparison::LessThan, false, vec![]).is_err());
+        assert!(
+            new_num_comparison_matcher(NumComparison::LessThan, false, vec![f64::NAN]).is_err()
+        );
+    }
+
     #[test]
     fn test_new_num_matcher_single() {
         let matcher = new_num_matcher(vec![42]).unwrap();
@@ -0,0 +1,91 @@
+//! Field reference patterns for the `fieldref` modifier
+
+use crate::pattern::string_matcher::{
+    contains_ignore_case, ends_with_ignore_case, eq_ignore_case, starts_with_ignore_case,
+};
+use crate::pattern::TextPatternModifier;
+use std::sync::Arc;
+
+/// Pattern comparing a field against another field of the same event
+///
+/// The referenced value is only known at evaluation time, so matching takes
+/// both values rather than implementing [`StringMatcher`](crate::pattern::StringMatcher).
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldRefPattern {
+    /// Name of the referenced field
+    pub field: Arc<str>,
+    /// Comparison to perform: exact, contains, prefix or suffix
+    pub modifier: TextPatternModifier,
+    /// Whether to perform case-insensitive matching
+    pub lowercase: bool,
+}
+
+impl FieldRefPattern {
+    /// Create a field reference pattern
+    ///
+    /// Only exact, contains, prefix and suffix comparisons are supported.
+    pub fn new(
+        field: impl Into<Arc<str>>,
+        modifier: TextPatternModifier,
+        lowercase: bool,
+    ) -> Result<Self, String> {
+        match modifier {
+            TextPatternModifier::None
+            | TextPatternModifier::Contains
+            | TextPatternModifier::Prefix
+            | TextPatternModifier::Suffix => Ok(Self {
+                field: field.into(),
+                modifier,
+                lowercase,
+            }),
+            other => Err(format!(
+                "fieldref does not support the {:?} modifier",
+                other
+            )),
+        }
+    }
+
+    /// Match a field value against the value of the referenced field
+    pub fn matches(&self, value: &str, reference: &str) -> bool {
+        match (self.modifier, self.lowercase) {
+            (TextPatternModifier::Contains, true) => contains_ignore_case(value, reference),
+            (TextPatternModifier::Contains, false) => value.contains(reference),
+            (TextPatternModifier::Prefix, true) => starts_with_ignore_case(value, reference),
+            (TextPatternModifier::Prefix, false) => value.starts_with(reference),
+            (TextPatternModifier::Suffix, true) => ends_with_ignore_case(value, reference),
+            (TextPatternModifier::Suffix, false) => value.ends_with(reference),
+            (_, true) => eq_ignore_case(value, reference),
+            (_, false) => value == reference,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_ref_comparisons() {
+        let exact = FieldRefPattern::new("Image", TextPatternModifier::None, true).unwrap();
+        assert!(exact.matches("C:\\Tools\\a.exe", "c:\\tools\\A.EXE"));
+        assert!(!exact.matches("C:\\Tools\\a.exe", "C:\\Tools\\b.exe"));
+
+        let prefix = FieldRefPattern::new("Dir", TextPatternModifier::Prefix, true).unwrap();
+        assert!(prefix.matches("C:\\Users\\Bob\\x.exe", "c:\\users\\bob"));
+
+        let suffix = FieldRefPattern::new("Name", TextPatternModifier::Suffix, false).unwrap();
+        assert!(suffix.matches("C:\\x\\evil.exe", "evil.exe"));
+        assert!(!suffix.matches("C:\\x\\evil.exe", "EVIL.exe"));
+
+        let contains = FieldRefPattern::new("User", TextPatternModifier::Contains, true).unwrap();
+        assert!(contains.matches("net user ADMIN /add", "admin"));
+        assert!(contains.matches("anything", ""));
+        assert!(!contains.matches("net user guest", "admin"));
+    }
+
+    #[test]
+    fn test_field_ref_unsupported_modifier() {
+        assert!(FieldRefPattern::new("Image", TextPatternModifier::Regex, true).is_err());
+        assert!(FieldRefPattern::new("Image", TextPatternModifier::Cidr, true).is_err());
+    }
+}
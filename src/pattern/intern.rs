@@ -2,9 +2,13 @@
 //!
 //! This module provides a thread-safe string interner that allows multiple
 //! patterns to share the same string storage, reducing memory overhead
-//! and improving cache locality.
+//! and improving cache locality. Compiled regular expressions are shared the
+//! same way, keyed by the interned pattern and its flags.
 
+use crate::error::SigmaError;
+use crate::pattern::security::{safe_regex_compile_with_flags, RegexFlags};
 use once_cell::sync::Lazy;
+use regex::Regex;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
@@ -12,6 +16,9 @@ use std::sync::{Arc, RwLock};
 /// Global string interner for pattern strings
 static PATTERN_INTERNER: Lazy<StringInterner> = Lazy::new(StringInterner::new);
 
+/// Global cache of compiled regular expressions
+static REGEX_CACHE: Lazy<RegexCache> = Lazy::new(RegexCache::new);
+
 /// Configuration for the string interner
 pub struct StringInternerConfig {
     /// Maximum number of entries to cache
@@ -152,6 +159,86 @@ pub struct InternerStats {
     pub poison_events: u64,
 }
 
+/// Thread-safe cache of compiled regular expressions with size limits
+///
+/// Patterns are keyed by their interned string and flags, so a regex shared by
+/// many rules is validated and compiled once. `Regex` clones share the
+/// compiled program.
+pub struct RegexCache {
+    regexes: RwLock<HashMap<(Arc<str>, RegexFlags), Regex>>,
+    max_capacity: usize,
+}
+
+impl RegexCache {
+    /// Create a new regex cache with default configuration
+    pub fn new() -> Self {
+        Self::with_config(StringInternerConfig::default())
+    }
+
+    /// Create a new regex cache with specified configuration
+    pub fn with_config(config: StringInternerConfig) -> Self {
+        Self {
+            regexes: RwLock::new(HashMap::new()),
+            max_capacity: config.max_capacity,
+        }
+    }
+
+    /// Get the compiled regex for a pattern, compiling it safely on first use
+    pub fn get_or_compile(&self, pattern: &str, flags: RegexFlags) -> Result<Regex, SigmaError> {
+        let key = (intern_pattern(pattern), flags);
+
+        if let Some(regex) = self
+            .regexes
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(&key)
+        {
+            return Ok(regex.clone());
+        }
+
+        // Compile outside the lock; failures are not cached
+        let regex = safe_regex_compile_with_flags(pattern, flags)?;
+
+        let mut regexes = self
+            .regexes
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if regexes.len() >= self.max_capacity && !regexes.contains_key(&key) {
+            // Same simple eviction as the string interner
+            let to_remove = (self.max_capacity / 10).max(1);
+            let keys_to_remove: Vec<_> = regexes.keys().take(to_remove).cloned().collect();
+            for key in keys_to_remove {
+                regexes.remove(&key);
+            }
+        }
+        Ok(regexes.entry(key).or_insert(regex).clone())
+    }
+
+    /// Number of cached regexes
+    pub fn len(&self) -> usize {
+        self.regexes
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .len()
+    }
+
+    /// Whether the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for RegexCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Compile a regex safely through the global regex cache
+pub fn intern_regex(pattern: &str, flags: RegexFlags) -> Result<Regex, SigmaError> {
+    REGEX_CACHE.get_or_compile(pattern, flags)
+}
+
 /// Intern a pattern string using the global interner
 pub fn intern_pattern(s: &str) -> Arc<str> {
     PATTERN_INTERNER.intern(s)
@@ -235,6 +322,42 @@ mod tests {
         assert!(stats.unique_strings >= 5); // At least half should remain
     }
 
+    #[test]
+    fn test_regex_cache() {
+        let cache = RegexCache::new();
+        let flags = RegexFlags::default();
+
+        let r1 = cache.get_or_compile(r"cmd\.exe$", flags).unwrap();
+        let r2 = cache.get_or_compile(r"cmd\.exe$", flags).unwrap();
+        assert_eq!(cache.len(), 1);
+        assert_eq!(r1.as_str(), r2.as_str());
+
+        // Flags are part of the key
+        let insensitive = RegexFlags {
+            case_insensitive: true,
+            ..Default::default()
+        };
+        let r3 = cache.get_or_compile(r"cmd\.exe$", insensitive).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert!(r3.is_match("CMD.EXE"));
+        assert!(!r1.is_match("CMD.EXE"));
+
+        // Unsafe patterns are rejected and not cached
+        assert!(cache.get_or_compile("(a+)+", flags).is_err());
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_regex_cache_eviction() {
+        let cache = RegexCache::with_config(StringInternerConfig { max_capacity: 10 });
+        for i in 0..15 {
+            cache
+                .get_or_compile(&format!("re_{}", i), RegexFlags::default())
+                .unwrap();
+        }
+        assert!(cache.len() <= 10);
+    }
+
     #[test]
     fn test_custom_capacity() {
         let config = StringInternerConfig { max_capacity: 100 };
@@ -0,0 +1,77 @@
+//! IP network pattern matching for the `cidr` modifier
+
+use crate::pattern::traits::StringMatcher;
+use ipnetwork::IpNetwork;
+use std::net::IpAddr;
+
+/// Pattern matching IP addresses inside a network
+///
+/// Event values that do not parse as an IP address never match.
+#[derive(Debug, Clone)]
+pub struct IpNetworkPattern {
+    /// The network to match addresses against
+    pub network: IpNetwork,
+}
+
+impl IpNetworkPattern {
+    /// Create a pattern from CIDR notation such as `10.0.0.0/8` or `fd00::/8`
+    ///
+    /// A bare address is treated as a single-host network.
+    pub fn new(cidr: &str) -> Result<Self, String> {
+        cidr.trim()
+            .parse()
+            .map(|network| Self { network })
+            .map_err(|e| format!("Invalid CIDR '{}': {}", cidr, e))
+    }
+}
+
+impl StringMatcher for IpNetworkPattern {
+    fn string_match(&self, value: &str) -> bool {
+        match value.trim().parse::<IpAddr>() {
+            // IPv4-mapped IPv6 addresses match IPv4 networks
+            Ok(addr) => self.network.contains(addr.to_canonical()) || self.network.contains(addr),
+            Err(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ipv4_network() {
+        let pattern = IpNetworkPattern::new("10.0.0.0/8").unwrap();
+
+        assert!(pattern.string_match("10.0.0.1"));
+        assert!(pattern.string_match("10.255.255.255"));
+        assert!(pattern.string_match(" 10.1.2.3 "));
+        assert!(pattern.string_match("::ffff:10.1.2.3"));
+        assert!(!pattern.string_match("11.0.0.1"));
+        assert!(!pattern.string_match("fd00::1"));
+    }
+
+    #[test]
+    fn test_ipv6_network() {
+        let pattern = IpNetworkPattern::new("fd00::/8").unwrap();
+
+        assert!(pattern.string_match("fd00::1"));
+        assert!(pattern.string_match("FDAB:1234::"));
+        assert!(!pattern.string_match("fe80::1"));
+        assert!(!pattern.string_match("10.0.0.1"));
+    }
+
+    #[test]
+    fn test_single_host_and_invalid_values() {
+        let pattern = IpNetworkPattern::new("192.168.1.10").unwrap();
+        assert!(pattern.string_match("192.168.1.10"));
+        assert!(!pattern.string_match("192.168.1.11"));
+
+        assert!(!pattern.string_match(""));
+        assert!(!pattern.string_match("not an ip"));
+        assert!(!pattern.string_match("192.168.1.10:443"));
+
+        assert!(IpNetworkPattern::new("10.0.0.0/33").is_err());
+        assert!(IpNetworkPattern::new("example.com").is_err());
+    }
+}
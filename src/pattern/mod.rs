@@ -3,11 +3,14 @@
 pub mod coercion;
 pub mod escape;
 pub mod factory;
+pub mod field_ref;
 pub mod intern;
+pub mod ip_matcher;
 pub mod num_matcher;
 pub mod security;
 pub mod string_matcher;
 pub mod traits;
+pub mod transform;
 pub mod whitespace;
 
 #[cfg(test)]
@@ -16,11 +19,17 @@ mod test_escape;
 pub use coercion::*;
 pub use escape::{escape_sigma_for_glob, escape_sigma_for_glob_cow};
 pub use factory::*;
-pub use intern::{global_interner_stats, intern_pattern, InternerStats, StringInternerConfig};
+pub use field_ref::FieldRefPattern;
+pub use intern::{
+    global_interner_stats, intern_pattern, intern_regex, InternerStats, RegexCache,
+    StringInternerConfig,
+};
+pub use ip_matcher::IpNetworkPattern;
 pub use num_matcher::*;
 pub use security::*;
 pub use string_matcher::*;
 pub use traits::*;
+pub use transform::{apply_transforms, ValueTransform};
 pub use whitespace::*;
 
 /// Type of sigma detection identifier
@@ -49,6 +58,8 @@ pub enum TextPatternModifier {
     Regex,
     /// Keyword pattern
     Keyword,
+    /// IP address within a CIDR network
+    Cidr,
 }
 
 #[cfg(test)]
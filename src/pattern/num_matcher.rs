@@ -15,6 +15,60 @@ impl NumMatcher for NumPattern {
     }
 }
 
+/// Comparison performed by the `lt`, `lte`, `gt` and `gte` modifiers, or by
+/// a plain non-integer value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumComparison {
+    /// Value must equal the bound
+    Equal,
+    /// Value must be less than the bound
+    LessThan,
+    /// Value must be less than or equal to the bound
+    LessOrEqual,
+    /// Value must be greater than the bound
+    GreaterThan,
+    /// Value must be greater than or equal to the bound
+    GreaterOrEqual,
+}
+
+impl NumComparison {
+    /// Look up the comparison for a modifier name
+    pub fn from_modifier(modifier: &str) -> Option<Self> {
+        match modifier {
+            "lt" => Some(Self::LessThan),
+            "lte" => Some(Self::LessOrEqual),
+            "gt" => Some(Self::GreaterThan),
+            "gte" => Some(Self::GreaterOrEqual),
+            _ => None,
+        }
+    }
+}
+
+/// Pattern comparing values against a numeric bound
+#[derive(Debug, Clone)]
+pub struct NumComparePattern {
+    /// The comparison to perform
+    pub comparison: NumComparison,
+    /// The bound to compare against
+    pub bound: f64,
+}
+
+impl NumMatcher for NumComparePattern {
+    fn num_match(&self, value: i64) -> bool {
+        self.float_match(value as f64)
+    }
+
+    fn float_match(&self, value: f64) -> bool {
+        match self.comparison {
+            NumComparison::Equal => value == self.bound,
+            NumComparison::LessThan => value < self.bound,
+            NumComparison::LessOrEqual => value <= self.bound,
+            NumComparison::GreaterThan => value > self.bound,
+            NumComparison::GreaterOrEqual => value >= self.bound,
+        }
+    }
+}
+
 /// Collection of numeric matchers (OR logic)
 #[derive(Debug)]
 pub struct NumMatchers {
@@ -32,6 +86,33 @@ impl NumMatcher for NumMatchers {
     fn num_match(&self, value: i64) -> bool {
         self.matchers.iter().any(|m| m.num_match(value))
     }
+
+    fn float_match(&self, value: f64) -> bool {
+        self.matchers.iter().any(|m| m.float_match(value))
+    }
+}
+
+/// Collection of numeric matchers (AND logic)
+#[derive(Debug)]
+pub struct NumMatchersConj {
+    matchers: Vec<Box<dyn NumMatcher>>,
+}
+
+impl NumMatchersConj {
+    /// Create a new collection of numeric matchers (AND logic)
+    pub fn new(matchers: Vec<Box<dyn NumMatcher>>) -> Self {
+        Self { matchers }
+    }
+}
+
+impl NumMatcher for NumMatchersConj {
+    fn num_match(&self, value: i64) -> bool {
+        self.matchers.iter().all(|m| m.num_match(value))
+    }
+
+    fn float_match(&self, value: f64) -> bool {
+        self.matchers.iter().all(|m| m.float_match(value))
+    }
 }
 
 #[cfg(test)]
@@ -62,4 +143,54 @@ mod tests {
         assert!(collection.num_match(3));
         assert!(!collection.num_match(4));
     }
+
+    #[test]
+    fn test_num_pattern_float_truncates() {
+        let pattern = NumPattern { value: 42 };
+
+        assert!(pattern.float_match(42.0));
+        assert!(pattern.float_match(42.9));
+        assert!(!pattern.float_match(f64::NAN));
+        assert!(!pattern.float_match(1e300));
+    }
+
+    #[test]
+    fn test_num_compare_pattern() {
+        let gt = NumComparePattern {
+            comparison: NumComparison::GreaterThan,
+            bound: 10.0,
+        };
+        assert!(gt.num_match(11));
+        assert!(!gt.num_match(10));
+        assert!(gt.float_match(10.5));
+
+        let lte = NumComparePattern {
+            comparison: NumComparison::LessOrEqual,
+            bound: 1.5,
+        };
+        assert!(lte.float_match(1.5));
+        assert!(lte.num_match(1));
+        assert!(!lte.num_match(2));
+        assert!(!lte.float_match(f64::NAN));
+    }
+
+    #[test]
+    fn test_num_matchers_conj() {
+        // Port range 1024 <= port < 49152
+        let range = NumMatchersConj::new(vec![
+            Box::new(NumComparePattern {
+                comparison: NumComparison::GreaterOrEqual,
+                bound: 1024.0,
+            }),
+            Box::new(NumComparePattern {
+                comparison: NumComparison::LessThan,
+                bound: 49152.0,
+            }),
+        ]);
+
+        assert!(range.num_match(1024));
+        assert!(range.num_match(8080));
+        assert!(!range.num_match(80));
+        assert!(!range.num_match(49152));
+    }
 }
@@ -43,6 +43,30 @@ static REDOS_COMPILED_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
         .collect()
 });
 
+/// Flags set by the Sigma regex sub-modifiers `|i`, `|m` and `|s`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct RegexFlags {
+    /// `i`: case-insensitive matching
+    pub case_insensitive: bool,
+    /// `m`: `^` and `$` match at line boundaries
+    pub multi_line: bool,
+    /// `s`: `.` also matches newlines
+    pub dot_matches_new_line: bool,
+}
+
+impl RegexFlags {
+    /// Set the flag for a regex sub-modifier, returning false for other modifiers
+    pub fn set_modifier(&mut self, modifier: &str) -> bool {
+        match modifier {
+            "i" => self.case_insensitive = true,
+            "m" => self.multi_line = true,
+            "s" => self.dot_matches_new_line = true,
+            _ => return false,
+        }
+        true
+    }
+}
+
 /// Validate and compile a regex pattern safely
 ///
 /// This function protects against ReDoS attacks by:
@@ -51,6 +75,17 @@ static REDOS_COMPILED_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
 /// - Setting size limits for compilation
 /// - Adding timeouts (future enhancement)
 pub fn safe_regex_compile(pattern: &str) -> Result<Regex, SigmaError> {
+    safe_regex_compile_with_flags(pattern, RegexFlags::default())
+}
+
+/// Validate and compile a regex pattern safely with Sigma regex flags
+///
+/// Performs the same checks as [`safe_regex_compile`] and passes the flags
+/// to the regex builder.
+pub fn safe_regex_compile_with_flags(
+    pattern: &str,
+    flags: RegexFlags,
+) -> Result<Regex, SigmaError> {
     // Check pattern length
     if pattern.len() > MAX_REGEX_PATTERN_LENGTH {
         return Err(SigmaError::UnsafeRegex {
@@ -102,6 +137,9 @@ pub fn safe_regex_compile(pattern: &str) -> Result<Regex, SigmaError> {
 
     // Compile with size limits
     let regex_result = regex::RegexBuilder::new(pattern)
+        .case_insensitive(flags.case_insensitive)
+        .multi_line(flags.multi_line)
+        .dot_matches_new_line(flags.dot_matches_new_line)
         .dfa_size_limit(MAX_DFA_SIZE)
         .size_limit(MAX_NFA_SIZE)
         .build();
@@ -288,6 +326,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_regex_flags() {
+        let mut flags = RegexFlags::default();
+        assert!(flags.set_modifier("i"));
+        assert!(flags.set_modifier("s"));
+        assert!(!flags.set_modifier("contains"));
+
+        let re = safe_regex_compile_with_flags("^cmd.exe$", flags).unwrap();
+        assert!(re.is_match("CMD\nEXE"));
+        assert!(!re.is_match("x\ncmd.exe"));
+
+        let multi_line = RegexFlags {
+            multi_line: true,
+            ..Default::default()
+        };
+        let re = safe_regex_compile_with_flags("^cmd.exe$", multi_line).unwrap();
+        assert!(re.is_match("x\ncmd.exe"));
+
+        // Safety checks still apply
+        assert!(safe_regex_compile_with_flags("(a+)+", flags).is_err());
+    }
+
     #[test]
     fn test_excessive_nesting() {
         let deeply_nested = "((((((((((a))))))))))";
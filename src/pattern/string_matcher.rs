@@ -2,7 +2,7 @@
 
 use crate::pattern::traits::StringMatcher;
 use crate::pattern::whitespace::handle_whitespace;
-use glob::Pattern as GlobPattern;
+use glob::{MatchOptions, Pattern as GlobPattern};
 use regex::Regex;
 use std::sync::Arc;
 
@@ -21,8 +21,7 @@ impl StringMatcher for ContentPattern {
     fn string_match(&self, value: &str) -> bool {
         let value = handle_whitespace(value, self.no_collapse_ws);
         if self.lowercase {
-            // Use case-insensitive comparison without double allocation
-            value.eq_ignore_ascii_case(&*self.token)
+            eq_ignore_case(&value, &self.token)
         } else {
             value.as_ref() == &*self.token
         }
@@ -44,9 +43,7 @@ impl StringMatcher for PrefixPattern {
     fn string_match(&self, value: &str) -> bool {
         let value = handle_whitespace(value, self.no_collapse_ws);
         if self.lowercase {
-            // Use case-insensitive prefix check without allocation
-            value.len() >= self.token.len()
-                && value[..self.token.len()].eq_ignore_ascii_case(&*self.token)
+            starts_with_ignore_case(&value, &self.token)
         } else {
             value.starts_with(&*self.token)
         }
@@ -68,13 +65,7 @@ impl StringMatcher for SuffixPattern {
     fn string_match(&self, value: &str) -> bool {
         let value = handle_whitespace(value, self.no_collapse_ws);
         if self.lowercase {
-            // Use case-insensitive suffix check without allocation
-            if value.len() >= self.token.len() {
-                let start = value.len() - self.token.len();
-                value[start..].eq_ignore_ascii_case(&*self.token)
-            } else {
-                false
-            }
+            ends_with_ignore_case(&value, &self.token)
         } else {
             value.ends_with(&*self.token)
         }
@@ -97,8 +88,10 @@ impl StringMatcher for RegexPattern {
 /// Pattern for glob matching
 #[derive(Debug)]
 pub struct GlobPatternMatcher {
-    /// The compiled glob pattern
+    /// The compiled glob pattern, lowercased when matching case-insensitively
     pub glob: GlobPattern,
+    /// Whether to perform case-insensitive matching
+    pub lowercase: bool,
     /// Whether to preserve whitespace
     pub no_collapse_ws: bool,
 }
@@ -106,7 +99,20 @@ pub struct GlobPatternMatcher {
 impl StringMatcher for GlobPatternMatcher {
     fn string_match(&self, value: &str) -> bool {
         let value = handle_whitespace(value, self.no_collapse_ws);
-        self.glob.matches(value.as_ref())
+        if !self.lowercase {
+            self.glob.matches(value.as_ref())
+        } else if value.is_ascii() {
+            self.glob.matches_with(
+                value.as_ref(),
+                MatchOptions {
+                    case_sensitive: false,
+                    ..MatchOptions::new()
+                },
+            )
+        } else {
+            // glob only folds ASCII case, so fold non-ASCII values up front
+            self.glob.matches(&value.to_lowercase())
+        }
     }
 }
 
@@ -162,6 +168,48 @@ impl StringMatcher for StringMatchersConj {
 
 // Helper functions
 
+/// Case-insensitive equality, avoiding allocation for ASCII input
+pub(crate) fn eq_ignore_case(value: &str, token: &str) -> bool {
+    if value.is_ascii() && token.is_ascii() {
+        value.eq_ignore_ascii_case(token)
+    } else {
+        value.to_lowercase() == token.to_lowercase()
+    }
+}
+
+/// Case-insensitive substring check, avoiding allocation for ASCII input
+pub(crate) fn contains_ignore_case(value: &str, token: &str) -> bool {
+    if value.is_ascii() && token.is_ascii() {
+        token.is_empty()
+            || value
+                .as_bytes()
+                .windows(token.len())
+                .any(|window| window.eq_ignore_ascii_case(token.as_bytes()))
+    } else {
+        value.to_lowercase().contains(&token.to_lowercase())
+    }
+}
+
+/// Case-insensitive prefix check, avoiding allocation for ASCII input
+pub(crate) fn starts_with_ignore_case(value: &str, token: &str) -> bool {
+    if value.is_ascii() && token.is_ascii() {
+        value.len() >= token.len()
+            && value.as_bytes()[..token.len()].eq_ignore_ascii_case(token.as_bytes())
+    } else {
+        value.to_lowercase().starts_with(&token.to_lowercase())
+    }
+}
+
+/// Case-insensitive suffix check, avoiding allocation for ASCII input
+pub(crate) fn ends_with_ignore_case(value: &str, token: &str) -> bool {
+    if value.is_ascii() && token.is_ascii() {
+        value.len() >= token.len()
+            && value.as_bytes()[value.len() - token.len()..].eq_ignore_ascii_case(token.as_bytes())
+    } else {
+        value.to_lowercase().ends_with(&token.to_lowercase())
+    }
+}
+
 /// Escape Sigma pattern for glob matching
 pub fn escape_sigma_for_glob(pattern: &str) -> String {
     if pattern.is_empty() {
@@ -280,6 +328,70 @@ mod tests {
         assert!(!pattern.string_match("testing"));
     }
 
+    #[test]
+    fn test_affix_patterns_case_insensitive() {
+        let suffix = SuffixPattern {
+            token: Arc::from("\\powershell.exe"),
+            lowercase: true,
+            no_collapse_ws: false,
+        };
+        assert!(suffix.string_match("C:\\Windows\\System32\\PowerShell.EXE"));
+        assert!(!suffix.string_match("C:\\pwsh.exe"));
+
+        let prefix = PrefixPattern {
+            token: Arc::from("ab"),
+            lowercase: true,
+            no_collapse_ws: false,
+        };
+        assert!(prefix.string_match("ABC"));
+        // Multi-byte characters must not split on a char boundary
+        assert!(!prefix.string_match("äbc"));
+        assert!(!prefix.string_match("a"));
+    }
+
+    #[test]
+    fn test_case_insensitive_non_ascii() {
+        let content = ContentPattern {
+            token: Arc::from("Файл"),
+            lowercase: true,
+            no_collapse_ws: false,
+        };
+        assert!(content.string_match("ФАЙЛ"));
+        assert!(content.string_match("файл"));
+
+        let suffix = SuffixPattern {
+            token: Arc::from("ÜBER"),
+            lowercase: true,
+            no_collapse_ws: false,
+        };
+        assert!(suffix.string_match("grüße über"));
+    }
+
+    #[test]
+    fn test_glob_pattern_case_insensitive() {
+        let glob = GlobPatternMatcher {
+            glob: GlobPattern::new("*invoke-mimikatz*").unwrap(),
+            lowercase: true,
+            no_collapse_ws: false,
+        };
+        assert!(glob.string_match("powershell Invoke-Mimikatz -DumpCreds"));
+        assert!(!glob.string_match("powershell Get-Process"));
+
+        let glob = GlobPatternMatcher {
+            glob: GlobPattern::new("*straße*").unwrap(),
+            lowercase: true,
+            no_collapse_ws: false,
+        };
+        assert!(glob.string_match("HAUPTSTRAßE 1"));
+
+        let cased = GlobPatternMatcher {
+            glob: GlobPattern::new("*Invoke*").unwrap(),
+            lowercase: false,
+            no_collapse_ws: false,
+        };
+        assert!(!cased.string_match("invoke"));
+    }
+
     #[test]
     fn test_whitespace_handling() {
         let pattern = ContentPattern {
@@ -12,6 +12,19 @@ pub trait StringMatcher: Debug + Send + Sync {
 pub trait NumMatcher: Debug + Send + Sync {
     /// Match a numeric value against this pattern
     fn num_match(&self, value: i64) -> bool;
+
+    /// Match a floating point value against this pattern
+    ///
+    /// By default the value is truncated to an integer, matching the
+    /// coercion applied to equality matches.
+    fn float_match(&self, value: f64) -> bool {
+        const I64_MAX_PLUS_ONE: f64 = 9223372036854775808.0;
+        let truncated = value.trunc();
+        truncated.is_finite()
+            && truncated >= i64::MIN as f64
+            && truncated < I64_MAX_PLUS_ONE
+            && self.num_match(truncated as i64)
+    }
 }
 
 /// Result of a pattern match operation
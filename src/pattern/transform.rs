@@ -0,0 +1,268 @@
+//! Value transformations for Sigma encoding and expansion modifiers
+//!
+//! Modifiers such as `base64offset` and `wide` rewrite the rule value before a
+//! matcher is built, so `CommandLine|wide|base64offset|contains: 'ping'` looks
+//! for the encoded forms of `ping` rather than the literal. A single value can
+//! expand into several alternative patterns.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+/// Maximum number of patterns a single rule value may expand into
+pub(crate) const MAX_EXPANSIONS: usize = 10_000;
+
+/// Characters accepted in place of a leading `-` or `/` by `windash`
+const WINDASH_VARIANTS: [char; 5] = ['-', '/', '\u{2013}', '\u{2014}', '\u{2015}'];
+
+/// Start offsets into the shifted base64 encodings used by `base64offset`
+const BASE64_OFFSET_START: [usize; 3] = [0, 2, 3];
+
+/// Characters trimmed from the end of the encoding, indexed by `(len + shift) % 3`
+const BASE64_OFFSET_END_TRIM: [usize; 3] = [0, 3, 2];
+
+/// A transformation applied to rule values by a field modifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueTransform {
+    /// `base64`: the value is base64 encoded
+    Base64,
+    /// `base64offset`: the value appears somewhere inside base64 encoded data
+    Base64Offset,
+    /// `utf16`: UTF-16 little endian with a byte order mark
+    Utf16,
+    /// `utf16le` / `wide`: UTF-16 little endian
+    Utf16Le,
+    /// `utf16be`: UTF-16 big endian
+    Utf16Be,
+    /// `windash`: command-line flags may start with any dash variant or `/`
+    WinDash,
+}
+
+impl ValueTransform {
+    /// Look up the transform for a modifier name
+    pub fn from_modifier(modifier: &str) -> Option<Self> {
+        match modifier {
+            "base64" => Some(Self::Base64),
+            "base64offset" => Some(Self::Base64Offset),
+            "utf16" => Some(Self::Utf16),
+            "utf16le" | "wide" => Some(Self::Utf16Le),
+            "utf16be" => Some(Self::Utf16Be),
+            "windash" => Some(Self::WinDash),
+            _ => None,
+        }
+    }
+
+    /// Apply the transform to one value, returning its alternative forms
+    fn apply(self, value: &[u8]) -> Result<Vec<Vec<u8>>, String> {
+        Ok(match self {
+            Self::Base64 => vec![STANDARD.encode(value).into_bytes()],
+            Self::Base64Offset => (0..3)
+                .filter_map(|shift| {
+                    let mut shifted = vec![b' '; shift];
+                    shifted.extend_from_slice(value);
+                    let encoded = STANDARD.encode(&shifted);
+                    let end = encoded
+                        .len()
+                        .checked_sub(BASE64_OFFSET_END_TRIM[(value.len() + shift) % 3])?;
+                    encoded
+                        .get(BASE64_OFFSET_START[shift]..end)
+                        .filter(|variant| !variant.is_empty())
+                        .map(|variant| variant.as_bytes().to_vec())
+                })
+                .collect(),
+            Self::Utf16 => {
+                let mut encoded = vec![0xFF, 0xFE];
+                encoded.extend(encode_utf16(value, u16::to_le_bytes));
+                vec![encoded]
+            }
+            Self::Utf16Le => vec![encode_utf16(value, u16::to_le_bytes)],
+            Self::Utf16Be => vec![encode_utf16(value, u16::to_be_bytes)],
+            Self::WinDash => expand_windash(&String::from_utf8_lossy(value))?
+                .into_iter()
+                .map(String::into_bytes)
+                .collect(),
+        })
+    }
+}
+
+/// Expand every flag prefix in a value into each `windash` variant
+///
+/// A `-` or `/` is treated as a flag prefix when it starts a word, i.e. it is
+/// not preceded by a word character and is followed by one. Fails when the
+/// flags would produce more than [`MAX_EXPANSIONS`] variants.
+fn expand_windash(value: &str) -> Result<Vec<String>, String> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let chars: Vec<char> = value.chars().collect();
+
+    let mut variants = vec![String::with_capacity(value.len())];
+    for (i, &c) in chars.iter().enumerate() {
+        let flag_prefix = matches!(c, '-' | '/')
+            && !i.checked_sub(1).is_some_and(|prev| is_word(chars[prev]))
+            && chars.get(i + 1).is_some_and(|&next| is_word(next));
+
+        if flag_prefix {
+            if variants.len() * WINDASH_VARIANTS.len() > MAX_EXPANSIONS {
+                return Err(format!(
+                    "windash expands to more than {} values",
+                    MAX_EXPANSIONS
+                ));
+            }
+            variants = variants
+                .iter()
+                .flat_map(|variant| {
+                    WINDASH_VARIANTS.iter().map(move |dash| {
+                        let mut expanded = variant.clone();
+                        expanded.push(*dash);
+                        expanded
+                    })
+                })
+                .collect();
+        } else {
+            variants.iter_mut().for_each(|variant| variant.push(c));
+        }
+    }
+
+    Ok(variants)
+}
+
+fn encode_utf16(value: &[u8], to_bytes: fn(u16) -> [u8; 2]) -> Vec<u8> {
+    String::from_utf8_lossy(value)
+        .encode_utf16()
+        .flat_map(to_bytes)
+        .collect()
+}
+
+/// Apply transforms in modifier order to a value, returning every resulting pattern
+///
+/// With no transforms the value is returned unchanged. Fails when the value
+/// would expand into more than [`MAX_EXPANSIONS`] patterns.
+pub fn apply_transforms(
+    transforms: &[ValueTransform],
+    value: String,
+) -> Result<Vec<String>, String> {
+    if transforms.is_empty() {
+        return Ok(vec![value]);
+    }
+
+    let mut current = vec![value.into_bytes()];
+    for transform in transforms {
+        let mut next = Vec::with_capacity(current.len());
+        for bytes in &current {
+            next.extend(transform.apply(bytes)?);
+            if next.len() > MAX_EXPANSIONS {
+                return Err(format!(
+                    "transforms expand to more than {} values",
+                    MAX_EXPANSIONS
+                ));
+            }
+        }
+        current = next;
+    }
+
+    Ok(current
+        .into_iter()
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(
+            apply_transforms(&[ValueTransform::Base64], "/bin/sh".to_string()).unwrap(),
+            vec!["L2Jpbi9zaA=="]
+        );
+    }
+
+    #[test]
+    fn test_base64_offset() {
+        // Matches pySigma / the Sigma specification examples
+        assert_eq!(
+            apply_transforms(&[ValueTransform::Base64Offset], "/bin/sh".to_string()).unwrap(),
+            vec!["L2Jpbi9za", "9iaW4vc2", "vYmluL3No"]
+        );
+
+        // Every variant occurs inside the encoding of text containing the value
+        for prefix in ["", "a", "ab"] {
+            let encoded = STANDARD.encode(format!("{}curl http://evil", prefix));
+            let variants =
+                apply_transforms(&[ValueTransform::Base64Offset], "http".to_string()).unwrap();
+            assert!(variants.iter().any(|v| encoded.contains(v.as_str())));
+        }
+    }
+
+    #[test]
+    fn test_utf16_variants() {
+        assert_eq!(
+            apply_transforms(&[ValueTransform::Utf16Le], "ab".to_string()).unwrap(),
+            vec!["a\0b\0"]
+        );
+        assert_eq!(
+            apply_transforms(&[ValueTransform::Utf16Be], "ab".to_string()).unwrap(),
+            vec!["\0a\0b"]
+        );
+        assert_eq!(
+            ValueTransform::from_modifier("wide"),
+            Some(ValueTransform::Utf16Le)
+        );
+    }
+
+    #[test]
+    fn test_windash() {
+        assert_eq!(
+            apply_transforms(&[ValueTransform::WinDash], "-enc".to_string()).unwrap(),
+            vec!["-enc", "/enc", "\u{2013}enc", "\u{2014}enc", "\u{2015}enc"]
+        );
+
+        // Only dashes that start a flag are expanded
+        let variants =
+            apply_transforms(&[ValueTransform::WinDash], " -a x-y /b".to_string()).unwrap();
+        assert_eq!(variants.len(), 25);
+        assert!(variants.contains(&" /a x-y \u{2014}b".to_string()));
+        assert!(variants.iter().all(|v| v.contains("x-y")));
+
+        assert_eq!(
+            apply_transforms(&[ValueTransform::WinDash], "no flags - here".to_string()).unwrap(),
+            vec!["no flags - here"]
+        );
+    }
+
+    #[test]
+    fn test_windash_expansion_limit() {
+        // Five flags give 5^5 variants, within the limit; seven exceed it
+        let five = "cmd -a -b -c -d -e".to_string();
+        assert_eq!(
+            apply_transforms(&[ValueTransform::WinDash], five)
+                .unwrap()
+                .len(),
+            3125
+        );
+
+        let seven = "cmd -a -b -c -d -e -f -g".to_string();
+        let err = apply_transforms(&[ValueTransform::WinDash], seven).unwrap_err();
+        assert!(err.contains("more than 10000 values"));
+    }
+
+    #[test]
+    fn test_chained_wide_base64() {
+        // PowerShell -EncodedCommand payloads are base64 of UTF-16LE
+        assert_eq!(
+            apply_transforms(
+                &[ValueTransform::Utf16Le, ValueTransform::Base64],
+                "ping".to_string()
+            )
+            .unwrap(),
+            vec!["cABpAG4AZwA="]
+        );
+        assert_eq!(
+            apply_transforms(
+                &[ValueTransform::Utf16, ValueTransform::Base64],
+                "a".to_string()
+            )
+            .unwrap(),
+            vec!["//5hAA=="]
+        );
+    }
+}
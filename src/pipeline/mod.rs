@@ -0,0 +1,632 @@
+//! Processing pipelines that adapt rules to the shape of the event stream
+//!
+//! SigmaHQ rules use Sysmon/Windows field names (`CommandLine`), while events
+//! are often normalized differently (`process.command_line` in ECS). A
+//! [`ProcessingPipeline`] rewrites a rule's detection before its tree is built,
+//! in the spirit of pySigma pipelines:
+//!
+//! ```yaml
+//! name: ecs_windows
+//! priority: 10
+//! transformations:
+//!   - id: ecs_process_fields
+//!     type: field_name_mapping
+//!     mapping:
+//!       CommandLine: process.command_line
+//!       Image: process.executable
+//!     rule_conditions:
+//!       - type: logsource
+//!         product: windows
+//!         category: process_creation
+//!   - type: drop_detection_item
+//!     field_name_conditions:
+//!       - type: include_fields
+//!         fields: [EventID]
+//! ```
+//!
+//! Transformations run in order, each one seeing the output of the previous.
+//! Renamed fields are also renamed in the aggregation of the condition, as in
+//! `count(User) by SourceIp > 5`.
+
+use crate::error::{Result, SigmaError};
+use crate::rule::{Detection, Rule};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Deserializer};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Aggregation after the condition pipe: function, optional field and optional group field
+static AGGREGATION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^(\s*\w+\s*\(\s*)([^\s()]*)(\s*\))(?:(\s+by\s+)([^\s<>=!]+))?(.*)$")
+        .expect("aggregation regex should compile")
+});
+
+/// An ordered list of transformations applied to rules before compilation
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ProcessingPipeline {
+    #[serde(default)]
+    /// Pipeline name, for logging
+    pub name: Option<String>,
+
+    #[serde(default)]
+    /// Pipelines with a lower priority run first when chained
+    pub priority: i32,
+
+    #[serde(default)]
+    /// Transformations in the order they are applied
+    pub transformations: Vec<ProcessingItem>,
+}
+
+/// A transformation together with the rules and fields it applies to
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProcessingItem {
+    #[serde(default)]
+    /// Item identifier, for logging
+    pub id: Option<String>,
+
+    #[serde(flatten)]
+    /// The change made to matching rules
+    pub transformation: Transformation,
+
+    #[serde(default)]
+    /// Conditions on the rule; the item is skipped unless they hold
+    pub rule_conditions: Vec<RuleCondition>,
+
+    #[serde(default)]
+    /// How rule conditions are combined
+    pub rule_cond_op: ConditionOp,
+
+    #[serde(default)]
+    /// Conditions on the field name of each detection item; all must hold
+    pub field_name_conditions: Vec<FieldNameCondition>,
+
+    #[serde(default)]
+    /// Negate the combined field name conditions
+    pub field_name_cond_not: bool,
+}
+
+/// A change made to the detection of a rule
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Transformation {
+    /// Rename fields; `|fieldref` values naming a mapped field are renamed too
+    ///
+    /// A rule in which two fields of one selection would end up with the same
+    /// name and modifiers fails to load.
+    FieldNameMapping {
+        /// Rule field name to event field name
+        mapping: HashMap<String, String>,
+    },
+    /// Prepend a prefix to field names
+    FieldNamePrefix {
+        /// Prefix such as `winlog.event_data.`
+        prefix: String,
+    },
+    /// Replace regex matches in string values
+    ReplaceString {
+        /// Pattern to search for
+        #[serde(deserialize_with = "deserialize_regex")]
+        regex: Regex,
+        /// Replacement, which may use `$1`-style capture references
+        replacement: String,
+    },
+    /// Remove detection items; a selection left empty fails the rule
+    DropDetectionItem,
+}
+
+/// Condition on the rule a processing item is applied to
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleCondition {
+    /// Fields set here must equal the rule's logsource
+    Logsource {
+        #[serde(default)]
+        /// Required product
+        product: Option<String>,
+        #[serde(default)]
+        /// Required category
+        category: Option<String>,
+        #[serde(default)]
+        /// Required service
+        service: Option<String>,
+    },
+}
+
+/// How several rule conditions are combined
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConditionOp {
+    /// Every condition must hold
+    #[default]
+    And,
+    /// At least one condition must hold
+    Or,
+}
+
+/// Condition on the field name of a detection item
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FieldNameCondition {
+    /// The field is one of the listed names
+    IncludeFields {
+        /// Field names, compared exactly
+        fields: Vec<String>,
+    },
+    /// The field is none of the listed names
+    ExcludeFields {
+        /// Field names, compared exactly
+        fields: Vec<String>,
+    },
+}
+
+fn deserialize_regex<'de, D>(deserializer: D) -> std::result::Result<Regex, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let pattern = String::deserialize(deserializer)?;
+    Regex::new(&pattern).map_err(serde::de::Error::custom)
+}
+
+impl ProcessingPipeline {
+    /// Load a pipeline from YAML
+    pub fn from_yaml_str(data: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(data)?)
+    }
+
+    /// Load a pipeline from a YAML file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_yaml_str(&std::fs::read_to_string(path)?)
+    }
+
+    /// Combine pipelines into one, ordered by priority
+    ///
+    /// Pipelines with equal priority keep the order they were given in.
+    pub fn chain(pipelines: impl IntoIterator<Item = ProcessingPipeline>) -> Self {
+        let mut pipelines: Vec<_> = pipelines.into_iter().collect();
+        pipelines.sort_by_key(|p| p.priority);
+
+        let name = pipelines
+            .iter()
+            .filter_map(|p| p.name.as_deref())
+            .collect::<Vec<_>>()
+            .join("+");
+
+        Self {
+            name: (!name.is_empty()).then_some(name),
+            priority: pipelines.first().map_or(0, |p| p.priority),
+            transformations: pipelines
+                .into_iter()
+                .flat_map(|p| p.transformations)
+                .collect(),
+        }
+    }
+
+    /// Return the rule's detection with every applicable transformation applied
+    ///
+    /// Fails when a transformation gives two items of a selection the same key.
+    pub fn apply(&self, rule: &Rule) -> Result<Detection> {
+        let mut detection = rule.detection.clone();
+        let name = self.name.as_deref().unwrap_or("unnamed");
+
+        for item in self.transformations.iter().filter(|i| i.applies_to(rule)) {
+            tracing::debug!(
+                "Pipeline {} applying {} to rule {}",
+                name,
+                item.id.as_deref().unwrap_or("unnamed item"),
+                rule.id
+            );
+
+            let idents: Vec<String> = detection.iter().map(|(k, _)| k.clone()).collect();
+            for ident in idents {
+                if let Some(value) = detection.0.get_mut(&ident) {
+                    item.transform_ident(value).map_err(|reason| {
+                        SigmaError::InvalidRule(format!(
+                            "Pipeline {} cannot transform '{}' of rule {}: {}",
+                            name, ident, rule.id, reason
+                        ))
+                    })?;
+                }
+            }
+
+            if let Some(condition) = detection
+                .condition()
+                .and_then(|c| item.transform_condition(c))
+            {
+                detection.insert("condition".to_string(), Value::String(condition));
+            }
+        }
+
+        Ok(detection)
+    }
+}
+
+impl ProcessingItem {
+    /// Whether the rule conditions hold for a rule
+    fn applies_to(&self, rule: &Rule) -> bool {
+        let mut results = self.rule_conditions.iter().map(|c| c.matches(rule));
+        match self.rule_cond_op {
+            ConditionOp::And => results.all(|r| r),
+            ConditionOp::Or => self.rule_conditions.is_empty() || results.any(|r| r),
+        }
+    }
+
+    /// Whether the field name conditions hold for a field
+    fn selects_field(&self, field: &str) -> bool {
+        let selected = self.field_name_conditions.iter().all(|c| c.matches(field));
+        selected != self.field_name_cond_not
+    }
+
+    /// New name of a field renamed by this item, if it renames the field
+    fn renamed_field(&self, field: &str) -> Option<String> {
+        if !self.selects_field(field) {
+            return None;
+        }
+        match &self.transformation {
+            Transformation::FieldNameMapping { mapping } => mapping.get(field).cloned(),
+            Transformation::FieldNamePrefix { prefix } => Some(format!("{}{}", prefix, field)),
+            _ => None,
+        }
+    }
+
+    /// Rename the aggregation fields of a condition, returning it if changed
+    fn transform_condition(&self, condition: &str) -> Option<String> {
+        let (search, aggregation) = condition.split_once('|')?;
+        let captures = AGGREGATION.captures(aggregation)?;
+        let field = captures.get(2).map_or("", |m| m.as_str());
+        let group = captures.get(5).map(|m| m.as_str());
+
+        let renamed_field = (!field.is_empty())
+            .then(|| self.renamed_field(field))
+            .flatten();
+        let renamed_group = group.and_then(|group| self.renamed_field(group));
+        if renamed_field.is_none() && renamed_group.is_none() {
+            return None;
+        }
+
+        Some(format!(
+            "{}|{}{}{}{}{}{}",
+            search,
+            &captures[1],
+            renamed_field.as_deref().unwrap_or(field),
+            &captures[3],
+            captures.get(4).map_or("", |m| m.as_str()),
+            renamed_group.as_deref().or(group).unwrap_or(""),
+            &captures[6]
+        ))
+    }
+
+    fn transform_ident(&self, value: &mut Value) -> std::result::Result<(), String> {
+        match value {
+            Value::Object(selection) => self.transform_selection(selection)?,
+            Value::Array(items) => {
+                for item in items {
+                    match item {
+                        Value::Object(selection) => self.transform_selection(selection)?,
+                        keyword => self.transform_keyword(keyword),
+                    }
+                }
+            }
+            keyword => self.transform_keyword(keyword),
+        }
+        Ok(())
+    }
+
+    /// Keywords have no field name, so only unconditional value replacement applies
+    fn transform_keyword(&self, value: &mut Value) {
+        if self.field_name_conditions.is_empty() {
+            if let Transformation::ReplaceString { regex, replacement } = &self.transformation {
+                replace_strings(value, regex, replacement);
+            }
+        }
+    }
+
+    fn transform_selection(
+        &self,
+        selection: &mut Map<String, Value>,
+    ) -> std::result::Result<(), String> {
+        let items = std::mem::take(selection);
+        // Original key of each item, to report two items ending up with one key
+        let mut sources: HashMap<String, String> = HashMap::with_capacity(items.len());
+
+        for (key, mut value) in items {
+            let (field, modifiers) = match key.split_once('|') {
+                Some((field, modifiers)) => (field, Some(modifiers)),
+                None => (key.as_str(), None),
+            };
+
+            let new_key = if !self.selects_field(field) {
+                key.clone()
+            } else {
+                let field = match &self.transformation {
+                    Transformation::FieldNameMapping { mapping } => {
+                        let is_fieldref = modifiers.is_some_and(|m| {
+                            m.split('|').any(|m| m.eq_ignore_ascii_case("fieldref"))
+                        });
+                        if is_fieldref {
+                            map_field_refs(&mut value, mapping);
+                        }
+                        mapping.get(field).map_or(field, String::as_str).to_string()
+                    }
+                    Transformation::FieldNamePrefix { prefix } => format!("{}{}", prefix, field),
+                    Transformation::ReplaceString { regex, replacement } => {
+                        replace_strings(&mut value, regex, replacement);
+                        field.to_string()
+                    }
+                    Transformation::DropDetectionItem => continue,
+                };
+
+                match modifiers {
+                    Some(modifiers) => format!("{}|{}", field, modifiers),
+                    None => field,
+                }
+            };
+
+            if let Some(previous) = sources.insert(new_key.clone(), key.clone()) {
+                return Err(format!(
+                    "fields '{}' and '{}' both become '{}'",
+                    previous, key, new_key
+                ));
+            }
+            selection.insert(new_key, value);
+        }
+
+        Ok(())
+    }
+}
+
+impl RuleCondition {
+    fn matches(&self, rule: &Rule) -> bool {
+        match self {
+            RuleCondition::Logsource {
+                product,
+                category,
+                service,
+            } => {
+                rule.logsource
+                    .matches(product.as_deref(), category.as_deref(), service.as_deref())
+            }
+        }
+    }
+}
+
+impl FieldNameCondition {
+    fn matches(&self, field: &str) -> bool {
+        match self {
+            FieldNameCondition::IncludeFields { fields } => fields.iter().any(|f| f == field),
+            FieldNameCondition::ExcludeFields { fields } => !fields.iter().any(|f| f == field),
+        }
+    }
+}
+
+/// Replace regex matches in a string value or list of string values
+fn replace_strings(value: &mut Value, regex: &Regex, replacement: &str) {
+    match value {
+        Value::String(s) => {
+            if let std::borrow::Cow::Owned(replaced) = regex.replace_all(s, replacement) {
+                *s = replaced;
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                replace_strings(item, regex, replacement);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Rename the fields named by `|fieldref` values
+fn map_field_refs(value: &mut Value, mapping: &HashMap<String, String>) {
+    match value {
+        Value::String(s) => {
+            if let Some(target) = mapping.get(s.as_str()) {
+                *s = target.clone();
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                map_field_refs(item, mapping);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rule::rule_from_yaml;
+    use serde_json::json;
+
+    const RULE: &[u8] = br#"
+title: Suspicious Whoami
+id: 12345678-1234-1234-1234-123456789050
+logsource:
+    product: windows
+    category: process_creation
+detection:
+    selection:
+        EventID: 1
+        Image|endswith: '\whoami.exe'
+        ParentImage|fieldref: Image
+    keywords:
+        - 'C:\Windows'
+    condition: selection or keywords
+"#;
+
+    fn pipeline(yaml: &str) -> ProcessingPipeline {
+        ProcessingPipeline::from_yaml_str(yaml).expect("pipeline should load")
+    }
+
+    #[test]
+    fn test_field_name_transformations() {
+        let rule = rule_from_yaml(RULE).unwrap();
+        let detection = pipeline(
+            r#"
+name: ecs
+transformations:
+  - type: field_name_mapping
+    mapping:
+      Image: process.executable
+      ParentImage: process.parent.executable
+  - type: field_name_prefix
+    prefix: 'winlog.'
+    field_name_conditions:
+      - type: include_fields
+        fields: [EventID]
+"#,
+        )
+        .apply(&rule)
+        .unwrap();
+
+        assert_eq!(
+            detection.get("selection").unwrap(),
+            &json!({
+                "winlog.EventID": 1,
+                "process.executable|endswith": "\\whoami.exe",
+                "process.parent.executable|fieldref": "process.executable"
+            })
+        );
+        assert_eq!(detection.get("keywords").unwrap(), &json!(["C:\\Windows"]));
+        assert_eq!(detection.condition(), Some("selection or keywords"));
+    }
+
+    #[test]
+    fn test_value_replacement_and_drop() {
+        let rule = rule_from_yaml(RULE).unwrap();
+        let detection = pipeline(
+            r#"
+transformations:
+  - type: replace_string
+    regex: '^C:\\Windows'
+    replacement: '%SYSTEMROOT%'
+  - type: replace_string
+    regex: '\\'
+    replacement: '/'
+    field_name_conditions:
+      - type: include_fields
+        fields: [Image]
+  - type: drop_detection_item
+    field_name_conditions:
+      - type: exclude_fields
+        fields: [Image]
+    field_name_cond_not: true
+"#,
+        )
+        .apply(&rule)
+        .unwrap();
+
+        assert_eq!(
+            detection.get("selection").unwrap(),
+            &json!({"EventID": 1, "ParentImage|fieldref": "Image"})
+        );
+        assert_eq!(detection.get("keywords").unwrap(), &json!(["%SYSTEMROOT%"]));
+    }
+
+    #[test]
+    fn test_rule_conditions_and_chaining() {
+        let rule = rule_from_yaml(RULE).unwrap();
+        let linux = pipeline(
+            r#"
+name: linux
+priority: 20
+transformations:
+  - type: field_name_prefix
+    prefix: 'linux.'
+    rule_conditions:
+      - type: logsource
+        product: linux
+      - type: logsource
+        category: file_event
+    rule_cond_op: or
+"#,
+        );
+        let windows = pipeline(
+            r#"
+name: windows
+priority: 10
+transformations:
+  - type: field_name_prefix
+    prefix: 'win.'
+    rule_conditions:
+      - type: logsource
+        product: windows
+"#,
+        );
+
+        let chained = ProcessingPipeline::chain([linux, windows]);
+        assert_eq!(chained.name.as_deref(), Some("windows+linux"));
+
+        let detection = chained.apply(&rule).unwrap();
+        assert!(detection
+            .get("selection")
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .keys()
+            .all(|k| k.starts_with("win.")));
+
+        assert!(ProcessingPipeline::from_yaml_str(
+            "transformations:\n  - type: replace_string\n    regex: '('\n    replacement: ''\n"
+        )
+        .is_err());
+        assert!(ProcessingPipeline::from_yaml_str("transformations:\n  - type: rename\n").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_aggregation_fields_and_collisions() {
+        let rule = rule_from_yaml(
+            br#"
+title: Many Logons
+id: 12345678-1234-1234-1234-123456789051
+logsource:
+    product: windows
+detection:
+    selection:
+        EventID: 4625
+        TargetUserName: admin
+        User: admin
+    timeframe: 5m
+    condition: selection | count(TargetUserName) by IpAddress > 5
+"#,
+        )
+        .unwrap();
+
+        let detection = pipeline(
+            r#"
+transformations:
+  - type: field_name_mapping
+    mapping:
+      TargetUserName: user.name
+      IpAddress: source.ip
+"#,
+        )
+        .apply(&rule)
+        .unwrap();
+        assert_eq!(
+            detection.condition(),
+            Some("selection | count(user.name) by source.ip > 5")
+        );
+
+        let mut parser = crate::parser::Parser::new(detection, false);
+        parser.run().await.unwrap();
+        let aggregation = parser.aggregation().unwrap();
+        assert_eq!(aggregation.by_field.as_deref(), Some("source.ip"));
+
+        let err = pipeline(
+            r#"
+name: ecs
+transformations:
+  - type: field_name_mapping
+    mapping:
+      TargetUserName: user.name
+      User: user.name
+"#,
+        )
+        .apply(&rule)
+        .unwrap_err();
+        assert!(err.to_string().contains("both become 'user.name'"));
+    }
+}
@@ -0,0 +1,191 @@
+use crate::error::{Result, SigmaError};
+use crate::parser::aggregation::parse_timeframe;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Correlation section of a Sigma v2 correlation rule
+///
+/// Correlations reference base rules by `name` or `id` and raise an alert
+/// when their matches satisfy the correlation within `timespan`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Correlation {
+    /// Kind of correlation to perform
+    #[serde(rename = "type")]
+    pub correlation_type: CorrelationType,
+
+    /// Names or ids of the referenced base rules
+    pub rules: Vec<String>,
+
+    #[serde(rename = "group-by", default)]
+    /// Event fields whose values partition the correlation state
+    pub group_by: Vec<String>,
+
+    /// Window the referenced matches must fall into, e.g. `5m`
+    pub timespan: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Threshold applied to the correlated value
+    pub condition: Option<CorrelationCondition>,
+}
+
+/// Sigma correlation types
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CorrelationType {
+    /// Number of matching events
+    EventCount,
+    /// Number of distinct values of a field across matching events
+    ValueCount,
+    /// All referenced rules matched, in any order
+    Temporal,
+    /// All referenced rules matched in the listed order
+    TemporalOrdered,
+}
+
+/// Comparison bounds of a correlation condition
+///
+/// Every bound that is present must hold for the condition to be satisfied.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct CorrelationCondition {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Value must be greater than this bound
+    pub gt: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Value must be greater than or equal to this bound
+    pub gte: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Value must be less than this bound
+    pub lt: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Value must be less than or equal to this bound
+    pub lte: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Value must be equal to this bound
+    pub eq: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Field whose distinct values are counted by `value_count`
+    pub field: Option<String>,
+}
+
+impl CorrelationCondition {
+    /// Check whether the value satisfies all configured bounds
+    pub fn evaluate(&self, value: f64) -> bool {
+        self.gt.map_or(true, |b| value > b)
+            && self.gte.map_or(true, |b| value >= b)
+            && self.lt.map_or(true, |b| value < b)
+            && self.lte.map_or(true, |b| value <= b)
+            && self.eq.map_or(true, |b| (value - b).abs() < f64::EPSILON)
+    }
+
+    fn has_bound(&self) -> bool {
+        self.gt.is_some()
+            || self.gte.is_some()
+            || self.lt.is_some()
+            || self.lte.is_some()
+            || self.eq.is_some()
+    }
+}
+
+impl Correlation {
+    /// Parsed `timespan`
+    pub fn window(&self) -> Result<Duration> {
+        parse_timeframe(&self.timespan).ok_or_else(|| {
+            SigmaError::InvalidRule(format!("Invalid correlation timespan '{}'", self.timespan))
+        })
+    }
+
+    /// Condition to apply, defaulting temporal correlations to "all rules matched"
+    pub fn effective_condition(&self) -> CorrelationCondition {
+        match (&self.condition, self.correlation_type) {
+            (Some(condition), _) if condition.has_bound() => condition.clone(),
+            (_, CorrelationType::Temporal | CorrelationType::TemporalOrdered) => {
+                CorrelationCondition {
+                    gte: Some(self.rules.len() as f64),
+                    ..Default::default()
+                }
+            }
+            (condition, _) => condition.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Validate the correlation section
+    pub fn validate(&self) -> Result<()> {
+        if self.rules.is_empty() {
+            return Err(SigmaError::InvalidRule(
+                "Correlation must reference at least one rule".to_string(),
+            ));
+        }
+
+        self.window()?;
+
+        let condition = self.condition.as_ref();
+        match self.correlation_type {
+            CorrelationType::EventCount | CorrelationType::ValueCount
+                if !condition.is_some_and(CorrelationCondition::has_bound) =>
+            {
+                return Err(SigmaError::InvalidRule(format!(
+                    "Correlation type {:?} requires a condition",
+                    self.correlation_type
+                )));
+            }
+            CorrelationType::ValueCount if condition.and_then(|c| c.field.as_ref()).is_none() => {
+                return Err(SigmaError::InvalidRule(
+                    "value_count correlation requires a condition field".to_string(),
+                ));
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_correlation_deserialize() {
+        let yaml = r#"
+type: value_count
+rules:
+  - failed_logon
+group-by:
+  - SourceIp
+timespan: 10m
+condition:
+  gte: 5
+  field: TargetUserName
+"#;
+        let correlation: Correlation = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(correlation.correlation_type, CorrelationType::ValueCount);
+        assert_eq!(correlation.group_by, vec!["SourceIp"]);
+        assert_eq!(correlation.window().unwrap(), Duration::from_secs(600));
+        assert!(correlation.validate().is_ok());
+
+        let condition = correlation.effective_condition();
+        assert!(condition.evaluate(5.0));
+        assert!(!condition.evaluate(4.0));
+    }
+
+    #[test]
+    fn test_correlation_validation() {
+        let mut correlation = Correlation {
+            correlation_type: CorrelationType::EventCount,
+            rules: vec!["base".to_string()],
+            group_by: vec![],
+            timespan: "1h".to_string(),
+            condition: None,
+        };
+        assert!(correlation.validate().is_err());
+
+        // Temporal correlations default to requiring every referenced rule
+        correlation.correlation_type = CorrelationType::Temporal;
+        correlation.rules.push("other".to_string());
+        assert!(correlation.validate().is_ok());
+        assert_eq!(correlation.effective_condition().gte, Some(2.0));
+
+        correlation.timespan = "soon".to_string();
+        assert!(correlation.validate().is_err());
+    }
+}
@@ -2,6 +2,13 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 
+/// Detection keys that configure the rule rather than define a search identifier
+const RESERVED_KEYS: [&str; 2] = ["condition", "timeframe"];
+
+fn is_reserved_key(key: &str) -> bool {
+    RESERVED_KEYS.contains(&key)
+}
+
 /// Detection represents the detection field in sigma rule
 /// contains condition expression and identifier fields for building AST
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
@@ -19,11 +26,16 @@ impl Detection {
         self.0.get("condition").and_then(|v| v.as_str())
     }
 
-    /// Extract all non-condition keys and their values
+    /// Get the aggregation timeframe (e.g. "5m") if present
+    pub fn timeframe(&self) -> Option<&str> {
+        self.0.get("timeframe").and_then(|v| v.as_str())
+    }
+
+    /// Extract all search identifiers (everything except condition and timeframe)
     pub fn extract(&self) -> HashMap<String, Value> {
         self.0
             .iter()
-            .filter(|(k, _)| k.as_str() != "condition")
+            .filter(|(k, _)| !is_reserved_key(k))
             .map(|(k, v)| (k.clone(), v.clone()))
             .collect()
     }
@@ -38,9 +50,9 @@ impl Detection {
         self.0.contains_key(key)
     }
 
-    /// Get the number of detection rules (excluding condition)
+    /// Get the number of detection rules (excluding condition and timeframe)
     pub fn rule_count(&self) -> usize {
-        self.0.len() - if self.condition().is_some() { 1 } else { 0 }
+        self.0.keys().filter(|k| !is_reserved_key(k)).count()
     }
 
     /// Insert a new detection rule
@@ -48,9 +60,9 @@ impl Detection {
         self.0.insert(key, value);
     }
 
-    /// Get an iterator over the detection rules (excluding condition)
+    /// Get an iterator over the detection rules (excluding condition and timeframe)
     pub fn iter(&self) -> impl Iterator<Item = (&String, &Value)> {
-        self.0.iter().filter(|(k, _)| k.as_str() != "condition")
+        self.0.iter().filter(|(k, _)| !is_reserved_key(k))
     }
 }
 
@@ -96,6 +108,19 @@ mod tests {
         assert!(!extracted.contains_key("condition"));
     }
 
+    #[test]
+    fn test_detection_timeframe_is_not_a_selection() {
+        let mut detection = Detection::new();
+        detection.insert("condition".to_string(), json!("selection | count() > 5"));
+        detection.insert("timeframe".to_string(), json!("5m"));
+        detection.insert("selection".to_string(), json!({"EventID": 4625}));
+
+        assert_eq!(detection.timeframe(), Some("5m"));
+        assert_eq!(detection.rule_count(), 1);
+        assert_eq!(detection.iter().count(), 1);
+        assert!(!detection.extract().contains_key("timeframe"));
+    }
+
     #[test]
     fn test_detection_deserialize() {
         let yaml = r#"
@@ -0,0 +1,55 @@
+use super::{Detection, Logsource, Rule};
+use crate::error::{Result, SigmaError};
+use serde::{Deserialize, Serialize};
+
+/// Filter section of a Sigma v2 filter document
+///
+/// Filters refine other rules without editing them: the filter condition is
+/// ANDed with the condition of every targeted rule, so exclusions are written
+/// as `condition: not selection`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Filter {
+    #[serde(default)]
+    /// Ids or names of the targeted rules; when empty, every rule matching
+    /// the filter's logsource is targeted
+    pub rules: Vec<String>,
+
+    #[serde(flatten)]
+    /// Search identifiers and the condition applied to the targeted rules
+    pub detection: Detection,
+}
+
+impl Filter {
+    /// Check whether a filter with this section and `logsource` applies to `rule`
+    ///
+    /// Fields set in the filter's logsource must equal the rule's.
+    pub fn applies_to(&self, logsource: &Logsource, rule: &Rule) -> bool {
+        let referenced =
+            self.rules.is_empty() || self.rules.iter().any(|r| rule.is_referenced_by(r));
+
+        referenced
+            && rule.logsource.matches(
+                logsource.product.as_deref(),
+                logsource.category.as_deref(),
+                logsource.service.as_deref(),
+            )
+    }
+
+    /// Validate the filter section of a document with the given logsource
+    pub fn validate(&self, logsource: &Logsource) -> Result<()> {
+        if self.detection.condition().is_none() {
+            return Err(SigmaError::MissingCondition);
+        }
+
+        let has_logsource = logsource.product.is_some()
+            || logsource.category.is_some()
+            || logsource.service.is_some();
+        if self.rules.is_empty() && !has_logsource {
+            return Err(SigmaError::InvalidRule(
+                "Filter must reference rules or a logsource".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 /// Logsource represents the logsource field in sigma rule
 /// It defines relevant event streams and is used for pre-filtering
-#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct Logsource {
     #[serde(skip_serializing_if = "Option::is_none")]
     /// Product name (e.g., windows, linux)
@@ -49,6 +49,26 @@ impl Logsource {
 
         product_match && category_match && service_match
     }
+
+    /// Check whether a rule with this logsource can apply to events of `event`
+    ///
+    /// Unlike [`matches`](Self::matches), a field left unset on either side is
+    /// a wildcard, so generic rules (`product: windows`) cover more specific
+    /// events (`windows`/`process_creation`) and vice versa. Only a field set
+    /// on both sides to different values rules the event out. Values are
+    /// compared ignoring ASCII case, as event classification does.
+    pub fn covers(&self, event: &Logsource) -> bool {
+        fn compatible(rule: &Option<String>, event: &Option<String>) -> bool {
+            match (rule, event) {
+                (Some(rule), Some(event)) => rule.eq_ignore_ascii_case(event),
+                _ => true,
+            }
+        }
+
+        compatible(&self.product, &event.product)
+            && compatible(&self.category, &event.category)
+            && compatible(&self.service, &event.service)
+    }
 }
 
 #[cfg(test)]
@@ -73,4 +93,33 @@ mod tests {
         // Should match when no filters provided
         assert!(logsource.matches(None, None, None));
     }
+
+    #[test]
+    fn test_logsource_covers() {
+        let event = Logsource {
+            product: Some("windows".to_string()),
+            category: Some("process_creation".to_string()),
+            ..Default::default()
+        };
+
+        let generic = Logsource {
+            product: Some("Windows".to_string()),
+            ..Default::default()
+        };
+        let sysmon = Logsource {
+            product: Some("windows".to_string()),
+            category: Some("process_creation".to_string()),
+            service: Some("sysmon".to_string()),
+            definition: None,
+        };
+        let linux = Logsource {
+            product: Some("linux".to_string()),
+            ..Default::default()
+        };
+
+        assert!(generic.covers(&event));
+        assert!(sysmon.covers(&event));
+        assert!(!linux.covers(&event));
+        assert!(Logsource::default().covers(&event));
+    }
 }
@@ -28,18 +28,28 @@
 //! ```
 
 use crate::error::{Result, SigmaError};
+use crate::parser::PlaceholderRegistry;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::sync::Arc;
 
+/// Correlation module for Sigma v2 correlation rules
+pub mod correlation;
 /// Detection module containing Sigma detection logic
 pub mod detection;
+/// Filter module for Sigma v2 filter documents
+pub mod filter;
 /// Logsource module for log source definitions
 pub mod logsource;
+/// Multi-document rule files with `action: global`, `reset` and `repeat`
+pub mod multipart;
 /// Tags module for rule tag handling
 pub mod tags;
 
+pub use correlation::{Correlation, CorrelationCondition, CorrelationType};
 pub use detection::Detection;
+pub use filter::Filter;
 pub use logsource::Logsource;
 pub use tags::Tags;
 
@@ -66,6 +76,10 @@ pub struct Rule {
     /// Unique rule identifier
     pub id: String,
 
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Rule name, used by correlation rules to reference this rule
+    pub name: Option<String>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     /// Severity level
     pub level: Option<String>,
@@ -85,9 +99,18 @@ pub struct Rule {
     /// Log source configuration
     pub logsource: Logsource,
 
+    #[serde(default)]
     /// Detection rules and conditions
     pub detection: Detection,
 
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Correlation over other rules' matches, present on correlation rules only
+    pub correlation: Option<Correlation>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Filter applied to other rules, present on filter documents only
+    pub filter: Option<Filter>,
+
     #[serde(default)]
     /// Rule tags for categorization
     pub tags: Vec<String>,
@@ -106,6 +129,21 @@ impl Rule {
     pub fn has_tags(&self, tags: &[String]) -> bool {
         Tags::from(self.tags.clone()).has_all(tags)
     }
+
+    /// Check if this is a correlation rule rather than a detection rule
+    pub fn is_correlation(&self) -> bool {
+        self.correlation.is_some()
+    }
+
+    /// Check if this is a filter document rather than a detection rule
+    pub fn is_filter(&self) -> bool {
+        self.filter.is_some()
+    }
+
+    /// Check if the reference used by a correlation rule or filter points to this rule
+    pub fn is_referenced_by(&self, reference: &str) -> bool {
+        self.id == reference || self.name.as_deref() == Some(reference)
+    }
 }
 
 /// RuleHandle is a meta object containing all fields from raw yaml, but is enhanced to also
@@ -120,6 +158,10 @@ pub struct RuleHandle {
     pub multipart: bool,
     /// Whether to preserve whitespace in patterns
     pub no_collapse_ws: bool,
+    /// Placeholder definitions for `|expand` fields
+    pub placeholders: Option<Arc<PlaceholderRegistry>>,
+    /// Whether to reject malformed detections (see [`crate::parser::StrictViolation`])
+    pub strict: bool,
 }
 
 impl RuleHandle {
@@ -130,6 +172,8 @@ impl RuleHandle {
             path,
             multipart: false,
             no_collapse_ws: false,
+            placeholders: None,
+            strict: false,
         }
     }
 
@@ -144,6 +188,18 @@ impl RuleHandle {
         self.no_collapse_ws = no_collapse_ws;
         self
     }
+
+    /// Set the placeholder definitions used to resolve `|expand` fields
+    pub fn with_placeholders(mut self, placeholders: Arc<PlaceholderRegistry>) -> Self {
+        self.placeholders = Some(placeholders);
+        self
+    }
+
+    /// Set whether to reject malformed detections
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
 }
 
 /// Parse a Rule from YAML data with validation
@@ -153,6 +209,23 @@ pub fn rule_from_yaml(data: &[u8]) -> Result<Rule> {
     Ok(rule)
 }
 
+/// Parse every Rule from a possibly multi-document YAML file
+///
+/// Single-document files yield one rule. Documents in multi-document files are
+/// combined according to their `action` (see [`multipart`]), and each resulting
+/// rule is validated. Rules inherit an `id` set in a global document, so
+/// several rules of a file may share one id.
+pub fn rules_from_yaml(data: &[u8]) -> Result<Vec<Rule>> {
+    multipart::expand_documents(data)?
+        .into_iter()
+        .map(|document| {
+            let rule: Rule = serde_yaml::from_value(document)?;
+            validate_rule(&rule)?;
+            Ok(rule)
+        })
+        .collect()
+}
+
 /// Validate that a rule meets the minimum requirements
 fn validate_rule(rule: &Rule) -> Result<()> {
     // Validate title is not empty
@@ -176,6 +249,14 @@ fn validate_rule(rule: &Rule) -> Result<()> {
         )));
     }
 
+    // Correlation rules and filters have no detection of their own
+    if let Some(correlation) = &rule.correlation {
+        return correlation.validate();
+    }
+    if let Some(filter) = &rule.filter {
+        return filter.validate(&rule.logsource);
+    }
+
     // Validate detection has a condition
     if rule.detection.condition().is_none() {
         return Err(SigmaError::MissingCondition);
@@ -209,16 +290,9 @@ fn validate_rule(rule: &Rule) -> Result<()> {
     Ok(())
 }
 
-/// Check if rule data is multipart (contains document separator not at start)
+/// Check if rule data is multipart, i.e. expands to more than one rule document
 pub fn is_multipart(data: &[u8]) -> bool {
-    // Check if data starts with "---"
-    let starts_with_separator = data.starts_with(b"---");
-
-    // Check if data contains "---" anywhere
-    let contains_separator = data.windows(3).any(|window| window == b"---");
-
-    // Multipart if it contains separator but doesn't start with it
-    !starts_with_separator && contains_separator
+    multipart::expand_documents(data).is_ok_and(|documents| documents.len() > 1)
 }
 
 #[cfg(test)]
@@ -271,6 +345,84 @@ falsepositives:
         assert!(!is_multipart(no_separator));
     }
 
+    #[test]
+    fn test_rules_from_yaml() {
+        let yaml = r#"
+action: global
+title: Whoami Execution
+logsource:
+  category: process_creation
+  product: windows
+detection:
+  condition: selection
+level: medium
+---
+id: 12345678-1234-1234-1234-123456789012
+detection:
+  selection:
+    Image|endswith: '\whoami.exe'
+---
+action: repeat
+id: 12345678-1234-1234-1234-123456789013
+logsource:
+  product: linux
+detection:
+  selection:
+    Image|endswith: '/whoami'
+        "#;
+
+        let rules = rules_from_yaml(yaml.as_bytes()).expect("Failed to parse multipart YAML");
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].title, "Whoami Execution");
+        assert_eq!(rules[1].id, "12345678-1234-1234-1234-123456789013");
+        assert_eq!(rules[1].level, Some("medium".to_string()));
+        assert_eq!(rules[1].logsource.product.as_deref(), Some("linux"));
+        assert_eq!(
+            rules[1].logsource.category.as_deref(),
+            Some("process_creation")
+        );
+        assert_eq!(
+            rules[1].detection.get("selection"),
+            Some(&serde_json::json!({"Image|endswith": "/whoami"}))
+        );
+        assert_eq!(rules[1].detection.condition(), Some("selection"));
+
+        let single = r#"
+title: Single
+id: 12345678-1234-1234-1234-123456789012
+detection:
+  selection:
+    EventID: 1
+  condition: selection
+        "#;
+        let single = rules_from_yaml(single.as_bytes()).expect("Failed to parse single document");
+        assert_eq!(single.len(), 1);
+
+        // A document that only inherits the global header is not a valid rule
+        let incomplete = "action: global\ntitle: T\n---\nlevel: low\n";
+        assert!(rules_from_yaml(incomplete.as_bytes()).is_err());
+
+        // A repeated rule without its own id shares the previous rule's id
+        let inherited_id = r#"
+title: Whoami
+id: 12345678-1234-1234-1234-123456789012
+detection:
+  selection:
+    Image|endswith: '\whoami.exe'
+  condition: selection
+---
+action: repeat
+detection:
+  selection:
+    Image|endswith: '/whoami'
+        "#;
+        let rules = rules_from_yaml(inherited_id.as_bytes()).unwrap();
+        assert_eq!(rules.len(), 2);
+        assert!(rules
+            .iter()
+            .all(|rule| rule.id == "12345678-1234-1234-1234-123456789012"));
+    }
+
     #[test]
     fn test_rule_validation_empty_title() {
         let yaml = r#"
@@ -368,4 +520,100 @@ detection:
         let result2 = rule_from_yaml(yaml2.as_bytes());
         assert!(result2.is_ok());
     }
+
+    #[test]
+    fn test_correlation_rule_from_yaml() {
+        let yaml = r#"
+title: Many Failed Logons From Single Source
+id: 12345678-1234-1234-1234-123456789020
+correlation:
+  type: event_count
+  rules:
+    - failed_logon
+  group-by:
+    - SourceIp
+  timespan: 5m
+  condition:
+    gte: 10
+level: high
+        "#;
+
+        let rule = rule_from_yaml(yaml.as_bytes()).unwrap();
+        assert!(rule.is_correlation());
+        assert!(rule.detection.condition().is_none());
+
+        let correlation = rule.correlation.unwrap();
+        assert_eq!(correlation.correlation_type, CorrelationType::EventCount);
+        assert_eq!(correlation.rules, vec!["failed_logon"]);
+        assert_eq!(correlation.condition.unwrap().gte, Some(10.0));
+
+        // value_count needs the field to count
+        let yaml = r#"
+title: Invalid Correlation
+id: 12345678-1234-1234-1234-123456789021
+correlation:
+  type: value_count
+  rules: [failed_logon]
+  timespan: 5m
+  condition:
+    gte: 10
+        "#;
+        assert!(rule_from_yaml(yaml.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_filter_rule_from_yaml() {
+        let yaml = r#"
+title: Exclude Domain Controllers
+id: 12345678-1234-1234-1234-123456789030
+logsource:
+  product: windows
+filter:
+  rules:
+    - 12345678-1234-1234-1234-123456789012
+    - whoami_execution
+  selection:
+    ComputerName|startswith: 'DC-'
+  condition: not selection
+        "#;
+
+        let rule = rule_from_yaml(yaml.as_bytes()).unwrap();
+        assert!(rule.is_filter());
+        assert!(rule.detection.condition().is_none());
+
+        let filter = rule.filter.as_ref().unwrap();
+        assert_eq!(filter.rules.len(), 2);
+        assert_eq!(filter.detection.condition(), Some("not selection"));
+        assert!(filter.detection.get("rules").is_none());
+
+        let mut target = rule_from_yaml(
+            br#"
+title: Whoami
+id: 12345678-1234-1234-1234-123456789031
+name: whoami_execution
+logsource:
+  product: windows
+  category: process_creation
+detection:
+  selection:
+    Image|endswith: '\whoami.exe'
+  condition: selection
+"#,
+        )
+        .unwrap();
+        assert!(filter.applies_to(&rule.logsource, &target));
+        target.name = None;
+        assert!(!filter.applies_to(&rule.logsource, &target));
+
+        // Filters need a target
+        let yaml = r#"
+title: Untargeted Filter
+id: 12345678-1234-1234-1234-123456789032
+filter:
+  selection:
+    User: admin
+  condition: not selection
+        "#;
+        assert!(rule_from_yaml(yaml.as_bytes()).is_err());
+    }
 }
@@ -0,0 +1,201 @@
+//! Multi-document rule files using `action: global`, `reset` and `repeat`
+//!
+//! Legacy Sigma collections put several YAML documents in one file. A
+//! document's `action` decides how it combines with the others:
+//!
+//! - `global`: merged into every following rule document until a `reset`
+//! - `reset`: discards the global document collected so far
+//! - `repeat`: a new rule built from the previous rule with this document merged
+//!   in
+//! - no action: a rule, built from the global document with this document merged in
+//!
+//! Merging is recursive for mappings; any other value in the later document
+//! replaces the earlier one. Documents that set no `id` of their own keep the
+//! inherited one, so the rules of a file may share an id.
+
+use crate::error::{Result, SigmaError};
+use serde::Deserialize;
+use serde_yaml::{Mapping, Value};
+
+/// Key selecting how a document combines with the rest of the file
+const ACTION_KEY: &str = "action";
+
+/// Expand the documents of a rule file into one merged YAML mapping per rule
+pub fn expand_documents(data: &[u8]) -> Result<Vec<Value>> {
+    let mut global = Value::Mapping(Mapping::new());
+    let mut previous: Option<Value> = None;
+    let mut rules = Vec::new();
+
+    for (index, document) in serde_yaml::Deserializer::from_slice(data).enumerate() {
+        let mut document = match Value::deserialize(document)? {
+            // Empty documents, e.g. after a trailing separator
+            Value::Null => continue,
+            Value::Mapping(mapping) => mapping,
+            _ => {
+                return Err(SigmaError::InvalidRule(format!(
+                    "Document {} of rule file is not a mapping",
+                    index + 1
+                )))
+            }
+        };
+
+        let action = match document.remove(ACTION_KEY) {
+            None => None,
+            Some(Value::String(action)) => Some(action),
+            Some(other) => {
+                return Err(SigmaError::InvalidRule(format!(
+                    "Invalid action in document {}: {:?}",
+                    index + 1,
+                    other
+                )))
+            }
+        };
+
+        match action.as_deref() {
+            Some("global") => merge(&mut global, Value::Mapping(document)),
+            Some("reset") => global = Value::Mapping(Mapping::new()),
+            Some("repeat") => {
+                let mut rule = previous.clone().ok_or_else(|| {
+                    SigmaError::InvalidRule(format!(
+                        "Document {} repeats a rule but no rule precedes it",
+                        index + 1
+                    ))
+                })?;
+                merge(&mut rule, Value::Mapping(document));
+                rules.push(rule.clone());
+                previous = Some(rule);
+            }
+            None => {
+                let mut rule = global.clone();
+                merge(&mut rule, Value::Mapping(document));
+                rules.push(rule.clone());
+                previous = Some(rule);
+            }
+            Some(other) => {
+                return Err(SigmaError::InvalidRule(format!(
+                    "Unknown action '{}' in document {}",
+                    other,
+                    index + 1
+                )))
+            }
+        }
+    }
+
+    Ok(rules)
+}
+
+/// Recursively merge `overlay` into `base`
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(yaml: &str) -> Vec<Value> {
+        expand_documents(yaml.as_bytes()).expect("documents should expand")
+    }
+
+    #[test]
+    fn test_global_merge() {
+        let rules = expand(
+            r#"
+action: global
+title: Shared
+logsource:
+  product: windows
+detection:
+  condition: selection
+---
+logsource:
+  category: process_creation
+detection:
+  selection:
+    Image|endswith: '\cmd.exe'
+---
+title: Override
+detection:
+  selection:
+    EventID: 4688
+"#,
+        );
+
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0]["title"], Value::from("Shared"));
+        assert_eq!(rules[0]["logsource"]["product"], Value::from("windows"));
+        assert_eq!(
+            rules[0]["logsource"]["category"],
+            Value::from("process_creation")
+        );
+        assert_eq!(rules[0]["detection"]["condition"], Value::from("selection"));
+        assert!(rules[0].get(ACTION_KEY).is_none());
+
+        assert_eq!(rules[1]["title"], Value::from("Override"));
+        assert!(rules[1]["logsource"].get("category").is_none());
+        assert_eq!(
+            rules[1]["detection"]["selection"]["EventID"],
+            Value::from(4688)
+        );
+    }
+
+    #[test]
+    fn test_reset_and_repeat() {
+        let rules = expand(
+            r#"
+action: global
+tags: [attack.execution]
+---
+title: First
+detection:
+  selection:
+    EventID: 1
+  condition: selection
+---
+action: repeat
+title: Second
+detection:
+  selection:
+    Image: evil.exe
+---
+action: reset
+---
+title: Third
+"#,
+        );
+
+        assert_eq!(rules.len(), 3);
+        assert_eq!(rules[1]["title"], Value::from("Second"));
+        assert_eq!(rules[1]["tags"][0], Value::from("attack.execution"));
+        assert_eq!(
+            rules[1]["detection"]["selection"]["EventID"],
+            Value::from(1)
+        );
+        assert_eq!(
+            rules[1]["detection"]["selection"]["Image"],
+            Value::from("evil.exe")
+        );
+        assert_eq!(rules[1]["detection"]["condition"], Value::from("selection"));
+        assert!(rules[2].get("tags").is_none());
+    }
+
+    #[test]
+    fn test_invalid_actions() {
+        assert!(expand_documents(b"action: repeat\ntitle: Orphan\n").is_err());
+        assert!(expand_documents(b"action: merge\ntitle: Unknown\n").is_err());
+        assert!(expand_documents(b"- not\n- a mapping\n").is_err());
+        assert_eq!(expand("---\ntitle: Only\n---\n").len(), 1);
+    }
+}
@@ -0,0 +1,227 @@
+//! Event-to-logsource classification for routing events to relevant rules
+//!
+//! A [`LogsourceClassifier`] holds an ordered list of mappings, each a set of
+//! field conditions and the logsource events satisfying them belong to:
+//!
+//! ```yaml
+//! - product: windows
+//!   category: process_creation
+//!   service: sysmon
+//!   when:
+//!     Channel: Microsoft-Windows-Sysmon/Operational
+//!     EventID: 1
+//! - product: windows
+//!   service: security
+//!   when:
+//!     Channel: Security
+//! ```
+//!
+//! The first mapping whose conditions all hold classifies the event.
+
+use crate::error::{Result, SigmaError};
+use crate::event::{Selector, Value};
+use crate::rule::Logsource;
+use serde::Deserialize;
+use std::path::Path;
+
+/// Classifies events into logsources from their field values
+#[derive(Debug, Clone, Default)]
+pub struct LogsourceClassifier {
+    mappings: Vec<LogsourceMapping>,
+}
+
+/// A logsource and the conditions an event must meet to belong to it
+#[derive(Debug, Clone)]
+struct LogsourceMapping {
+    logsource: Logsource,
+    /// Field names and their accepted values
+    conditions: Vec<(String, Vec<String>)>,
+}
+
+/// A mapping as written in a classifier file
+#[derive(Deserialize)]
+struct MappingDefinition {
+    #[serde(flatten)]
+    logsource: Logsource,
+    when: serde_json::Map<String, serde_json::Value>,
+}
+
+impl LogsourceClassifier {
+    /// Create a classifier without mappings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a mapping; events whose fields each equal one of the listed
+    /// values are classified as `logsource`
+    pub fn add_mapping<I, K, V, S>(&mut self, logsource: Logsource, conditions: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let conditions = conditions
+            .into_iter()
+            .map(|(field, values)| (field.into(), values.into_iter().map(Into::into).collect()))
+            .collect();
+
+        self.mappings.push(LogsourceMapping {
+            logsource,
+            conditions,
+        });
+    }
+
+    /// Number of mappings
+    pub fn len(&self) -> usize {
+        self.mappings.len()
+    }
+
+    /// Whether there are no mappings
+    pub fn is_empty(&self) -> bool {
+        self.mappings.is_empty()
+    }
+
+    /// Load mappings from a YAML list
+    pub fn from_yaml_str(data: &str) -> Result<Self> {
+        let definitions: Vec<MappingDefinition> = serde_yaml::from_str(data)?;
+        let mut classifier = Self::new();
+
+        for definition in definitions {
+            if definition.when.is_empty() {
+                return Err(SigmaError::Configuration(
+                    "Logsource mapping without conditions would match every event".to_string(),
+                ));
+            }
+
+            let mut conditions = Vec::with_capacity(definition.when.len());
+            for (field, value) in definition.when {
+                let values = match value {
+                    serde_json::Value::Array(values) => values,
+                    value => vec![value],
+                };
+                let values = values
+                    .into_iter()
+                    .map(|value| {
+                        scalar_to_string(&value).ok_or_else(|| {
+                            SigmaError::Configuration(format!(
+                                "Logsource condition on '{}' must be a scalar or list of scalars",
+                                field
+                            ))
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                conditions.push((field, values));
+            }
+
+            classifier.add_mapping(definition.logsource, conditions);
+        }
+
+        Ok(classifier)
+    }
+
+    /// Load mappings from a YAML file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_yaml_str(&std::fs::read_to_string(path)?)
+    }
+
+    /// Classify an event, returning the logsource of the first matching mapping
+    ///
+    /// String comparisons ignore ASCII case, as Windows channel and provider
+    /// names do.
+    pub fn classify<E: Selector + ?Sized>(&self, event: &E) -> Option<&Logsource> {
+        self.classify_index(event)
+            .map(|index| &self.mappings[index].logsource)
+    }
+
+    /// Position of the mapping classifying an event, as in [`logsources`](Self::logsources)
+    pub(crate) fn classify_index<E: Selector + ?Sized>(&self, event: &E) -> Option<usize> {
+        self.mappings.iter().position(|mapping| {
+            mapping.conditions.iter().all(|(field, accepted)| {
+                let (value, _) = event.select(field);
+                value
+                    .as_ref()
+                    .and_then(event_value_to_string)
+                    .is_some_and(|v| {
+                        accepted
+                            .iter()
+                            .any(|expected| expected.eq_ignore_ascii_case(&v))
+                    })
+            })
+        })
+    }
+
+    /// Logsources of the mappings, in order
+    pub(crate) fn logsources(&self) -> impl Iterator<Item = &Logsource> {
+        self.mappings.iter().map(|mapping| &mapping.logsource)
+    }
+}
+
+fn scalar_to_string(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        serde_json::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn event_value_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.to_string()),
+        Value::Integer(i) => Some(i.to_string()),
+        Value::Float(f) => Some(f.to_string()),
+        Value::Boolean(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DynamicEvent;
+    use serde_json::json;
+
+    #[test]
+    fn test_classify() {
+        let classifier = LogsourceClassifier::from_yaml_str(
+            r#"
+- product: windows
+  category: process_creation
+  when:
+    Channel: Microsoft-Windows-Sysmon/Operational
+    EventID: 1
+- product: windows
+  service: security
+  when:
+    Channel: Security
+    EventID: [4624, 4625]
+"#,
+        )
+        .unwrap();
+        assert_eq!(classifier.len(), 2);
+
+        let sysmon = DynamicEvent::new(json!({
+            "Channel": "microsoft-windows-sysmon/operational",
+            "EventID": 1
+        }));
+        let logsource = classifier.classify(&sysmon).unwrap();
+        assert_eq!(logsource.category.as_deref(), Some("process_creation"));
+
+        let logon = DynamicEvent::new(json!({"Channel": "Security", "EventID": "4625"}));
+        let logsource = classifier.classify(&logon).unwrap();
+        assert_eq!(logsource.service.as_deref(), Some("security"));
+
+        let unknown = DynamicEvent::new(json!({"Channel": "Security", "EventID": 4688}));
+        assert!(classifier.classify(&unknown).is_none());
+    }
+
+    #[test]
+    fn test_invalid_definitions() {
+        assert!(LogsourceClassifier::from_yaml_str("- product: windows\n  when: {}\n").is_err());
+        assert!(
+            LogsourceClassifier::from_yaml_str("- product: windows\n  when:\n    A: {b: 1}\n")
+                .is_err()
+        );
+    }
+}
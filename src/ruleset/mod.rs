@@ -7,20 +7,24 @@ use anyhow::Result;
 use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
-use tokio::sync::{RwLock, Semaphore};
+use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
 
 use crate::{
-    ast::MatchResult,
-    event::DynamicEvent,
-    parser::ParseError,
-    rule::{rule_from_yaml, Rule, RuleHandle},
+    aggregation::AggregationConfig,
+    ast::{Explanation, MatchResult},
+    event::{DynamicEvent, Event},
+    parser::{ParseError, PlaceholderRegistry},
+    pipeline::ProcessingPipeline,
+    rule::{rules_from_yaml, Rule, RuleHandle},
     tree::{build_tree, Tree},
     Result as SigmaResult, SigmaEngineBuilder, SigmaError,
 };
 
-/// Maximum number of concurrent rule evaluations to prevent resource exhaustion
-const MAX_CONCURRENT_EVALUATIONS: usize = 100;
+/// Event-to-logsource classification for rule routing
+pub mod classifier;
+
+pub use classifier::LogsourceClassifier;
 
 /// Maximum number of rules to load from a single directory
 const MAX_RULES_PER_DIR: usize = 10000;
@@ -30,8 +34,24 @@ const MAX_RULES_PER_DIR: usize = 10000;
 pub struct RuleSet {
     /// Compiled rules with their detection trees
     rules: Vec<CompiledRule>,
-    /// Index of rules by ID for fast lookup
-    rule_index: HashMap<String, usize>,
+    /// Index of rules by ID for fast lookup; rules of a multipart file may share one
+    rule_index: HashMap<String, Vec<usize>>,
+    /// Indices of the rules covering each classifier mapping's logsource, in load order
+    routes: Vec<Vec<usize>>,
+    /// Classifier routing events to the rules of their logsource
+    classifier: Option<Arc<LogsourceClassifier>>,
+    /// Correlation rules, evaluated over base-rule matches by a `CorrelationEngine`
+    correlations: Vec<Arc<Rule>>,
+    /// Filter documents, ANDed into the trees of the rules they target
+    filters: Vec<CompiledFilter>,
+    /// Placeholder definitions for `|expand` fields in rules added later
+    placeholders: Option<Arc<PlaceholderRegistry>>,
+    /// Processing pipeline applied to rules added later
+    pipeline: Option<Arc<ProcessingPipeline>>,
+    /// Whether rules added later are validated strictly
+    strict: bool,
+    /// Limits on the aggregation state of rules added later
+    aggregation_config: AggregationConfig,
     /// Metadata about the ruleset
     metadata: RuleSetMetadata,
 }
@@ -43,10 +63,74 @@ struct CompiledRule {
     rule: Arc<Rule>,
     /// The compiled detection tree
     tree: Arc<Tree>,
+    /// Rule ID reported in matches, or "unknown" for rules without one
+    id: Arc<str>,
+    /// Rule title reported in matches
+    title: Arc<str>,
     /// Whether this rule is enabled
     enabled: bool,
 }
 
+impl CompiledRule {
+    /// Evaluate the rule's tree against an event, explaining matches if asked
+    ///
+    /// Without explanations only a matching rule yields a [`RuleMatch`], so
+    /// rules that do not match cost no allocation.
+    fn evaluate(&self, event: &dyn Event, explain: bool) -> Option<RuleMatch> {
+        let rule_start = std::time::Instant::now();
+        let (matched, applicable, explanation) = if explain {
+            self.tree.explain_event(event)
+        } else {
+            let (matched, applicable) = self.tree.match_event_sync(event);
+            (matched, applicable, None)
+        };
+        let evaluation_time = rule_start.elapsed();
+        if !matched && !explain {
+            return None;
+        }
+
+        Some(RuleMatch {
+            rule_id: self.id.to_string(),
+            rule_title: self.title.to_string(),
+            matched,
+            match_result: MatchResult {
+                matched,
+                applicable,
+            },
+            evaluation_time,
+            explanation,
+        })
+    }
+}
+
+/// One thread's share of an event's evaluation in [`RuleSet::evaluate_sharded`]
+#[derive(Default)]
+struct ShardResult {
+    /// Matches keyed by rule index
+    matches: Vec<(usize, RuleMatch)>,
+    rules_evaluated: usize,
+    evaluation_time: std::time::Duration,
+}
+
+/// A compiled filter document
+#[derive(Debug)]
+struct CompiledFilter {
+    /// The filter document
+    rule: Arc<Rule>,
+    /// The filter's condition, ANDed into targeted rules
+    root: Arc<dyn crate::ast::SyncBranch>,
+}
+
+impl CompiledFilter {
+    /// Check whether this filter targets the rule
+    fn applies_to(&self, rule: &Rule) -> bool {
+        self.rule
+            .filter
+            .as_ref()
+            .is_some_and(|filter| filter.applies_to(&self.rule.logsource, rule))
+    }
+}
+
 /// Metadata about the ruleset
 #[derive(Debug, Clone)]
 pub struct RuleSetMetadata {
@@ -64,7 +148,8 @@ pub struct RuleSetMetadata {
 /// Result of evaluating a ruleset against an event
 #[derive(Debug, Clone)]
 pub struct RuleSetResult {
-    /// Matches from all rules
+    /// Matches of the rules that matched, or of every evaluated rule when
+    /// explaining
     pub matches: Vec<RuleMatch>,
     /// Total number of rules evaluated
     pub rules_evaluated: usize,
@@ -85,6 +170,8 @@ pub struct RuleMatch {
     pub match_result: MatchResult,
     /// Time taken to evaluate this rule
     pub evaluation_time: std::time::Duration,
+    /// Why the rule matched, when evaluated with explanations
+    pub explanation: Option<Explanation>,
 }
 
 impl RuleSet {
@@ -93,6 +180,14 @@ impl RuleSet {
         Self {
             rules: Vec::new(),
             rule_index: HashMap::new(),
+            routes: Vec::new(),
+            classifier: None,
+            correlations: Vec::new(),
+            filters: Vec::new(),
+            placeholders: None,
+            pipeline: None,
+            strict: false,
+            aggregation_config: AggregationConfig::default(),
             metadata: RuleSetMetadata {
                 total_rules: 0,
                 enabled_rules: 0,
@@ -105,6 +200,17 @@ impl RuleSet {
     /// Load rules from the specified directories
     pub async fn load(builder: &SigmaEngineBuilder) -> SigmaResult<Self> {
         let mut ruleset = Self::new();
+        if let Some(placeholders) = &builder.placeholders {
+            ruleset.set_placeholders(placeholders.clone());
+        }
+        if let Some(pipeline) = &builder.pipeline {
+            ruleset.set_pipeline(pipeline.clone());
+        }
+        if let Some(classifier) = &builder.classifier {
+            ruleset.set_classifier(classifier.clone());
+        }
+        ruleset.set_strict(builder.strict);
+        ruleset.set_aggregation_config(builder.aggregation_config.clone());
 
         for dir in &builder.rule_dirs {
             ruleset
@@ -122,6 +228,54 @@ impl RuleSet {
         Ok(ruleset)
     }
 
+    /// Set the placeholder definitions used to resolve `|expand` fields
+    ///
+    /// Only rules added after this call are affected. Without definitions a
+    /// rule using a placeholder fails to load.
+    pub fn set_placeholders(&mut self, placeholders: Arc<PlaceholderRegistry>) {
+        self.placeholders = Some(placeholders);
+    }
+
+    /// Set the processing pipeline applied to rule detections before compilation
+    ///
+    /// Only rules added after this call are affected.
+    pub fn set_pipeline(&mut self, pipeline: Arc<ProcessingPipeline>) {
+        self.pipeline = Some(pipeline);
+    }
+
+    /// Set the classifier used to evaluate events only against rules for their logsource
+    ///
+    /// Events the classifier cannot place are evaluated against every rule.
+    pub fn set_classifier(&mut self, classifier: Arc<LogsourceClassifier>) {
+        self.routes = classifier
+            .logsources()
+            .map(|logsource| {
+                self.rules
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, compiled)| compiled.rule.logsource.covers(logsource))
+                    .map(|(index, _)| index)
+                    .collect()
+            })
+            .collect();
+        self.classifier = Some(classifier);
+    }
+
+    /// Set whether to reject rules with unknown modifiers, unused or missing
+    /// search identifiers, or empty value lists
+    ///
+    /// Only rules added after this call are affected.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Set the limits on the state kept by aggregation conditions
+    ///
+    /// Only rules added after this call are affected.
+    pub fn set_aggregation_config(&mut self, config: AggregationConfig) {
+        self.aggregation_config = config;
+    }
+
     /// Load rules from a directory
     pub async fn load_directory(&mut self, dir: &str) -> Result<()> {
         self.load_from_directory(dir, false)
@@ -210,26 +364,47 @@ impl RuleSet {
                 SigmaError::Parse(format!("Failed to read file {}: {}", path.display(), e))
             })?;
 
-        let rule = rule_from_yaml(&contents)?;
-        self.add_rule(rule).await?;
+        // Multi-document files expand to one rule per rule document
+        let rules = rules_from_yaml(&contents)?;
+        let multipart = rules.len() > 1;
+        for rule in rules {
+            self.insert_rule(rule, multipart).await?;
+        }
 
         Ok(())
     }
 
     /// Add a rule to the ruleset
+    ///
+    /// Filter documents are attached to every loaded rule they target, now
+    /// and as matching rules are added later.
     pub async fn add_rule(&mut self, rule: Rule) -> SigmaResult<()> {
+        self.insert_rule(rule, false).await
+    }
+
+    /// Add a rule, marking its handle as part of a multipart file
+    async fn insert_rule(&mut self, rule: Rule, multipart: bool) -> SigmaResult<()> {
+        // Correlation rules have no detection tree; they consume other rules' matches
+        if rule.is_correlation() {
+            self.correlations.push(Arc::new(rule));
+            return Ok(());
+        }
+
+        if rule.is_filter() {
+            return self.add_filter(rule).await;
+        }
+
         // Wrap rule in Arc for efficient sharing
         let rule_arc = Arc::new(rule);
 
-        // Create a rule handle with clone for tree building
+        // Build the detection tree
         // Note: RuleHandle requires ownership of Rule, not Arc<Rule>, so we must clone here.
         // The Arc is still used to share the rule with the CompiledRule struct below.
-        let rule_handle = RuleHandle::new((*rule_arc).clone(), std::path::PathBuf::from("ruleset"));
+        let mut tree = self.compile((*rule_arc).clone(), multipart).await?;
 
-        // Build the detection tree
-        let tree = build_tree(rule_handle)
-            .await
-            .map_err(|e: ParseError| SigmaError::Parse(e.to_string()))?;
+        for filter in self.filters.iter().filter(|f| f.applies_to(&rule_arc)) {
+            tree = tree.with_filter(Arc::clone(&filter.root));
+        }
 
         // Store the compiled rule
         let index = self.rules.len();
@@ -239,8 +414,22 @@ impl RuleSet {
             rule_arc.id.clone()
         };
 
-        self.rule_index.insert(rule_id.clone(), index);
+        self.rule_index.entry(rule_id).or_default().push(index);
+        if let Some(classifier) = &self.classifier {
+            for (route, logsource) in self.routes.iter_mut().zip(classifier.logsources()) {
+                if rule_arc.logsource.covers(logsource) {
+                    route.push(index);
+                }
+            }
+        }
+        let id: Arc<str> = if rule_arc.id.is_empty() {
+            Arc::from("unknown")
+        } else {
+            Arc::from(rule_arc.id.as_str())
+        };
         self.rules.push(CompiledRule {
+            id,
+            title: Arc::from(rule_arc.title.as_str()),
             rule: rule_arc,
             tree: Arc::new(tree),
             enabled: true,
@@ -252,6 +441,59 @@ impl RuleSet {
         Ok(())
     }
 
+    /// Compile a filter document and attach it to the rules it targets
+    async fn add_filter(&mut self, rule: Rule) -> SigmaResult<()> {
+        let Some(filter) = rule.filter.clone() else {
+            return Ok(());
+        };
+
+        // The filter's own selections and condition form its tree
+        let mut filter_rule = rule.clone();
+        filter_rule.detection = filter.detection;
+        let root = self.compile(filter_rule, false).await?.root;
+
+        let filter = CompiledFilter {
+            rule: Arc::new(rule),
+            root,
+        };
+        for compiled in self.rules.iter_mut() {
+            if filter.applies_to(&compiled.rule) {
+                compiled.tree = Arc::new(
+                    (*compiled.tree)
+                        .clone()
+                        .with_filter(Arc::clone(&filter.root)),
+                );
+            }
+        }
+
+        self.filters.push(filter);
+        Ok(())
+    }
+
+    /// Build the detection tree for a rule with the ruleset's settings
+    async fn compile(&self, mut rule: Rule, multipart: bool) -> SigmaResult<Tree> {
+        if let Some(pipeline) = &self.pipeline {
+            rule.detection = pipeline.apply(&rule)?;
+        }
+
+        let mut rule_handle = RuleHandle::new(rule, std::path::PathBuf::from("ruleset"))
+            .with_multipart(multipart)
+            .with_strict(self.strict);
+        if let Some(placeholders) = &self.placeholders {
+            rule_handle = rule_handle.with_placeholders(placeholders.clone());
+        }
+
+        build_tree(rule_handle)
+            .await
+            .map(|tree| tree.with_aggregation_config(self.aggregation_config.clone()))
+            .map_err(|e: ParseError| match e {
+                ParseError::StrictValidation { rule_id, violation } => {
+                    SigmaError::StrictValidation { rule_id, violation }
+                }
+                e => SigmaError::Parse(e.to_string()),
+            })
+    }
+
     /// Get the number of rules in the set
     pub fn len(&self) -> usize {
         self.metadata.total_rules
@@ -263,109 +505,211 @@ impl RuleSet {
     }
 
     /// Evaluate all rules against an event
+    ///
+    /// Kept for compatibility; delegates to [`evaluate_sync`](Self::evaluate_sync).
     pub async fn evaluate(&self, event: &DynamicEvent) -> SigmaResult<RuleSetResult> {
+        Ok(self.evaluate_sync(event))
+    }
+
+    /// Evaluate the rules for an event's logsource sequentially on the calling thread
+    ///
+    /// The event is borrowed, not cloned, and no tasks are spawned.
+    pub fn evaluate_sync(&self, event: &dyn Event) -> RuleSetResult {
+        self.evaluate_with(event, false)
+    }
+
+    /// Evaluate all rules against an event, explaining each match
+    pub async fn explain(&self, event: &DynamicEvent) -> SigmaResult<RuleSetResult> {
+        Ok(self.explain_sync(event))
+    }
+
+    /// Evaluate like [`evaluate_sync`](Self::evaluate_sync), recording for each
+    /// matching rule the satisfied condition, the matched detection
+    /// identifiers and the field values that matched
+    pub fn explain_sync(&self, event: &dyn Event) -> RuleSetResult {
+        self.evaluate_with(event, true)
+    }
+
+    fn evaluate_with(&self, event: &dyn Event, explain: bool) -> RuleSetResult {
         let start = std::time::Instant::now();
+        let route = self.route(event);
+
         let mut matches = Vec::new();
         let mut rules_evaluated = 0;
+        self.for_each_candidate(route, |_, compiled_rule| {
+            rules_evaluated += 1;
+            matches.extend(compiled_rule.evaluate(event, explain));
+        });
 
-        // Wrap event in Arc for efficient sharing across tasks
-        let event_arc = Arc::new(event.clone());
+        RuleSetResult {
+            rules_evaluated,
+            matches,
+            evaluation_time: start.elapsed(),
+        }
+    }
 
-        // Create semaphore to limit concurrent evaluations
-        let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_EVALUATIONS));
+    /// Evaluate a batch of events with the rules split across `shards` threads
+    ///
+    /// Every thread evaluates its share of the rules against each event in
+    /// batch order, so aggregation state sees events in the order given.
+    /// Results are returned per event with matches in rule load order; an
+    /// event's evaluation time is the sum of the time each thread spent on it.
+    pub fn evaluate_sharded<E: Event>(&self, events: &[E], shards: usize) -> Vec<RuleSetResult> {
+        let shards = shards.clamp(1, self.rules.len().max(1));
+        if shards == 1 {
+            return events.iter().map(|e| self.evaluate_sync(e)).collect();
+        }
 
-        // Evaluate rules in parallel with bounded concurrency
-        let tasks: Vec<_> = self
-            .rules
-            .iter()
-            .filter(|r| r.enabled)
-            .map(|compiled_rule| {
-                let event_ref = Arc::clone(&event_arc);
-                let tree = Arc::clone(&compiled_rule.tree);
-                let rule = Arc::clone(&compiled_rule.rule);
-                let semaphore = Arc::clone(&semaphore);
-
-                tokio::spawn(async move {
-                    // Acquire permit before evaluation
-                    let _permit = semaphore.acquire().await.map_err(|e| {
-                        SigmaError::Parse(format!("Failed to acquire semaphore: {}", e))
-                    })?;
-
-                    let rule_start = std::time::Instant::now();
-                    let (matched, applicable) = tree.match_event(&*event_ref).await;
-                    let evaluation_time = rule_start.elapsed();
-
-                    let match_result = MatchResult {
-                        matched,
-                        applicable,
-                    };
-
-                    Ok::<RuleMatch, SigmaError>(RuleMatch {
-                        rule_id: if rule.id.is_empty() {
-                            "unknown".to_string()
-                        } else {
-                            rule.id.clone()
-                        },
-                        rule_title: rule.title.clone(),
-                        matched,
-                        match_result,
-                        evaluation_time,
+        let routes: Vec<Option<&[usize]>> = events.iter().map(|e| self.route(e)).collect();
+
+        let shard_results: Vec<Vec<ShardResult>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..shards)
+                .map(|shard| {
+                    let routes = &routes;
+                    scope.spawn(move || {
+                        events
+                            .iter()
+                            .zip(routes)
+                            .map(|(event, route)| {
+                                let start = std::time::Instant::now();
+                                let mut result = ShardResult::default();
+                                self.for_each_candidate(*route, |index, rule| {
+                                    if index % shards == shard {
+                                        result.rules_evaluated += 1;
+                                        if let Some(m) = rule.evaluate(event, false) {
+                                            result.matches.push((index, m));
+                                        }
+                                    }
+                                });
+                                result.evaluation_time = start.elapsed();
+                                result
+                            })
+                            .collect()
                     })
                 })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                })
+                .collect()
+        });
+
+        let mut per_event: Vec<ShardResult> =
+            (0..events.len()).map(|_| ShardResult::default()).collect();
+        for shard in shard_results {
+            for (total, result) in per_event.iter_mut().zip(shard) {
+                total.rules_evaluated += result.rules_evaluated;
+                total.evaluation_time += result.evaluation_time;
+                total.matches.extend(result.matches);
+            }
+        }
+
+        per_event
+            .into_iter()
+            .map(|mut result| {
+                result.matches.sort_unstable_by_key(|(index, _)| *index);
+                RuleSetResult {
+                    matches: result.matches.into_iter().map(|(_, m)| m).collect(),
+                    rules_evaluated: result.rules_evaluated,
+                    evaluation_time: result.evaluation_time,
+                }
             })
-            .collect();
+            .collect()
+    }
 
-        // Collect results
-        for task in tasks {
-            match task.await {
-                Ok(result) => match result {
-                    Ok(rule_match) => {
-                        rules_evaluated += 1;
-                        matches.push(rule_match);
+    /// Call `f` with the index of each enabled rule that may apply to an event
+    /// routed to `route`, in load order
+    fn for_each_candidate<'a>(
+        &'a self,
+        route: Option<&[usize]>,
+        mut f: impl FnMut(usize, &'a CompiledRule),
+    ) {
+        match route {
+            Some(indices) => {
+                for &index in indices {
+                    let rule = &self.rules[index];
+                    if rule.enabled {
+                        f(index, rule);
                     }
-                    Err(e) => {
-                        warn!("Rule evaluation error: {}", e);
+                }
+            }
+            None => {
+                for (index, rule) in self.rules.iter().enumerate() {
+                    if rule.enabled {
+                        f(index, rule);
                     }
-                },
-                Err(e) => {
-                    warn!("Task join error: {}", e);
                 }
             }
         }
+    }
 
-        Ok(RuleSetResult {
-            matches,
-            rules_evaluated,
-            evaluation_time: start.elapsed(),
-        })
+    /// Indices of the rules that may apply to an event, in load order
+    ///
+    /// Returns `None` when there is no classifier or it cannot place the event,
+    /// in which case every rule is a candidate.
+    fn route(&self, event: &dyn Event) -> Option<&[usize]> {
+        let mapping = self.classifier.as_ref()?.classify_index(event)?;
+        let indices = &self.routes[mapping];
+
+        debug!(
+            "Routed event to {} of {} rules",
+            indices.len(),
+            self.rules.len()
+        );
+        Some(indices)
     }
 
-    /// Enable or disable a rule by ID
+    /// Enable or disable every rule with an ID
     pub fn set_rule_enabled(&mut self, rule_id: &str, enabled: bool) -> Result<()> {
-        if let Some(&index) = self.rule_index.get(rule_id) {
-            if let Some(rule) = self.rules.get_mut(index) {
-                // Only update counter if state actually changes
-                if rule.enabled != enabled {
-                    rule.enabled = enabled;
-                    if enabled {
-                        self.metadata.enabled_rules += 1;
-                    } else {
-                        self.metadata.enabled_rules -= 1;
-                    }
+        let Some(indices) = self.rule_index.get(rule_id) else {
+            return Err(anyhow::anyhow!("Rule not found: {}", rule_id));
+        };
+        for &index in indices {
+            let rule = self
+                .rules
+                .get_mut(index)
+                .ok_or_else(|| anyhow::anyhow!("Rule index out of bounds"))?;
+            // Only update counter if state actually changes
+            if rule.enabled != enabled {
+                rule.enabled = enabled;
+                if enabled {
+                    self.metadata.enabled_rules += 1;
+                } else {
+                    self.metadata.enabled_rules -= 1;
                 }
-                Ok(())
-            } else {
-                Err(anyhow::anyhow!("Rule index out of bounds"))
             }
-        } else {
-            Err(anyhow::anyhow!("Rule not found: {}", rule_id))
         }
+        Ok(())
     }
 
     /// Get rule metadata
     pub fn get_metadata(&self) -> &RuleSetMetadata {
         &self.metadata
     }
+
+    /// Get the correlation rules loaded into the set
+    pub fn correlation_rules(&self) -> &[Arc<Rule>] {
+        &self.correlations
+    }
+
+    /// Get the filter documents loaded into the set
+    pub fn filter_rules(&self) -> impl Iterator<Item = &Arc<Rule>> {
+        self.filters.iter().map(|filter| &filter.rule)
+    }
+
+    /// Resolve a correlation rule reference (rule name or id) to the rule ID
+    pub fn resolve_reference(&self, reference: &str) -> Option<&str> {
+        self.rules
+            .iter()
+            .map(|compiled| &compiled.rule)
+            .find(|rule| rule.is_referenced_by(reference))
+            .map(|rule| rule.id.as_str())
+    }
 }
 
 /// Thread-safe wrapper for RuleSet
@@ -409,6 +753,7 @@ impl ConcurrentRuleSet {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rule::{rule_from_yaml, Logsource};
     use serde_json::json;
 
     #[tokio::test]
@@ -486,8 +831,171 @@ mod tests {
 
         let result = ruleset.evaluate(&event).await?;
         assert_eq!(result.rules_evaluated, 1);
-        assert_eq!(result.matches.len(), 1);
+        assert!(result.matches.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rule_evaluation_with_aggregation() -> SigmaResult<()> {
+        let mut ruleset = RuleSet::new();
+
+        let rule_yaml = br#"
+        title: Failed Logon Burst
+        id: 12345678-1234-1234-1234-123456789010
+        detection:
+            selection:
+                EventID: 4625
+            timeframe: 5m
+            condition: selection | count() by SourceIp > 2
+        "#;
+
+        let rule = rule_from_yaml(rule_yaml)?;
+        ruleset.add_rule(rule).await?;
+
+        let failed_logon = |ip: &str| {
+            DynamicEvent::new(json!({
+                "EventID": 4625,
+                "SourceIp": ip
+            }))
+        };
+
+        // The first two failures from one source stay below the threshold
+        for _ in 0..2 {
+            let result = ruleset.evaluate(&failed_logon("10.0.0.1")).await?;
+            assert!(result.matches.is_empty());
+        }
+
+        // Failures from another source are counted separately
+        let result = ruleset.evaluate(&failed_logon("10.0.0.2")).await?;
+        assert!(result.matches.is_empty());
+
+        let result = ruleset.evaluate(&failed_logon("10.0.0.1")).await?;
+        assert!(result.matches[0].matched);
+
+        // Non-matching events never feed the aggregation
+        let event = DynamicEvent::new(json!({"EventID": 4624, "SourceIp": "10.0.0.2"}));
+        for _ in 0..3 {
+            let result = ruleset.evaluate(&event).await?;
+            assert!(result.matches.is_empty());
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_and_sharded_evaluation() -> SigmaResult<()> {
+        let mut ruleset = RuleSet::new();
+        for (i, image) in ["cmd.exe", "powershell.exe", "whoami.exe"]
+            .iter()
+            .enumerate()
+        {
+            let rule_yaml = format!(
+                "title: Rule {i}\nid: 12345678-1234-1234-1234-12345678907{i}\n\
+                 detection:\n  selection:\n    Image|endswith: '{image}'\n  condition: selection\n"
+            );
+            ruleset
+                .add_rule(rule_from_yaml(rule_yaml.as_bytes())?)
+                .await?;
+        }
+        ruleset
+            .add_rule(rule_from_yaml(
+                br#"
+        title: Repeated Shell
+        id: 12345678-1234-1234-1234-123456789079
+        detection:
+            selection:
+                Image|endswith: 'cmd.exe'
+            condition: selection | count() > 1
+        "#,
+            )?)
+            .await?;
+
+        let events: Vec<DynamicEvent> = ["C:\\cmd.exe", "C:\\whoami.exe", "C:\\cmd.exe"]
+            .iter()
+            .map(|image| DynamicEvent::new(json!({ "Image": image })))
+            .collect();
+
+        let matched = |result: &RuleSetResult| -> Vec<String> {
+            assert!(result.matches.iter().all(|m| m.matched));
+            result
+                .matches
+                .iter()
+                .map(|m| m.rule_title.clone())
+                .collect()
+        };
+
+        let sync = ruleset.evaluate_sync(&events[1]);
+        assert_eq!(sync.rules_evaluated, 4);
+        assert_eq!(matched(&sync), ["Rule 2"]);
+
+        // Rules are split across threads; aggregation still sees events in order
+        let results = ruleset.evaluate_sharded(&events, 3);
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|result| result.rules_evaluated == 4));
+        assert_eq!(matched(&results[0]), ["Rule 0"]);
+        assert_eq!(matched(&results[1]), ["Rule 2"]);
+        assert_eq!(matched(&results[2]), ["Rule 0", "Repeated Shell"]);
+        let ids: Vec<_> = results[2].matches.iter().map(|m| &m.rule_id).collect();
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_explain() -> SigmaResult<()> {
+        let mut ruleset = RuleSet::new();
+        ruleset
+            .add_rule(rule_from_yaml(
+                br#"
+        title: Encoded PowerShell
+        id: 12345678-1234-1234-1234-123456789080
+        detection:
+            selection_img:
+                Image|endswith: '\powershell.exe'
+            selection_cli:
+                CommandLine|contains:
+                    - ' -enc '
+                    - ' -e '
+            filter:
+                User: SYSTEM
+            condition: all of selection_* and not filter
+        "#,
+            )?)
+            .await?;
+
+        let event = DynamicEvent::new(json!({
+            "Image": "C:\\Windows\\powershell.exe",
+            "CommandLine": "powershell -enc SQBFAFgA",
+            "User": "alice"
+        }));
+
+        let plain = ruleset.evaluate_sync(&event);
+        assert!(plain.matches[0].matched);
+        assert!(plain.matches[0].explanation.is_none());
+
+        let result = ruleset.explain(&event).await?;
+        let explanation = result.matches[0].explanation.as_ref().unwrap();
+        let mut identifiers = explanation.identifiers.clone();
+        identifiers.sort();
+        assert_eq!(identifiers, ["selection_cli", "selection_img"]);
+        assert!(explanation.condition.contains("NOT filter"));
+
+        let command_line = explanation
+            .fields
+            .iter()
+            .find(|f| f.field == "CommandLine")
+            .unwrap();
+        assert_eq!(command_line.value, json!("powershell -enc SQBFAFgA"));
+        assert!(command_line.pattern.contains("-enc"));
+        assert!(explanation.fields.iter().all(|f| f.field != "User"));
+
+        let filtered = DynamicEvent::new(json!({
+            "Image": "C:\\Windows\\powershell.exe",
+            "CommandLine": "powershell -enc SQBFAFgA",
+            "User": "SYSTEM"
+        }));
+        let result = ruleset.explain_sync(&filtered);
         assert!(!result.matches[0].matched);
+        assert!(result.matches[0].explanation.is_none());
         Ok(())
     }
 
@@ -519,6 +1027,266 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_processing_pipeline() -> SigmaResult<()> {
+        let rule = rule_from_yaml(
+            br#"
+        title: Encoded PowerShell
+        id: 12345678-1234-1234-1234-123456789051
+        logsource:
+            product: windows
+            category: process_creation
+        detection:
+            selection:
+                EventID: 1
+                CommandLine|contains: '-enc'
+            condition: selection
+        "#,
+        )?;
+        let pipeline = ProcessingPipeline::from_yaml_str(
+            r#"
+transformations:
+  - type: field_name_mapping
+    mapping:
+      CommandLine: process.command_line
+    rule_conditions:
+      - type: logsource
+        product: windows
+  - type: drop_detection_item
+    field_name_conditions:
+      - type: include_fields
+        fields: [EventID]
+"#,
+        )?;
+
+        let event = DynamicEvent::new(json!({
+            "process": {"command_line": "powershell.exe -enc SQBFAFgA"}
+        }));
+
+        let mut ruleset = RuleSet::new();
+        ruleset.set_pipeline(Arc::new(pipeline));
+        ruleset.add_rule(rule.clone()).await?;
+        assert!(ruleset.evaluate(&event).await?.matches[0].matched);
+
+        // The stored rule keeps its original detection
+        assert!(ruleset.rules[0].rule.detection.get("selection").unwrap()["EventID"].is_number());
+
+        let mut unmapped = RuleSet::new();
+        unmapped.add_rule(rule).await?;
+        assert!(unmapped.evaluate(&event).await?.matches.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_logsource_routing() -> SigmaResult<()> {
+        let rule = |id: &str, logsource: &str| {
+            rule_from_yaml(
+                format!(
+                    "title: Rule {id}\nid: 12345678-1234-1234-1234-12345678906{id}\n\
+                     logsource: {logsource}\n\
+                     detection:\n  selection:\n    User: admin\n  condition: selection\n"
+                )
+                .as_bytes(),
+            )
+        };
+
+        let mut ruleset = RuleSet::new();
+        ruleset
+            .add_rule(rule("0", "{product: windows, category: process_creation}")?)
+            .await?;
+        // Logsource values are compared ignoring case
+        ruleset.add_rule(rule("1", "{product: Windows}")?).await?;
+        ruleset
+            .add_rule(rule("2", "{product: linux, category: process_creation}")?)
+            .await?;
+        ruleset
+            .add_rule(rule(
+                "3",
+                "{product: windows, category: network_connection}",
+            )?)
+            .await?;
+
+        let mut classifier = LogsourceClassifier::new();
+        classifier.add_mapping(
+            Logsource {
+                product: Some("windows".to_string()),
+                category: Some("process_creation".to_string()),
+                ..Default::default()
+            },
+            [
+                ("Channel", vec!["Microsoft-Windows-Sysmon/Operational"]),
+                ("EventID", vec!["1"]),
+            ],
+        );
+        ruleset.set_classifier(Arc::new(classifier));
+        // Rules added after the classifier are routed too
+        ruleset
+            .add_rule(rule("4", "{product: windows, service: sysmon}")?)
+            .await?;
+
+        let sysmon = DynamicEvent::new(json!({
+            "Channel": "Microsoft-Windows-Sysmon/Operational",
+            "EventID": 1,
+            "User": "admin"
+        }));
+        let result = ruleset.evaluate(&sysmon).await?;
+        assert_eq!(result.rules_evaluated, 3);
+        let ids: Vec<_> = result.matches.iter().map(|m| m.rule_id.as_str()).collect();
+        assert_eq!(
+            ids,
+            [
+                "12345678-1234-1234-1234-123456789060",
+                "12345678-1234-1234-1234-123456789061",
+                "12345678-1234-1234-1234-123456789064"
+            ]
+        );
+        assert!(result.matches.iter().all(|m| m.matched));
+
+        // Unclassified events are evaluated against every rule
+        let other = DynamicEvent::new(json!({"User": "admin"}));
+        assert_eq!(ruleset.evaluate(&other).await?.rules_evaluated, 5);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_filter_documents() -> SigmaResult<()> {
+        let rule_yaml = br#"
+        title: Whoami Execution
+        id: 12345678-1234-1234-1234-123456789040
+        name: whoami_execution
+        logsource:
+            product: windows
+            category: process_creation
+        detection:
+            selection:
+                Image|endswith: '\whoami.exe'
+            condition: selection
+        "#;
+        let filter_yaml = br#"
+        title: Exclude Admin Workstations
+        id: 12345678-1234-1234-1234-123456789041
+        logsource:
+            product: windows
+        filter:
+            rules:
+                - whoami_execution
+            selection:
+                ComputerName|startswith: 'ADM-'
+            condition: not selection
+        "#;
+
+        let admin = DynamicEvent::new(json!({
+            "Image": "C:\\Windows\\System32\\whoami.exe",
+            "ComputerName": "ADM-01"
+        }));
+        let workstation = DynamicEvent::new(json!({
+            "Image": "C:\\Windows\\System32\\whoami.exe",
+            "ComputerName": "WS-01"
+        }));
+
+        // Filters apply whether they are loaded before or after their rules
+        for filter_first in [false, true] {
+            let mut ruleset = RuleSet::new();
+            if filter_first {
+                ruleset.add_rule(rule_from_yaml(filter_yaml)?).await?;
+                ruleset.add_rule(rule_from_yaml(rule_yaml)?).await?;
+            } else {
+                ruleset.add_rule(rule_from_yaml(rule_yaml)?).await?;
+                ruleset.add_rule(rule_from_yaml(filter_yaml)?).await?;
+            }
+
+            assert_eq!(ruleset.len(), 1);
+            assert_eq!(ruleset.filter_rules().count(), 1);
+            assert!(ruleset.evaluate(&admin).await?.matches.is_empty());
+            assert!(ruleset.evaluate(&workstation).await?.matches[0].matched);
+        }
+
+        // A filter for another logsource leaves the rule untouched
+        let mut ruleset = RuleSet::new();
+        ruleset.add_rule(rule_from_yaml(rule_yaml)?).await?;
+        let linux_filter = String::from_utf8_lossy(filter_yaml).replace("windows", "linux");
+        ruleset
+            .add_rule(rule_from_yaml(linux_filter.as_bytes())?)
+            .await?;
+        assert!(ruleset.evaluate(&admin).await?.matches[0].matched);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_load_multipart_rule_file() -> SigmaResult<()> {
+        use std::io::Write;
+        use tempfile::NamedTempFile;
+
+        let rule_yaml = r#"
+action: global
+title: Whoami Execution
+detection:
+    condition: selection
+---
+id: 12345678-1234-1234-1234-123456789005
+detection:
+    selection:
+        Image|endswith: '\whoami.exe'
+---
+action: repeat
+id: 12345678-1234-1234-1234-123456789006
+detection:
+    selection:
+        Image|endswith: '/whoami'
+"#;
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(rule_yaml.as_bytes()).unwrap();
+        temp_file.flush().unwrap();
+
+        let mut ruleset = RuleSet::new();
+        ruleset.load_rule_file(temp_file.path()).await?;
+        assert_eq!(ruleset.len(), 2);
+        assert!(ruleset.rules.iter().all(|rule| rule.tree.rule.multipart));
+
+        let event = DynamicEvent::new(json!({"Image": "/usr/bin/whoami"}));
+        let result = ruleset.evaluate(&event).await?;
+        let matched: Vec<_> = result
+            .matches
+            .iter()
+            .filter(|m| m.matched)
+            .map(|m| m.rule_id.as_str())
+            .collect();
+        assert_eq!(matched, ["12345678-1234-1234-1234-123456789006"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_load_multipart_file_with_global_id() -> SigmaResult<()> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/rules/multipart_global_id.yml");
+
+        let mut ruleset = RuleSet::new();
+        ruleset.load_rule_file(&path).await?;
+        assert_eq!(ruleset.len(), 3);
+
+        let id = "4a8e7c2d-9b1f-4e6a-8d3c-5f2b7a9e1c40";
+        for image in [
+            r"C:\Windows\System32\whoami.exe",
+            "/usr/bin/whoami",
+            "/bin/id",
+        ] {
+            let event = DynamicEvent::new(json!({"Image": image}));
+            let result = ruleset.evaluate(&event).await?;
+            let matched: Vec<_> = result
+                .matches
+                .iter()
+                .filter(|m| m.matched)
+                .map(|m| m.rule_id.as_str())
+                .collect();
+            assert_eq!(matched, [id], "{} should match one document", image);
+        }
+
+        // The shared id addresses every document of the file
+        ruleset.set_rule_enabled(id, false).unwrap();
+        assert_eq!(ruleset.get_metadata().enabled_rules, 0);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_file_size_limit() -> SigmaResult<()> {
         use std::io::Write;
@@ -558,6 +1326,56 @@ detection:
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_strict_loading() -> SigmaResult<()> {
+        let rule_yaml = br#"
+        title: Typo Rule
+        id: 12345678-1234-1234-1234-123456789101
+        detection:
+            selection:
+                CommandLine|contians: mimikatz
+            condition: selection
+        "#;
+
+        // Lenient loading treats the typo as an exact match
+        let mut ruleset = RuleSet::new();
+        ruleset.add_rule(rule_from_yaml(rule_yaml)?).await?;
+        assert_eq!(ruleset.len(), 1);
+
+        let mut ruleset = RuleSet::new();
+        ruleset.set_strict(true);
+        match ruleset.add_rule(rule_from_yaml(rule_yaml)?).await {
+            Err(SigmaError::StrictValidation { rule_id, violation }) => {
+                assert_eq!(rule_id, "12345678-1234-1234-1234-123456789101");
+                assert_eq!(
+                    violation,
+                    crate::parser::StrictViolation::UnknownModifier {
+                        identifier: "selection".to_string(),
+                        field: "CommandLine|contians".to_string(),
+                        modifier: "contians".to_string(),
+                    }
+                );
+            }
+            other => panic!("expected strict validation error, got {:?}", other),
+        }
+        assert!(ruleset.is_empty());
+
+        let rule_yaml = br#"
+        title: Valid Rule
+        id: 12345678-1234-1234-1234-123456789102
+        detection:
+            selection:
+                CommandLine|contains|all: ['-enc', 'hidden']
+                ScriptBlockText|re|i: 'invoke-'
+            filter_main:
+                User|exists: false
+            condition: selection and not 1 of filter_*
+        "#;
+        ruleset.add_rule(rule_from_yaml(rule_yaml)?).await?;
+        assert_eq!(ruleset.len(), 1);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_concurrent_evaluation_with_semaphore() -> SigmaResult<()> {
         let mut ruleset = RuleSet::new();
@@ -1,6 +1,6 @@
 use crate::{SigmaEngine, SigmaError};
 use axum::{
-    extract::{DefaultBodyLimit, State},
+    extract::{DefaultBodyLimit, Query, State},
     http::{HeaderMap, Request, StatusCode},
     middleware::{self, Next},
     response::Json,
@@ -62,6 +62,14 @@ pub struct EvaluateRequest {
     event: serde_json::Map<String, serde_json::Value>,
 }
 
+/// Query parameters of the evaluate endpoint
+#[derive(Debug, Default, Deserialize)]
+pub struct EvaluateParams {
+    /// Include an explanation of each match in the response
+    #[serde(default)]
+    explain: bool,
+}
+
 /// Service-specific metrics
 struct ServiceMetrics {
     requests_total: AtomicU64,
@@ -210,6 +218,7 @@ impl SigmaService {
 
     async fn evaluate_handler(
         State(service): State<SigmaService>,
+        Query(params): Query<EvaluateParams>,
         Json(request): Json<EvaluateRequest>,
     ) -> Result<Json<serde_json::Value>, StatusCode> {
         let start_time = std::time::Instant::now();
@@ -221,10 +230,18 @@ impl SigmaService {
         }
 
         // Create a DynamicEvent from the validated input
-        let event = crate::event::DynamicEvent::new(serde_json::Value::Object(request.event));
+        let event = service
+            .engine
+            .event_from_json(serde_json::Value::Object(request.event));
 
         // Evaluate the event against all rules
-        match service.engine.process_event(event).await {
+        let result = if params.explain {
+            service.engine.process_event_explained(event).await
+        } else {
+            service.engine.process_event(event).await
+        };
+
+        match result {
             Ok(result) => {
                 let has_matches = result.matches.iter().any(|m| m.matched);
                 let duration = start_time.elapsed();
@@ -237,12 +254,16 @@ impl SigmaService {
                     .iter()
                     .filter(|m| m.matched)
                     .map(|m| {
-                        serde_json::json!({
+                        let mut rule = serde_json::json!({
                             "rule_id": m.rule_id,
                             "rule_title": m.rule_title,
                             "matched": m.matched,
                             "evaluation_time_ms": m.evaluation_time.as_millis()
-                        })
+                        });
+                        if let Some(explanation) = &m.explanation {
+                            rule["explanation"] = serde_json::json!(explanation);
+                        }
+                        rule
                     })
                     .collect();
 
@@ -334,6 +355,28 @@ pub mod grpc {
         RuleMatch, RuleSummary, StreamEvaluateRequest, StreamEvaluateResponse,
     };
 
+    /// Convert a rule match, placing any explanation in the metadata map
+    fn rule_match_to_proto(m: &crate::RuleMatch) -> RuleMatch {
+        let mut metadata = std::collections::HashMap::new();
+        if let Some(explanation) = &m.explanation {
+            metadata.insert("condition".to_string(), explanation.condition.clone());
+            metadata.insert("identifiers".to_string(), explanation.identifiers.join(","));
+            metadata.insert(
+                "fields".to_string(),
+                serde_json::to_string(&explanation.fields).unwrap_or_default(),
+            );
+        }
+
+        RuleMatch {
+            rule_id: m.rule_id.clone(),
+            rule_title: m.rule_title.clone(),
+            matched: m.matched,
+            evaluation_time_ms: m.evaluation_time.as_millis() as u64,
+            confidence: if m.matched { 1.0 } else { 0.0 },
+            metadata,
+        }
+    }
+
     pub struct SigmaGrpcService {
         engine: Arc<SigmaEngine>,
         start_time: std::time::Instant,
@@ -360,23 +403,19 @@ pub mod grpc {
             let event_value: serde_json::Value = serde_json::from_str(&req.event_json)
                 .map_err(|e| Status::invalid_argument(format!("Invalid JSON: {}", e)))?;
 
-            let event = crate::event::DynamicEvent::new(event_value);
+            let event = self.engine.event_from_json(event_value);
 
             // Evaluate the event
-            match self.engine.process_event(event).await {
+            let result = if req.explain {
+                self.engine.process_event_explained(event).await
+            } else {
+                self.engine.process_event(event).await
+            };
+
+            match result {
                 Ok(result) => {
-                    let matches: Vec<RuleMatch> = result
-                        .matches
-                        .iter()
-                        .map(|m| RuleMatch {
-                            rule_id: m.rule_id.clone(),
-                            rule_title: m.rule_title.clone(),
-                            matched: m.matched,
-                            evaluation_time_ms: m.evaluation_time.as_millis() as u64,
-                            confidence: if m.matched { 1.0 } else { 0.0 },
-                            metadata: std::collections::HashMap::new(),
-                        })
-                        .collect();
+                    let matches: Vec<RuleMatch> =
+                        result.matches.iter().map(rule_match_to_proto).collect();
 
                     let has_matches = matches.iter().any(|m| m.matched);
 
@@ -506,21 +545,24 @@ pub mod grpc {
                             // Parse event
                             match serde_json::from_str::<serde_json::Value>(&request.event_json) {
                                 Ok(event_value) => {
-                                    let event = crate::event::DynamicEvent::new(event_value);
+                                    let event = engine.event_from_json(event_value);
 
                                     // Evaluate
-                                    match engine.process_event(event).await {
+                                    let explain = request
+                                        .metadata
+                                        .get("explain")
+                                        .is_some_and(|v| v == "true");
+                                    let result = if explain {
+                                        engine.process_event_explained(event).await
+                                    } else {
+                                        engine.process_event(event).await
+                                    };
+
+                                    match result {
                                         Ok(result) => {
                                             let matches: Vec<RuleMatch> = result.matches
                                                 .iter()
-                                                .map(|m| RuleMatch {
-                                                    rule_id: m.rule_id.clone(),
-                                                    rule_title: m.rule_title.clone(),
-                                                    matched: m.matched,
-                                                    evaluation_time_ms: m.evaluation_time.as_millis() as u64,
-                                                    confidence: if m.matched { 1.0 } else { 0.0 },
-                                                    metadata: std::collections::HashMap::new(),
-                                                })
+                                                .map(rule_match_to_proto)
                                                 .collect();
 
                                             let has_matches = matches.iter().any(|m| m.matched);
@@ -902,6 +944,54 @@ detection:
         assert!(result["evaluation_time_ms"].is_number());
     }
 
+    #[tokio::test]
+    async fn test_evaluate_endpoint_explain() {
+        let temp_dir = TempDir::new().unwrap();
+        let rule_content = r#"
+title: Whoami Execution
+id: 12345678-1234-1234-1234-123456789001
+logsource:
+    product: windows
+detection:
+    selection:
+        Image|endswith: '\whoami.exe'
+    condition: selection
+"#;
+        std::fs::write(temp_dir.path().join("whoami.yml"), rule_content).unwrap();
+        let engine = SigmaEngineBuilder::new()
+            .add_rule_dir(temp_dir.path().to_string_lossy())
+            .build()
+            .await
+            .unwrap();
+        let app = SigmaService::new(Arc::new(engine)).router();
+
+        let event_data = serde_json::json!({"Image": "C:\\Windows\\whoami.exe"});
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/evaluate?explain=true")
+                    .header("content-type", "application/json")
+                    .body(Body::from(serde_json::to_string(&event_data).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let result: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        let explanation = &result["rules"][0]["explanation"];
+        assert_eq!(explanation["condition"], "selection");
+        assert_eq!(explanation["identifiers"], serde_json::json!(["selection"]));
+        assert_eq!(explanation["fields"][0]["field"], "Image");
+        assert_eq!(explanation["fields"][0]["value"], "C:\\Windows\\whoami.exe");
+    }
+
     #[tokio::test]
     async fn test_evaluate_endpoint_empty_event() {
         let engine = create_test_engine().await;
@@ -1148,6 +1238,7 @@ detection:
         let request = Request::new(EvaluateEventRequest {
             event_json,
             rule_ids: vec![],
+            explain: false,
         });
         let response = service.evaluate_event(request).await.unwrap();
         let result = response.into_inner();
@@ -9,6 +9,9 @@ pub struct EvaluateEventRequest {
     /// Optional rule filter (evaluate only specific rules)
     #[prost(string, repeated, tag = "2")]
     pub rule_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Explain matches in RuleMatch.metadata ("condition", "identifiers", "fields")
+    #[prost(bool, tag = "3")]
+    pub explain: bool,
 }
 /// Response for event evaluation
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -190,7 +193,7 @@ pub struct StreamEvaluateRequest {
     /// Sequence number for ordering
     #[prost(uint64, tag = "2")]
     pub sequence: u64,
-    /// Optional metadata
+    /// Optional metadata; "explain" set to "true" explains matches
     #[prost(map = "string, string", tag = "3")]
     pub metadata: ::std::collections::HashMap<
         ::prost::alloc::string::String,
@@ -5,7 +5,7 @@ use std::sync::Arc;
 
 use crate::ast::{
     nodes::{Identifier, NodeAnd, NodeNot, NodeOr, NodeSimpleAnd, NodeSimpleOr},
-    Branch,
+    SyncBranch,
 };
 use crate::lexer::{Item, Token};
 use crate::parser::{ParseError, Parser};
@@ -35,7 +35,11 @@ pub async fn build_tree(rule: RuleHandle) -> Result<Tree, ParseError> {
         .ok_or_else(|| ParseError::MissingCondition)?;
 
     // Create parser with detection
-    let mut parser = Parser::new(rule.rule.detection.clone(), rule.no_collapse_ws);
+    let mut parser =
+        Parser::new(rule.rule.detection.clone(), rule.no_collapse_ws).with_strict(rule.strict);
+    if let Some(placeholders) = &rule.placeholders {
+        parser = parser.with_placeholders(placeholders.clone());
+    }
 
     // Run parser with rule context for better error messages
     parser.run().await.map_err(|e| {
@@ -47,6 +51,9 @@ pub async fn build_tree(rule: RuleHandle) -> Result<Tree, ParseError> {
             ParseError::NoValidFieldPatterns { field, errors, .. } => {
                 ParseError::no_valid_field_patterns(&rule.rule.id, field, errors)
             }
+            ParseError::StrictValidation { violation, .. } => {
+                ParseError::strict_validation(&rule.rule.id, violation)
+            }
             ParseError::FieldPatternCreationFailed {
                 field,
                 value,
@@ -99,8 +106,12 @@ pub async fn build_tree(rule: RuleHandle) -> Result<Tree, ParseError> {
         .result()
         .ok_or_else(|| ParseError::MissingCondition)?;
 
-    // Create tree with root and rule
-    Ok(Tree::new(root, Arc::new(rule)))
+    // Create tree with root and rule, attaching any aggregation after the pipe
+    let tree = Tree::new(root, Arc::new(rule));
+    Ok(match parser.aggregation() {
+        Some(aggregation) => tree.with_aggregation(aggregation),
+        None => tree,
+    })
 }
 
 /// Build a branch from token sequence
@@ -109,7 +120,18 @@ pub fn build_branch(
     tokens: Vec<Item>,
     depth: usize,
     no_collapse_ws: bool,
-) -> Result<Arc<dyn Branch>, ParseError> {
+) -> Result<Arc<dyn SyncBranch>, ParseError> {
+    build_branch_strict(detection, tokens, depth, no_collapse_ws, false)
+}
+
+/// Build a branch from token sequence, rejecting unknown modifiers when `strict`
+pub fn build_branch_strict(
+    detection: &Detection,
+    tokens: Vec<Item>,
+    depth: usize,
+    no_collapse_ws: bool,
+    strict: bool,
+) -> Result<Arc<dyn SyncBranch>, ParseError> {
     // Check recursion depth to prevent stack overflow
     if depth > MAX_RECURSION_DEPTH {
         return Err(ParseError::RecursionLimitExceeded {
@@ -120,8 +142,8 @@ pub fn build_branch(
 
     let mut iter = tokens.into_iter().peekable();
 
-    let mut and_nodes: Vec<Arc<dyn Branch>> = Vec::with_capacity(INITIAL_AND_CAPACITY);
-    let mut or_nodes: Vec<Arc<dyn Branch>> = Vec::with_capacity(INITIAL_OR_CAPACITY);
+    let mut and_nodes: Vec<Arc<dyn SyncBranch>> = Vec::with_capacity(INITIAL_AND_CAPACITY);
+    let mut or_nodes: Vec<Arc<dyn SyncBranch>> = Vec::with_capacity(INITIAL_OR_CAPACITY);
     let mut negated = false;
     let mut wildcard = None;
 
@@ -136,7 +158,8 @@ pub fn build_branch(
                         })?;
 
                 let ident_type = identify_type(&item.value, value);
-                let branch = build_rule_from_ident(value, ident_type, no_collapse_ws)?;
+                let branch =
+                    build_rule_from_ident(&item.value, value, ident_type, no_collapse_ws, strict)?;
 
                 and_nodes.push(if negated {
                     Arc::new(NodeNot::new(branch))
@@ -164,7 +187,8 @@ pub fn build_branch(
             Token::SepLpar => {
                 // Extract group and build recursively
                 let group_items = extract_group_items(&mut iter)?;
-                let branch = build_branch(detection, group_items, depth + 1, no_collapse_ws)?;
+                let branch =
+                    build_branch_strict(detection, group_items, depth + 1, no_collapse_ws, strict)?;
 
                 and_nodes.push(if negated {
                     Arc::new(NodeNot::new(branch))
@@ -176,8 +200,12 @@ pub fn build_branch(
 
             Token::IdentifierAll => {
                 let rules = match wildcard {
-                    Some(Token::StmtAllOf) => extract_all_to_rules(detection, no_collapse_ws)?,
-                    Some(Token::StmtOneOf) => extract_all_to_rules(detection, no_collapse_ws)?,
+                    Some(Token::StmtAllOf) => {
+                        extract_all_to_rules(detection, no_collapse_ws, strict)?
+                    }
+                    Some(Token::StmtOneOf) => {
+                        extract_all_to_rules(detection, no_collapse_ws, strict)?
+                    }
                     _ => return Err(ParseError::InvalidWildcardIdent),
                 };
 
@@ -210,10 +238,10 @@ pub fn build_branch(
 
                 let rules = match wildcard {
                     Some(Token::StmtAllOf) => {
-                        extract_wildcard_idents(detection, &glob, no_collapse_ws)?
+                        extract_wildcard_idents(detection, &glob, no_collapse_ws, strict)?
                     }
                     Some(Token::StmtOneOf) => {
-                        extract_wildcard_idents(detection, &glob, no_collapse_ws)?
+                        extract_wildcard_idents(detection, &glob, no_collapse_ws, strict)?
                     }
                     _ => return Err(ParseError::InvalidWildcardIdent),
                 };
@@ -286,9 +314,9 @@ enum BranchType {
 }
 
 fn reduce_branches(
-    mut branches: Vec<Arc<dyn Branch>>,
+    mut branches: Vec<Arc<dyn SyncBranch>>,
     branch_type: BranchType,
-) -> Result<Arc<dyn Branch>, ParseError> {
+) -> Result<Arc<dyn SyncBranch>, ParseError> {
     match branches.len() {
         0 => Err(ParseError::InvalidBranchStructure {
             message: "Cannot reduce empty branch list".to_string(),
@@ -386,12 +414,13 @@ fn validate_glob_pattern(pattern: &str) -> Result<(), ParseError> {
 fn extract_all_to_rules(
     detection: &Detection,
     no_collapse_ws: bool,
-) -> Result<Vec<Arc<dyn Branch>>, ParseError> {
+    strict: bool,
+) -> Result<Vec<Arc<dyn SyncBranch>>, ParseError> {
     let mut rules = Vec::new();
 
     for (key, value) in detection.iter() {
         let ident_type = identify_type(key, value);
-        let branch = build_rule_from_ident(value, ident_type, no_collapse_ws)?;
+        let branch = build_rule_from_ident(key, value, ident_type, no_collapse_ws, strict)?;
         rules.push(branch);
     }
 
@@ -402,11 +431,20 @@ fn extract_wildcard_idents(
     detection: &Detection,
     glob: &globset::GlobMatcher,
     no_collapse_ws: bool,
-) -> Result<Vec<Arc<dyn Branch>>, ParseError> {
+    strict: bool,
+) -> Result<Vec<Arc<dyn SyncBranch>>, ParseError> {
     let rules: Result<Vec<_>, _> = detection
         .iter()
         .filter(|(key, _)| glob.is_match(key))
-        .map(|(_, value)| build_rule_from_ident(value, IdentifierType::Selection, no_collapse_ws))
+        .map(|(key, value)| {
+            build_rule_from_ident(
+                key,
+                value,
+                IdentifierType::Selection,
+                no_collapse_ws,
+                strict,
+            )
+        })
         .collect();
 
     let rules = rules?;
@@ -427,10 +465,12 @@ fn identify_type(_key: &str, value: &serde_json::Value) -> IdentifierType {
 }
 
 fn build_rule_from_ident(
+    ident: &str,
     value: &serde_json::Value,
     ident_type: IdentifierType,
     _no_collapse_ws: bool,
-) -> Result<Arc<dyn Branch>, ParseError> {
+    strict: bool,
+) -> Result<Arc<dyn SyncBranch>, ParseError> {
     match ident_type {
         IdentifierType::Keywords => {
             // Handle keyword list
@@ -466,28 +506,30 @@ fn build_rule_from_ident(
                         }
                     };
                     tracing::error!("Processing single field: key={}, val={:?}", key, val);
-                    let (field_name, modifier) = parse_field_key(key);
+                    let (field_name, modifier, lowercase) =
+                        parse_field_key(ident, key, val, strict)?;
                     tracing::error!("Parsed field: name={}, modifier={:?}", field_name, modifier);
-                    let pattern = create_field_pattern_with_modifier(val, modifier)?;
+                    let pattern = create_field_pattern_with_modifier(val, modifier, lowercase)?;
                     let field_rule = crate::ast::FieldRule::new(Arc::from(field_name), pattern);
                     return Ok(Arc::new(Identifier::from_rule(field_rule)));
                 }
 
                 // For multiple fields, create an AND of field rules
                 tracing::error!("Creating AND of multiple field rules");
-                let branches: Vec<Arc<dyn Branch>> = obj
+                let branches: Vec<Arc<dyn SyncBranch>> = obj
                     .iter()
                     .map(|(key, val)| {
                         tracing::error!("Processing field: key={}, val={:?}", key, val);
-                        let (field_name, modifier) = parse_field_key(key);
+                        let (field_name, modifier, lowercase) =
+                            parse_field_key(ident, key, val, strict)?;
                         tracing::error!(
                             "Parsed field: name={}, modifier={:?}",
                             field_name,
                             modifier
                         );
-                        let pattern = create_field_pattern_with_modifier(val, modifier)?;
+                        let pattern = create_field_pattern_with_modifier(val, modifier, lowercase)?;
                         let field_rule = crate::ast::FieldRule::new(Arc::from(field_name), pattern);
-                        Ok(Arc::new(Identifier::from_rule(field_rule)) as Arc<dyn Branch>)
+                        Ok(Arc::new(Identifier::from_rule(field_rule)) as Arc<dyn SyncBranch>)
                     })
                     .collect::<Result<Vec<_>, ParseError>>()?;
 
@@ -499,27 +541,58 @@ fn build_rule_from_ident(
     }
 }
 
-fn parse_field_key(key: &str) -> (String, Option<crate::pattern::TextPatternModifier>) {
-    if let Some(pos) = key.find('|') {
-        let field = key[..pos].to_string();
-        let modifier_str = &key[pos + 1..];
-        let modifier = match modifier_str {
-            "contains" => Some(crate::pattern::TextPatternModifier::Contains),
-            "startswith" => Some(crate::pattern::TextPatternModifier::Prefix),
-            "endswith" => Some(crate::pattern::TextPatternModifier::Suffix),
-            "re" => Some(crate::pattern::TextPatternModifier::Regex),
-            "all" => Some(crate::pattern::TextPatternModifier::All),
-            _ => None,
-        };
-        (field, modifier)
-    } else {
-        (key.to_string(), None)
+/// Split a field key into its name, matching mode and case folding
+///
+/// Modifiers are read like the rule parser reads them. Unknown modifiers are
+/// rejected in strict mode, and known ones this builder cannot apply are
+/// always an error rather than silently dropped.
+fn parse_field_key(
+    ident: &str,
+    key: &str,
+    value: &serde_json::Value,
+    strict: bool,
+) -> Result<(String, Option<crate::pattern::TextPatternModifier>, bool), ParseError> {
+    let (field, modifiers) = crate::parser::parse_field_modifier(key);
+
+    if strict {
+        if let Some(modifier) = modifiers.unknown.first() {
+            return Err(ParseError::strict_validation(
+                "",
+                crate::parser::StrictViolation::UnknownModifier {
+                    identifier: ident.to_string(),
+                    field: key.to_string(),
+                    modifier: modifier.clone(),
+                },
+            ));
+        }
+    }
+
+    let unsupported = !modifiers.transforms.is_empty()
+        || modifiers.comparison.is_some()
+        || modifiers.time_part.is_some()
+        || modifiers.regex_flags != Default::default()
+        || modifiers.exists
+        || modifiers.fieldref
+        || modifiers.expand;
+    if unsupported {
+        return Err(ParseError::field_pattern_creation_failed(
+            key,
+            value.to_string(),
+            "modifiers not supported by the branch builder; build the tree with build_tree",
+        ));
     }
+
+    let modifier = match modifiers.modifier {
+        None if modifiers.all => Some(crate::pattern::TextPatternModifier::All),
+        modifier => modifier,
+    };
+    Ok((field.to_string(), modifier, !modifiers.cased))
 }
 
 fn create_field_pattern_with_modifier(
     value: &serde_json::Value,
     modifier: Option<crate::pattern::TextPatternModifier>,
+    lowercase: bool,
 ) -> Result<crate::ast::FieldPattern, ParseError> {
     use crate::pattern::{new_num_matcher, new_string_matcher, TextPatternModifier};
     use std::sync::Arc;
@@ -537,7 +610,7 @@ fn create_field_pattern_with_modifier(
 
             let matcher = new_string_matcher(
                 modifier,
-                false, // lowercase
+                lowercase,
                 false, // all
                 false, // no_collapse_ws
                 vec![s.clone()],
@@ -554,15 +627,28 @@ fn create_field_pattern_with_modifier(
                 let matcher = new_num_matcher(vec![num])
                     .map_err(|e| ParseError::UnsupportedValueType { value_type: e })?;
 
+                Ok(crate::ast::FieldPattern::Numeric {
+                    matcher: Arc::from(matcher),
+                    pattern_desc: Arc::from(n.to_string()),
+                })
+            } else if let Some(bound) = n.as_f64().filter(|_| n.is_f64()) {
+                // Compare floats by value, coercing events like the comparison modifiers
+                let matcher = crate::pattern::new_num_comparison_matcher(
+                    crate::pattern::NumComparison::Equal,
+                    false,
+                    vec![bound],
+                )
+                .map_err(|e| ParseError::UnsupportedValueType { value_type: e })?;
+
                 Ok(crate::ast::FieldPattern::Numeric {
                     matcher: Arc::from(matcher),
                     pattern_desc: Arc::from(n.to_string()),
                 })
             } else {
-                // Fall back to string matching for floats
+                // Fall back to string matching for integers beyond i64
                 let matcher = new_string_matcher(
                     TextPatternModifier::None,
-                    false, // lowercase
+                    lowercase,
                     false, // all
                     false, // no_collapse_ws
                     vec![n.to_string()],
@@ -578,7 +664,7 @@ fn create_field_pattern_with_modifier(
         serde_json::Value::Bool(b) => {
             let matcher = new_string_matcher(
                 TextPatternModifier::None,
-                false, // lowercase
+                lowercase,
                 false, // all
                 false, // no_collapse_ws
                 vec![b.to_string()],
@@ -590,21 +676,8 @@ fn create_field_pattern_with_modifier(
                 pattern_desc: Arc::from(b.to_string()),
             })
         }
-        serde_json::Value::Null => {
-            let matcher = new_string_matcher(
-                TextPatternModifier::None,
-                false, // lowercase
-                false, // all
-                false, // no_collapse_ws
-                vec!["null".to_string()],
-            )
-            .map_err(|e| ParseError::UnsupportedValueType { value_type: e })?;
-
-            Ok(crate::ast::FieldPattern::String {
-                matcher: Arc::from(matcher),
-                pattern_desc: Arc::from("null"),
-            })
-        }
+        // null matches a missing or null field
+        serde_json::Value::Null => Ok(crate::ast::FieldPattern::Exists(false)),
         _ => Err(ParseError::UnsupportedValueType {
             value_type: format!("{:?}", value),
         }),
@@ -683,6 +756,51 @@ detection:
         }
     }
 
+    #[test]
+    fn test_branch_field_modifiers() {
+        let branch_for = |key: &str, strict: bool| {
+            let mut detection = Detection::new();
+            detection.insert("selection".to_string(), serde_json::json!({ key: "value" }));
+            let tokens = vec![Item::new(Token::Identifier, "selection".to_string())];
+            build_branch_strict(&detection, tokens, 0, false, strict)
+        };
+
+        assert!(branch_for("CommandLine|contains|all", true).is_ok());
+        assert!(branch_for("CommandLine|cased", true).is_ok());
+
+        // Unknown modifiers are only rejected in strict mode
+        assert!(branch_for("CommandLine|contians", false).is_ok());
+        match branch_for("CommandLine|contians", true) {
+            Err(ParseError::StrictValidation { violation, .. }) => assert_eq!(
+                violation,
+                crate::parser::StrictViolation::UnknownModifier {
+                    identifier: "selection".to_string(),
+                    field: "CommandLine|contians".to_string(),
+                    modifier: "contians".to_string(),
+                }
+            ),
+            other => panic!("Expected strict validation error, got {:?}", other.err()),
+        }
+
+        // Known modifiers the builder cannot apply never degrade to plain matches
+        for key in [
+            "CommandLine|base64offset|contains",
+            "CommandLine|windash|contains",
+            "Size|lt",
+            "User|fieldref",
+            "Image|re|i",
+        ] {
+            assert!(
+                matches!(
+                    branch_for(key, false),
+                    Err(ParseError::FieldPatternCreationFailed { .. })
+                ),
+                "{}",
+                key
+            );
+        }
+    }
+
     #[test]
     fn test_glob_pattern_validation() {
         // Test pattern with too many wildcards
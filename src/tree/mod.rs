@@ -1,6 +1,10 @@
 use std::sync::Arc;
 
-use crate::ast::Branch;
+use tracing::warn;
+
+use crate::aggregation::{AggregationConfig, AggregationEvaluator, AggregationFunction};
+use crate::ast::nodes::{NodeAggregation, NodeAnd};
+use crate::ast::{Explanation, SyncBranch};
 use crate::rule::RuleHandle;
 
 /// Tree builder module for constructing AST from rules
@@ -9,26 +13,111 @@ pub mod builder;
 pub use builder::build_tree;
 
 /// Tree represents the full AST for a sigma rule
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Tree {
     /// Root node of the AST
-    pub root: Arc<dyn Branch>,
+    pub root: Arc<dyn SyncBranch>,
     /// Associated rule handle
     pub rule: Arc<RuleHandle>,
+    /// Aggregation applied to events matching the root, if the condition has one
+    pub aggregation: Option<Arc<NodeAggregation>>,
+    /// Per-rule aggregation state
+    evaluator: Option<Arc<AggregationEvaluator>>,
 }
 
 impl Tree {
     /// Create a new Tree with the given root branch and rule handle
-    pub fn new(root: Arc<dyn Branch>, rule: Arc<RuleHandle>) -> Self {
-        Self { root, rule }
+    pub fn new(root: Arc<dyn SyncBranch>, rule: Arc<RuleHandle>) -> Self {
+        Self {
+            root,
+            rule,
+            aggregation: None,
+            evaluator: None,
+        }
+    }
+
+    /// Attach an aggregation that must trigger before the tree reports a match
+    pub fn with_aggregation(mut self, aggregation: Arc<NodeAggregation>) -> Self {
+        self.aggregation = Some(aggregation);
+        self.evaluator = Some(Arc::new(AggregationEvaluator::new()));
+        self
+    }
+
+    /// Replace the aggregation state with a fresh one using the given limits
+    ///
+    /// Has no effect on trees without an aggregation.
+    pub fn with_aggregation_config(mut self, config: AggregationConfig) -> Self {
+        let Some(aggregation) = &self.aggregation else {
+            return self;
+        };
+
+        let limit = match aggregation.function {
+            AggregationFunction::Count if aggregation.time_window.is_some() => {
+                Some(config.max_window_entries_per_group)
+            }
+            AggregationFunction::CountDistinct(_) => Some(config.max_distinct_values_per_group),
+            _ => None,
+        };
+        if let Some(limit) = limit.filter(|limit| aggregation.threshold >= *limit as f64) {
+            warn!(
+                "Rule '{}' aggregates up to {} but its threshold is {}; raise the aggregation limits for it to fire",
+                self.rule.rule.id, limit, aggregation.threshold
+            );
+        }
+
+        self.evaluator = Some(Arc::new(AggregationEvaluator::with_config(config)));
+        self
+    }
+
+    /// AND an additional condition, such as a filter document's, into the root
+    ///
+    /// Aggregation state is shared with the original tree.
+    pub fn with_filter(mut self, filter: Arc<dyn SyncBranch>) -> Self {
+        self.root = Arc::new(NodeAnd::new(self.root, filter));
+        self
     }
 
     /// Match implements the Matcher interface
     pub async fn match_event(&self, event: &dyn crate::event::Event) -> (bool, bool) {
-        let result = self.root.matches(event).await;
+        self.match_event_sync(event)
+    }
+
+    /// Match an event, returning (matched, applicable), without awaiting
+    pub fn match_event_sync(&self, event: &dyn crate::event::Event) -> (bool, bool) {
+        let result = self.root.matches_sync(event);
+
+        // Only events matching the condition feed the aggregation state
+        if let (true, Some(node), Some(evaluator)) =
+            (result.matched, &self.aggregation, &self.evaluator)
+        {
+            let aggregated = evaluator.evaluate_sync(node, event);
+            return (aggregated.triggered, result.applicable);
+        }
+
         (result.matched, result.applicable)
     }
 
+    /// Match an event like [`Tree::match_event_sync`], also explaining a match
+    ///
+    /// The explanation is only returned when the tree reports a match, so an
+    /// event satisfying the condition without triggering its aggregation has none.
+    pub fn explain_event(
+        &self,
+        event: &dyn crate::event::Event,
+    ) -> (bool, bool, Option<Explanation>) {
+        let (result, explanation) = self.root.explain(event);
+
+        if let (true, Some(node), Some(evaluator)) =
+            (result.matched, &self.aggregation, &self.evaluator)
+        {
+            let aggregated = evaluator.evaluate_sync(node, event);
+            let explanation = explanation.filter(|_| aggregated.triggered);
+            return (aggregated.triggered, result.applicable, explanation);
+        }
+
+        (result.matched, result.applicable, explanation)
+    }
+
     /// Evaluate an event against this tree, returning a Result if it matches
     pub async fn eval(
         &self,
@@ -70,6 +159,7 @@ mod tests {
         // Create a simple rule
         let rule = Rule {
             id: "12345678-1234-1234-1234-123456789007".to_string(),
+            name: None,
             title: "Test Rule".to_string(),
             description: Some("Test Description".to_string()),
             author: None,
@@ -82,6 +172,8 @@ mod tests {
             fields: vec![],
             logsource: Logsource::default(),
             detection: Detection::new(),
+            correlation: None,
+            filter: None,
             tags: vec!["attack.discovery".to_string()],
         };
 
@@ -56,7 +56,11 @@ async fn test_lexer_cases() {
                 Token::StmtOneOf,
                 Token::IdentifierWithWildcard,
                 Token::SepPipe,
-                Token::Unsupported,
+                Token::KeywordAgg,
+                Token::SepLpar,
+                Token::SepRpar,
+                Token::OpGt,
+                Token::LitNumber,
                 Token::LitEof,
             ],
         },
@@ -204,7 +208,19 @@ async fn test_pipe_aggregation() {
     let item = rx.recv().await.unwrap();
     assert_eq!(item.token, Token::SepPipe);
 
-    // The rest should be unsupported for now
     let item = rx.recv().await.unwrap();
-    assert_eq!(item.token, Token::Unsupported);
+    assert_eq!(item.token, Token::KeywordAgg);
+    assert_eq!(item.value, "count");
+
+    let item = rx.recv().await.unwrap();
+    assert_eq!(item.token, Token::SepLpar);
+    let item = rx.recv().await.unwrap();
+    assert_eq!(item.token, Token::SepRpar);
+
+    let item = rx.recv().await.unwrap();
+    assert_eq!(item.token, Token::OpGt);
+
+    let item = rx.recv().await.unwrap();
+    assert_eq!(item.token, Token::LitNumber);
+    assert_eq!(item.value, "10");
 }
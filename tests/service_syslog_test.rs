@@ -0,0 +1,86 @@
+use std::io::{BufRead, BufReader};
+use std::net::UdpSocket;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+const RULE: &str = r#"
+title: Failed SSH Login
+id: 5d7a0c33-1f2b-4a5e-9c1d-2e3f4a5b6c7d
+logsource:
+  product: linux
+  service: sshd
+detection:
+  selection:
+    appname: sshd
+    message|contains: 'Failed password'
+  condition: selection
+level: medium
+"#;
+
+/// Kills the service when the test ends
+struct Service(Child);
+
+impl Drop for Service {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// Wait for a log line containing `needle`
+fn wait_for(lines: &mpsc::Receiver<String>, needle: &str) -> String {
+    let deadline = Instant::now() + Duration::from_secs(30);
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match lines.recv_timeout(remaining) {
+            Ok(line) if line.contains(needle) => return line,
+            Ok(_) => {}
+            Err(e) => panic!("service never logged {:?}: {}", needle, e),
+        }
+    }
+}
+
+#[test]
+fn test_service_evaluates_syslog_messages() {
+    let rules = tempfile::tempdir().unwrap();
+    std::fs::write(rules.path().join("ssh.yml"), RULE).unwrap();
+
+    let addr = UdpSocket::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let mut service = Service(
+        Command::new(env!("CARGO_BIN_EXE_sigma-rs-service"))
+            .arg("--rules")
+            .arg(rules.path())
+            .args(["--no-http", "--no-grpc", "--syslog-udp", &addr.to_string()])
+            .env("NO_COLOR", "1")
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap(),
+    );
+
+    let stdout = service.0.stdout.take().unwrap();
+    let (sender, lines) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    wait_for(&lines, "Service ready");
+
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket
+        .send_to(
+            b"<38>1 - host1 sshd 4242 - - Failed password for root from 10.0.0.5",
+            addr,
+        )
+        .unwrap();
+
+    let alert = wait_for(&lines, "Rule matched");
+    assert!(alert.contains("Failed SSH Login"));
+    assert!(alert.contains("10.0.0.5"));
+}